@@ -0,0 +1,35 @@
+//! 比较 `Registry::get` 与 `Registry::cached_get` 读取同一个键的开销
+//!
+//! 运行：`cargo bench --bench cached_get`
+
+use std::{hint::black_box, time::Instant};
+
+use gom::Registry;
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn bench(label: &str, mut f: impl FnMut() -> Option<u64>) {
+    for _ in 0..ITERATIONS / 10 {
+        black_box(f());
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<24} {:>8.1} ns/iter",
+        label,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
+
+fn main() {
+    for i in 0..1000u64 {
+        Registry::register(&format!("bench.key.{}", i), i).unwrap();
+    }
+    let name = "bench.key.500";
+
+    bench("Registry::get", || Registry::<u64>::get(name));
+    bench("Registry::cached_get", || Registry::<u64>::cached_get(name));
+}
@@ -0,0 +1,62 @@
+//! 多个线程同时注册并移除同一类型下互不相同的键时的吞吐量
+//!
+//! 同一类型的键分布在多个分片中，不同的键通常不会争用同一把锁；每个线程使用各自的类型时没有任何争用，作为对照
+//!
+//! 运行：`cargo bench --bench contention`
+
+use std::{any::Any, hint::black_box, thread, time::Instant};
+
+use gom::Registry;
+
+const THREADS: usize = 8;
+const ITERATIONS: usize = 200_000;
+
+struct Shared;
+
+struct Own<const N: usize>;
+
+fn worker<T: 'static + Send + Sync + Any>(thread: usize, value: fn() -> T) {
+    let names: Vec<_> = (0..64).map(|i| format!("bench.{}.{}", thread, i)).collect();
+    for i in 0..ITERATIONS {
+        let name = &names[i % names.len()];
+        Registry::register(name, value()).unwrap();
+        black_box(Registry::<T>::remove(name));
+    }
+}
+
+fn bench(label: &str, threads: usize, spawn: impl Fn(usize) -> thread::JoinHandle<()>) {
+    let start = Instant::now();
+    let workers: Vec<_> = (0..threads).map(&spawn).collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    let elapsed = start.elapsed();
+    let operations = (threads * ITERATIONS * 2) as f64;
+    println!(
+        "{:<28} {:>8.1} ns/op {:>10.0} ops/s",
+        label,
+        elapsed.as_nanos() as f64 / operations,
+        operations / elapsed.as_secs_f64()
+    );
+}
+
+fn main() {
+    bench("1 thread", 1, |t| {
+        thread::spawn(move || worker(t, || Shared))
+    });
+    bench("8 threads, one type", THREADS, |t| {
+        thread::spawn(move || worker(t, || Shared))
+    });
+    bench("8 threads, one type each", THREADS, |t| {
+        thread::spawn(move || match t {
+            0 => worker(t, || Own::<0>),
+            1 => worker(t, || Own::<1>),
+            2 => worker(t, || Own::<2>),
+            3 => worker(t, || Own::<3>),
+            4 => worker(t, || Own::<4>),
+            5 => worker(t, || Own::<5>),
+            6 => worker(t, || Own::<6>),
+            _ => worker(t, || Own::<7>),
+        })
+    });
+}
@@ -0,0 +1,44 @@
+//! 批量注册大量键时，`Registry::extend` 与逐个调用 `Registry::register` 的开销
+//!
+//! 运行：`cargo bench --bench extend`
+
+use std::{hint::black_box, time::Instant};
+
+use gom::Registry;
+
+const ROUNDS: usize = 100;
+const BATCH: usize = 10_000;
+
+fn bench(label: &str, mut f: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+        // 每轮结束后清空，从而每轮都注册新键
+        black_box(Registry::<u64>::clear());
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<20} {:>8.1} ns/key",
+        label,
+        elapsed.as_nanos() as f64 / (ROUNDS * BATCH) as f64
+    );
+}
+
+fn main() {
+    let names: Vec<_> = (0..BATCH).map(|i| format!("bench.key.{}", i)).collect();
+
+    bench("Registry::register", || {
+        for (i, name) in names.iter().enumerate() {
+            Registry::register(name, i as u64).unwrap();
+        }
+    });
+    bench("Registry::extend", || {
+        let report = Registry::<u64>::extend(
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.clone(), i as u64)),
+        );
+        assert_eq!(report.inserted, BATCH);
+    });
+}
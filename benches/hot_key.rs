@@ -0,0 +1,89 @@
+//! 比较 `Registry::with` 与 `HotKey::with` 访问同一个键的开销，以及多个线程同时以 `Registry::with` 访问同一个键时的开销；
+//! 最后在另一个线程反复创建并回收其他类型对应的表的同时再次测量 `Registry::with` 与 `Registry::exists`
+//!
+//! 运行：`cargo bench --bench hot_key`
+
+use std::{
+    hint::black_box,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Instant,
+};
+
+use gom::Registry;
+
+const ITERATIONS: u32 = 1_000_000;
+const THREADS: u32 = 4;
+
+// 仅由后台线程注册并移除，每次移除其最后一个键时该类型对应的表都会被回收
+struct Churn;
+
+fn bench(label: &str, mut f: impl FnMut() -> Option<u64>) {
+    for _ in 0..ITERATIONS / 10 {
+        black_box(f());
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<24} {:>8.1} ns/iter",
+        label,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
+
+fn main() {
+    for i in 0..1000u64 {
+        Registry::register(&format!("bench.key.{}", i), i).unwrap();
+    }
+    let name = "bench.key.500";
+    let hot = Registry::<u64>::hot_key(name);
+
+    bench("Registry::with", || Registry::<u64>::with(name, |v| *v));
+    bench("HotKey::with", || hot.with(|v| *v));
+
+    let start = Instant::now();
+    let workers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            thread::spawn(move || {
+                for _ in 0..ITERATIONS {
+                    black_box(Registry::<u64>::with(name, |v| *v));
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    println!(
+        "{:<24} {:>8.1} ns/iter ({} threads)",
+        "Registry::with",
+        start.elapsed().as_nanos() as f64 / (ITERATIONS * THREADS) as f64,
+        THREADS
+    );
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let churn = {
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                Registry::register("bench.churn", Churn).unwrap();
+                Registry::<Churn>::remove("bench.churn");
+                thread::yield_now();
+            }
+        })
+    };
+    bench("Registry::with (churn)", || {
+        Registry::<u64>::with(name, |v| *v)
+    });
+    bench("Registry::exists (churn)", || {
+        Some(Registry::<u64>::exists(name) as u64)
+    });
+    stop.store(true, Ordering::Relaxed);
+    churn.join().unwrap();
+}
@@ -0,0 +1,44 @@
+//! 单线程注册并移除键的吞吐量，覆盖新键、覆盖已存在的键与类型首次注册三种情况
+//!
+//! 运行：`cargo bench --bench register`
+
+use std::{hint::black_box, time::Instant};
+
+use gom::Registry;
+
+const ITERATIONS: usize = 1_000_000;
+
+fn bench(label: &str, mut f: impl FnMut(usize)) {
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        f(i);
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<28} {:>8.1} ns/iter",
+        label,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
+
+fn main() {
+    let names: Vec<_> = (0..1024).map(|i| format!("bench.key.{}", i)).collect();
+
+    bench("register + remove", |i| {
+        let name = &names[i % names.len()];
+        Registry::register(name, i as u64).unwrap();
+        black_box(Registry::<u64>::remove(name));
+    });
+    bench("register (overwrite)", |i| {
+        black_box(Registry::register(&names[0], i as u64)).unwrap();
+    });
+    bench("replace", |i| {
+        black_box(Registry::<u64>::replace(&names[0], i as u64));
+    });
+    bench("register + remove (new type)", |i| {
+        let name = &names[i % names.len()];
+        Registry::register(name, i as u32).unwrap();
+        // 连同该类型对应的表一起取出，下一次注册时重新创建
+        black_box(Registry::<u32>::take_map());
+    });
+}
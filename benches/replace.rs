@@ -0,0 +1,60 @@
+//! 统计 `Registry::replace` 与 `Registry::register` 覆盖已存在的键时的内存分配次数与开销
+//!
+//! 运行：`cargo bench --bench replace`
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use gom::Registry;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const ITERATIONS: u64 = 1_000_000;
+
+fn bench(label: &str, mut f: impl FnMut(u64)) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        f(i);
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!(
+        "{:<20} {:>8.1} ns/iter {:>6.2} allocs/iter",
+        label,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64,
+        allocations as f64 / ITERATIONS as f64
+    );
+}
+
+fn main() {
+    let name = "bench.a.rather.long.key.name";
+    Registry::register(name, 0u64).unwrap();
+
+    bench("Registry::replace", |i| {
+        black_box(Registry::<u64>::replace(name, i));
+    });
+    bench("Registry::register", |i| {
+        black_box(Registry::<u64>::register(name, i)).unwrap();
+    });
+}
@@ -0,0 +1,38 @@
+//! 比较 `Registry::with` 与 `Registry::with_slot` 访问同一个键的开销
+//!
+//! 运行：`cargo bench --bench slot`
+
+use std::{hint::black_box, time::Instant};
+
+use gom::Registry;
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn bench(label: &str, mut f: impl FnMut() -> Option<u64>) {
+    for _ in 0..ITERATIONS / 10 {
+        black_box(f());
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<20} {:>8.1} ns/iter",
+        label,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
+
+fn main() {
+    for i in 0..1000u64 {
+        Registry::register(&format!("bench.key.{}", i), i).unwrap();
+    }
+    let name = "bench.key.500";
+    let slot = Registry::<u64>::slot_of(name).unwrap();
+
+    bench("Registry::with", || Registry::<u64>::with(name, |v| *v));
+    bench("Registry::with_slot", || {
+        Registry::<u64>::with_slot(slot, |v| *v)
+    });
+}
@@ -8,17 +8,17 @@ pub struct Note {
 }
 
 fn note(text: &str) -> String {
-    if !Registry::<Note>::exists(NOTE) {
-        let note = Note {
+    Registry::<Note>::get_or_register_with(
+        NOTE,
+        || Note {
             text: Default::default(),
-        };
-        Registry::register(NOTE, note).unwrap();
-    }
-    Registry::<Note>::apply(NOTE, |t| {
-        let ret = t.text.clone();
-        t.text = text.to_string();
-        ret
-    })
+        },
+        |t| {
+            let ret = t.text.clone();
+            t.text = text.to_string();
+            ret
+        },
+    )
     .unwrap()
 }
 
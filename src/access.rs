@@ -0,0 +1,236 @@
+use std::{
+    any::Any,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    sync::{LockResult, TryLockError, TryLockResult},
+    time::Instant,
+};
+
+#[cfg(feature = "arc-swap")]
+use std::sync::Arc;
+
+#[cfg(feature = "arc-swap")]
+use crate::{read_mostly::ReadMostly, sync::MutexGuard};
+use crate::{
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+    RecordData, Taken, Value,
+};
+
+// 获取值自身的锁时的等待方式
+#[derive(Clone, Copy)]
+pub(crate) enum Wait {
+    // 一直等待
+    Block,
+    // 锁被占用时立即返回
+    Never,
+    // 锁被占用时等待至截止时间
+    Until(Instant),
+}
+
+// 以指定类型访问条目的值失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AccessError {
+    // 锁被占用，仅在 `Wait::Never` 或 `Wait::Until` 时出现
+    WouldBlock,
+    Poisoned,
+    // 值的类型与访问的类型不符
+    Downcast,
+}
+
+impl<T> From<TryLockError<T>> for AccessError {
+    fn from(e: TryLockError<T>) -> Self {
+        match e {
+            TryLockError::WouldBlock => AccessError::WouldBlock,
+            TryLockError::Poisoned(_) => AccessError::Poisoned,
+        }
+    }
+}
+
+// 以 `T` 只读访问条目的值：以 `register_read_mostly` 注册的值仅加载当前值的 `Arc`，其余的值持有值自身的读锁
+pub(crate) enum ReadAccess<'a, T> {
+    Locked(RwLockReadGuard<'a, Value>, PhantomData<T>),
+    #[cfg(feature = "arc-swap")]
+    Published(arc_swap::Guard<Arc<T>>),
+}
+
+impl<T: 'static> Deref for ReadAccess<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            ReadAccess::Locked(value, _) => value
+                .downcast_ref::<T>()
+                .expect("value type checked on creation"),
+            #[cfg(feature = "arc-swap")]
+            ReadAccess::Published(value) => value,
+        }
+    }
+}
+
+// 以 `T` 可写访问条目的值：以 `register_read_mostly` 注册的值持有其修改的互斥锁并修改当前值的副本，其余的值持有值自身的写锁
+//
+// 修改须通过 `commit` 发布并记录；未调用 `commit` 即被销毁时，副本上的修改被丢弃，值自身的锁中的修改则保留但不记录
+pub(crate) struct WriteAccess<'a, T> {
+    data: &'a RecordData,
+    state: Write<'a, T>,
+}
+
+enum Write<'a, T> {
+    Locked(RwLockWriteGuard<'a, Value>, PhantomData<T>),
+    #[cfg(feature = "arc-swap")]
+    Published {
+        read_mostly: &'a ReadMostly<T>,
+        value: T,
+        _writer: MutexGuard<'a, ()>,
+    },
+}
+
+// `WriteAccess::into_owned` 取出值之后仍持有的锁
+pub(crate) enum Held<'a> {
+    // 值自身的写锁，其中已留下占位值
+    Locked {
+        _value: RwLockWriteGuard<'a, Value>,
+    },
+    #[cfg(feature = "arc-swap")]
+    Published {
+        _writer: MutexGuard<'a, ()>,
+    },
+}
+
+impl<'a, T: 'static + Send + Sync> WriteAccess<'a, T> {
+    // 由调用方已获取的写锁构造，调用方应已确认值的类型为 `T`
+    pub(crate) fn locked(data: &'a RecordData, value: RwLockWriteGuard<'a, Value>) -> Self {
+        Self {
+            data,
+            state: Write::Locked(value, PhantomData),
+        }
+    }
+
+    // 发布修改并记录一次修改，之后释放锁
+    pub(crate) fn commit(self) {
+        match self.state {
+            Write::Locked(..) => self.data.touch(),
+            #[cfg(feature = "arc-swap")]
+            Write::Published {
+                read_mostly,
+                value,
+                _writer,
+            } => {
+                read_mostly.store(value);
+                self.data.touch();
+            }
+        }
+    }
+
+    // 取出值的所有权并继续持有锁：值自身的锁中留下占位值；以 `register_read_mostly` 注册的值取出的是当前值的副本，当前值保持不变
+    pub(crate) fn into_owned(self) -> (T, Held<'a>) {
+        match self.state {
+            Write::Locked(mut value, _) => {
+                let owned = std::mem::replace(&mut *value, Box::new(Taken))
+                    .downcast::<T>()
+                    .expect("value type checked on creation");
+                (*owned, Held::Locked { _value: value })
+            }
+            #[cfg(feature = "arc-swap")]
+            Write::Published { value, _writer, .. } => (value, Held::Published { _writer }),
+        }
+    }
+}
+
+impl<T: 'static> Deref for WriteAccess<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match &self.state {
+            Write::Locked(value, _) => value
+                .downcast_ref::<T>()
+                .expect("value type checked on creation"),
+            #[cfg(feature = "arc-swap")]
+            Write::Published { value, .. } => value,
+        }
+    }
+}
+
+impl<T: 'static> DerefMut for WriteAccess<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        match &mut self.state {
+            Write::Locked(value, _) => value
+                .downcast_mut::<T>()
+                .expect("value type checked on creation"),
+            #[cfg(feature = "arc-swap")]
+            Write::Published { value, .. } => value,
+        }
+    }
+}
+
+impl RecordData {
+    // 以 `T` 只读访问该值；所有读取值的函数都应通过该函数访问，从而支持以 `register_read_mostly` 注册的值
+    pub(crate) fn read_as<T: 'static + Send + Sync>(
+        &self,
+        wait: Wait,
+    ) -> Result<ReadAccess<'_, T>, AccessError> {
+        #[cfg(feature = "arc-swap")]
+        if let Some(read_mostly) = self.read_mostly::<T>() {
+            return Ok(ReadAccess::Published(read_mostly.load()));
+        }
+        let value = match wait {
+            Wait::Block => blocking(self.read())?,
+            Wait::Never => self.try_read()?,
+            Wait::Until(deadline) => self.try_read_until(deadline)?,
+        };
+        if !value.is::<T>() {
+            return Err(AccessError::Downcast);
+        }
+        Ok(ReadAccess::Locked(value, PhantomData))
+    }
+
+    // 以类型擦除的方式只读访问该值，供不知道值类型的函数使用
+    pub(crate) fn read_any(&self, func: &mut dyn FnMut(&dyn Any)) -> Result<(), AccessError> {
+        #[cfg(feature = "arc-swap")]
+        if let Some(read_mostly) = &self.read_mostly {
+            read_mostly.visit(func);
+            return Ok(());
+        }
+        let value = blocking(self.read())?;
+        func(value.as_ref());
+        Ok(())
+    }
+
+    // 以 `T` 可写访问该值；所有修改值的函数都应通过该函数访问，并在修改完成后调用 `WriteAccess::commit`
+    pub(crate) fn write_as<T: 'static + Send + Sync>(
+        &self,
+        wait: Wait,
+    ) -> Result<WriteAccess<'_, T>, AccessError> {
+        #[cfg(feature = "arc-swap")]
+        if let Some(read_mostly) = self.read_mostly::<T>() {
+            let writer = match wait {
+                Wait::Block => Some(read_mostly.lock()),
+                Wait::Never => read_mostly.try_lock(),
+                Wait::Until(deadline) => read_mostly.lock_until(deadline),
+            };
+            let writer = writer.ok_or(AccessError::WouldBlock)?;
+            return Ok(WriteAccess {
+                data: self,
+                state: Write::Published {
+                    read_mostly,
+                    value: read_mostly.cloned(),
+                    _writer: writer,
+                },
+            });
+        }
+        let value = match wait {
+            Wait::Block => blocking(self.write())?,
+            Wait::Never => self.try_write()?,
+            Wait::Until(deadline) => self.try_write_until(deadline)?,
+        };
+        if !value.is::<T>() {
+            return Err(AccessError::Downcast);
+        }
+        Ok(WriteAccess::locked(self, value))
+    }
+}
+
+// 一直等待的获取锁的结果只可能因锁中毒而失败
+fn blocking<G>(result: LockResult<G>) -> TryLockResult<G> {
+    result.map_err(TryLockError::Poisoned)
+}
@@ -0,0 +1,473 @@
+// 异步注册表，仅在启用 `async` 特性时编译
+//
+// 每种类型对应一个 `tokio::sync::RwLock` 保护的表，表中的每个值各自由一个 `tokio::sync::RwLock` 保护；
+// 类型到表的映射仅在查找或创建表时短暂加锁，不会跨越 `.await` 持有
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    future::Future,
+    marker::PhantomData,
+    pin::{pin, Pin},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use tokio::sync::{Notify, RwLock};
+
+use crate::sync::{lazy_static, Mutex, RwLock as SyncRwLock};
+
+// 注册表中的一个值及其版本号
+//
+// 值被移除后置为 `None`，移除之前已取得该条目的任务获取锁后将看到值已不存在；
+// 每次成功的修改都会使版本号加 1 并唤醒等待该值改变的任务
+struct AsyncValue<T> {
+    value: RwLock<Option<T>>,
+    version: AtomicU64,
+    removed: AtomicBool,
+    changed: Notify,
+}
+
+impl<T> AsyncValue<T> {
+    fn new(value: T, version: u64) -> Self {
+        Self {
+            value: RwLock::new(Some(value)),
+            version: AtomicU64::new(version),
+            removed: AtomicBool::new(false),
+            changed: Notify::new(),
+        }
+    }
+
+    fn modified(&self) {
+        self.version.fetch_add(1, Ordering::Release);
+        self.changed.notify_waiters();
+    }
+
+    fn removed(&self) {
+        self.removed.store(true, Ordering::Release);
+        self.changed.notify_waiters();
+    }
+}
+
+type AsyncEntry<T> = Arc<AsyncValue<T>>;
+type AsyncBucket<T> = RwLock<HashMap<Box<str>, AsyncEntry<T>>>;
+type Waiters = HashMap<Box<str>, Arc<Notify>>;
+
+lazy_static! {
+    static ref _ASYNC_TABLE: SyncRwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>> =
+        SyncRwLock::default();
+    // 正在等待的键对应的通知，同一类型下的同一个键的所有等待者共享一个通知，最后一个等待者结束时移除
+    static ref _ASYNC_WAITERS: Mutex<HashMap<TypeId, Waiters>> = Mutex::default();
+}
+
+// 一个正在等待的任务所持有的通知，被丢弃时如果已没有其他等待者，则移除该键对应的通知
+struct Waiter {
+    type_id: TypeId,
+    name: Box<str>,
+    notify: Arc<Notify>,
+}
+
+impl Waiter {
+    fn new(type_id: TypeId, name: &str) -> Self {
+        let mut map = _ASYNC_WAITERS.lock().unwrap_or_else(|e| e.into_inner());
+        let notify = map
+            .entry(type_id)
+            .or_default()
+            .entry(name.into())
+            .or_default();
+        Self {
+            type_id,
+            name: name.into(),
+            notify: Arc::clone(notify),
+        }
+    }
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        let mut map = _ASYNC_WAITERS.lock().unwrap_or_else(|e| e.into_inner());
+        // 通知仅在持有该锁时被复制，因而引用计数为 2（表中的一份与自身的一份）时自身是最后一个等待者
+        if Arc::strong_count(&self.notify) > 2 {
+            return;
+        }
+        if let Some(keys) = map.get_mut(&self.type_id) {
+            keys.remove(&self.name);
+            if keys.is_empty() {
+                map.remove(&self.type_id);
+            }
+        }
+    }
+}
+
+// 唤醒所有正在等待指定键的任务，在该键被注册之后调用
+fn notify_registered(type_id: TypeId, name: &str) {
+    let map = _ASYNC_WAITERS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(notify) = map.get(&type_id).and_then(|keys| keys.get(name)) {
+        notify.notify_waiters();
+    }
+}
+
+/// `AsyncRegistry::changed` 等函数所等待到的改变
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeEvent {
+    /// 值被修改或被重新注册的值替换
+    Modified,
+    /// 键已被移除或不存在
+    Removed,
+}
+
+/// `AsyncRegistry::changed` 与 `AsyncRegistry::changed_since` 返回的 future
+#[must_use = "futures do nothing unless polled"]
+pub struct Changed {
+    inner: Pin<Box<dyn Future<Output = ChangeEvent> + Send>>,
+}
+
+impl Future for Changed {
+    type Output = ChangeEvent;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<ChangeEvent> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+/// 可在异步任务中使用的注册表
+///
+/// 与 `Registry` 的接口相对应，但等待锁时挂起当前任务而不是阻塞线程，因而不会阻塞执行器；
+/// 传入的闭包函数在持有锁期间同步执行，其中不能 `.await`。
+///
+/// # 注解
+///
+/// + `AsyncRegistry` 使用独立的存储：通过 `Registry` 注册的值无法通过 `AsyncRegistry` 访问，反之亦然
+/// + 冻结、封存前缀、死锁检测等仅作用于 `Registry` 的功能不适用于 `AsyncRegistry`
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::AsyncRegistry;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// AsyncRegistry::register("async.counter", 0u32).await;
+/// AsyncRegistry::<u32>::apply("async.counter", |v| *v += 1).await;
+/// assert_eq!(AsyncRegistry::<u32>::with("async.counter", |v| *v).await, Some(1));
+/// # });
+/// ```
+pub struct AsyncRegistry<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T: 'static + Send + Sync> AsyncRegistry<T> {
+    // 获取该类型对应的表，`create` 为真时在表不存在时创建
+    fn _bucket(create: bool) -> Option<Arc<AsyncBucket<T>>> {
+        let type_id = TypeId::of::<T>();
+        let bucket = {
+            let map = _ASYNC_TABLE.read().unwrap_or_else(|e| e.into_inner());
+            map.get(&type_id).cloned()
+        };
+        let bucket = match bucket {
+            Some(bucket) => bucket,
+            None if create => {
+                let mut map = _ASYNC_TABLE.write().unwrap_or_else(|e| e.into_inner());
+                let bucket = map
+                    .entry(type_id)
+                    .or_insert_with(|| Arc::new(AsyncBucket::<T>::default()));
+                Arc::clone(bucket)
+            }
+            None => return None,
+        };
+        bucket.downcast().ok()
+    }
+
+    // 查找指定键对应的条目，查找后即释放表的锁
+    async fn _entry(name: &str) -> Option<AsyncEntry<T>> {
+        let bucket = Self::_bucket(false)?;
+        let map = bucket.read().await;
+        map.get(name).cloned()
+    }
+
+    /// 向注册表中注册一个新值
+    ///
+    /// 如果相同的键已存在，那么旧值将会被新值替换，新值的版本号在旧值的基础上加 1；
+    /// 正在等待旧值的锁的 `with` 与 `apply` 仍访问旧值，等待旧值改变的任务得到 `ChangeEvent::Modified`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::AsyncRegistry;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// AsyncRegistry::register("async.key", 42i32).await;
+    /// AsyncRegistry::register("async.key", 64i32).await;
+    /// assert_eq!(AsyncRegistry::<i32>::with("async.key", |v| *v).await, Some(64));
+    /// # });
+    /// ```
+    pub async fn register(name: &str, value: T) {
+        let bucket = Self::_bucket(true).expect("the table was just created");
+        let old = {
+            let mut map = bucket.write().await;
+            let version = map
+                .get(name)
+                .map_or(0, |old| old.version.load(Ordering::Acquire).wrapping_add(1));
+            map.insert(name.into(), Arc::new(AsyncValue::new(value, version)))
+        };
+        // 旧值在释放表的锁之后销毁
+        if let Some(old) = old {
+            old.modified();
+        }
+        notify_registered(TypeId::of::<T>(), name);
+    }
+
+    /// 从注册表中移除指定键对应的值
+    ///
+    /// 等待该值的写锁后取回该值，等待该值改变的任务得到 `ChangeEvent::Removed`；如果键不存在，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::AsyncRegistry;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// AsyncRegistry::register("async.removed", 42i32).await;
+    /// assert_eq!(AsyncRegistry::<i32>::remove("async.removed").await, Some(42));
+    /// assert_eq!(AsyncRegistry::<i32>::remove("async.removed").await, None);
+    /// # });
+    /// ```
+    pub async fn remove(name: &str) -> Option<T> {
+        let bucket = Self::_bucket(false)?;
+        let entry = bucket.write().await.remove(name)?;
+        let value = entry.value.write().await.take();
+        entry.removed();
+        value
+    }
+
+    /// 判断指定键是否存在于注册表中
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::AsyncRegistry;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// AsyncRegistry::register("async.exists", 42i32).await;
+    /// assert!(AsyncRegistry::<i32>::exists("async.exists").await);
+    /// assert!(!AsyncRegistry::<i32>::exists("async.other").await);
+    /// assert!(!AsyncRegistry::<u8>::exists("async.exists").await);
+    /// # });
+    /// ```
+    pub async fn exists(name: &str) -> bool {
+        let Some(bucket) = Self::_bucket(false) else {
+            return false;
+        };
+        let map = bucket.read().await;
+        map.contains_key(name)
+    }
+
+    /// 向注册表中的指定键应用一个函数，该函数可以修改注册表中的值
+    ///
+    /// 等待该值的写锁期间挂起当前任务；如果键不存在或等待期间该值已被移除，则返回 `None`，否则返回闭包函数的返回值。
+    /// 闭包函数返回后该值的版本号加 1
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::AsyncRegistry;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// AsyncRegistry::register("async.apply", 42i32).await;
+    /// assert_eq!(AsyncRegistry::<i32>::apply("async.apply", |v| { *v += 1; *v }).await, Some(43));
+    /// assert_eq!(AsyncRegistry::<i32>::apply("async.other", |v| *v += 1).await, None);
+    /// # });
+    /// ```
+    pub async fn apply<R, F: FnOnce(&mut T) -> R>(name: &str, func: F) -> Option<R> {
+        let entry = Self::_entry(name).await?;
+        let mut value = entry.value.write().await;
+        let ret = value.as_mut().map(func)?;
+        drop(value);
+        entry.modified();
+        Some(ret)
+    }
+
+    /// 向注册表中的指定键应用一个函数，该函数仅能读取注册表中的值
+    ///
+    /// 等待该值的读锁期间挂起当前任务；如果键不存在或等待期间该值已被移除，则返回 `None`，否则返回闭包函数的返回值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::AsyncRegistry;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// AsyncRegistry::register("async.with", 42i32).await;
+    /// assert_eq!(AsyncRegistry::<i32>::with("async.with", |v| *v).await, Some(42));
+    /// assert_eq!(AsyncRegistry::<i32>::with("async.other", |v| *v).await, None);
+    /// # });
+    /// ```
+    pub async fn with<R, F: FnOnce(&T) -> R>(name: &str, func: F) -> Option<R> {
+        let entry = Self::_entry(name).await?;
+        let value = entry.value.read().await;
+        value.as_ref().map(func)
+    }
+
+    /// 挂起当前任务，直至指定键在该类型下被注册
+    ///
+    /// 键已存在时立即完成。等待者在检查键是否存在之前就已登记，因而在检查之后、开始等待之前发生的注册不会被错过；
+    /// 返回的 future 在完成之前被丢弃时，其登记也随之移除
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::AsyncRegistry;
+    /// use std::time::Duration;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(async {
+    /// let loader = tokio::spawn(async {
+    ///     tokio::time::sleep(Duration::from_millis(20)).await;
+    ///     AsyncRegistry::register("async.app.config", String::from("loaded")).await;
+    /// });
+    /// AsyncRegistry::<String>::wait_for("async.app.config").await;
+    /// assert_eq!(
+    ///     AsyncRegistry::<String>::with("async.app.config", |s| s.clone()).await,
+    ///     Some(String::from("loaded"))
+    /// );
+    /// loader.await.unwrap();
+    /// # });
+    /// ```
+    pub async fn wait_for(name: &str) {
+        let waiter = Waiter::new(TypeId::of::<T>(), name);
+        loop {
+            let mut notified = pin!(waiter.notify.notified());
+            notified.as_mut().enable();
+            if Self::exists(name).await {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// 与 `wait_for` 相同，但最多等待 `timeout` 时间
+    ///
+    /// 键被注册时返回 `true`，超时返回 `false`。需要在启用了时间驱动的 tokio 运行时中调用
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::AsyncRegistry;
+    /// use std::time::Duration;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(async {
+    /// let missing = AsyncRegistry::<u16>::wait_for_timeout("async.app.missing", Duration::from_millis(10));
+    /// assert!(!missing.await);
+    /// assert_eq!(AsyncRegistry::<u16>::waiters("async.app.missing"), 0);
+    /// # });
+    /// ```
+    pub async fn wait_for_timeout(name: &str, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, Self::wait_for(name))
+            .await
+            .is_ok()
+    }
+
+    /// 获取正在通过 `wait_for` 或 `wait_for_timeout` 等待指定键的任务数量
+    pub fn waiters(name: &str) -> usize {
+        let map = _ASYNC_WAITERS.lock().unwrap_or_else(|e| e.into_inner());
+        map.get(&TypeId::of::<T>())
+            .and_then(|keys| keys.get(name))
+            .map_or(0, |notify| Arc::strong_count(notify) - 1)
+    }
+
+    /// 获取指定键对应的值的版本号
+    ///
+    /// 版本号在键首次注册时为 0，此后每次成功的 `apply` 或对已存在的键再次注册都会使其加 1。如果键不存在，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::AsyncRegistry;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// AsyncRegistry::register("async.versioned", 42i32).await;
+    /// assert_eq!(AsyncRegistry::<i32>::version("async.versioned").await, Some(0));
+    /// AsyncRegistry::<i32>::apply("async.versioned", |v| *v += 1).await;
+    /// AsyncRegistry::register("async.versioned", 64i32).await;
+    /// assert_eq!(AsyncRegistry::<i32>::version("async.versioned").await, Some(2));
+    /// assert_eq!(AsyncRegistry::<i32>::version("async.other").await, None);
+    /// # });
+    /// ```
+    pub async fn version(name: &str) -> Option<u64> {
+        let entry = Self::_entry(name).await?;
+        Some(entry.version.load(Ordering::Acquire))
+    }
+
+    /// 返回在指定键对应的值下一次被成功修改后完成的 future
+    ///
+    /// 以 future 首次被轮询时该值的版本号为基准；值被修改或被重新注册的值替换时得到 `ChangeEvent::Modified`，
+    /// 被移除时得到 `ChangeEvent::Removed`，键不存在时立即得到 `ChangeEvent::Removed`。
+    /// 需要确保不错过读取值与开始等待之间的修改时，使用 `version` 与 `changed_since`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{AsyncRegistry, ChangeEvent};
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// AsyncRegistry::register("async.watched", 0u32).await;
+    /// let watcher = tokio::spawn(AsyncRegistry::<u32>::changed("async.watched"));
+    /// tokio::task::yield_now().await;
+    /// AsyncRegistry::<u32>::apply("async.watched", |v| *v += 1).await;
+    /// assert_eq!(watcher.await.unwrap(), ChangeEvent::Modified);
+    ///
+    /// let watcher = tokio::spawn(AsyncRegistry::<u32>::changed("async.watched"));
+    /// tokio::task::yield_now().await;
+    /// AsyncRegistry::<u32>::remove("async.watched").await;
+    /// assert_eq!(watcher.await.unwrap(), ChangeEvent::Removed);
+    /// # });
+    /// ```
+    pub fn changed(name: &str) -> Changed {
+        Changed {
+            inner: Box::pin(Self::_changed(name.to_string(), None)),
+        }
+    }
+
+    /// 返回在指定键对应的值的版本号大于 `version` 时完成的 future
+    ///
+    /// 版本号已大于 `version` 时立即得到 `ChangeEvent::Modified`；其余与 `changed` 相同
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{AsyncRegistry, ChangeEvent};
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// AsyncRegistry::register("async.since", 0u32).await;
+    /// let seen = AsyncRegistry::<u32>::version("async.since").await.unwrap();
+    /// AsyncRegistry::<u32>::apply("async.since", |v| *v += 1).await;
+    /// // 在读取版本号之后、开始等待之前发生的修改不会被错过
+    /// assert_eq!(AsyncRegistry::<u32>::changed_since("async.since", seen).await, ChangeEvent::Modified);
+    /// # });
+    /// ```
+    pub fn changed_since(name: &str, version: u64) -> Changed {
+        Changed {
+            inner: Box::pin(Self::_changed(name.to_string(), Some(version))),
+        }
+    }
+
+    // 等待值的版本号大于 `since` 或值被移除，`since` 为 `None` 时以当前版本号为基准
+    async fn _changed(name: String, since: Option<u64>) -> ChangeEvent {
+        let Some(entry) = Self::_entry(&name).await else {
+            return ChangeEvent::Removed;
+        };
+        let since = since.unwrap_or_else(|| entry.version.load(Ordering::Acquire));
+        loop {
+            let mut notified = pin!(entry.changed.notified());
+            notified.as_mut().enable();
+            if entry.removed.load(Ordering::Acquire) {
+                return ChangeEvent::Removed;
+            }
+            if entry.version.load(Ordering::Acquire) > since {
+                return ChangeEvent::Modified;
+            }
+            notified.await;
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::{any::TypeId, sync::Arc};
+
+#[cfg(loom)]
+use crate::sync::{LocalCell, LocalRefCell};
+use crate::{
+    insert_slot, intern, is_sealed, BootstrapError, Record, RegisterError, RegisterErrorKind,
+    TypeTable, TypeTables, _TABLE, BOOTSTRAPPING, CONTEXT,
+};
+
+/// 在 `bootstrap` 的闭包函数中直接向注册表写入值的句柄
+pub struct Bootstrapper<'a> {
+    map: &'a mut TypeTables,
+    discarded: Vec<Record>,
+}
+
+impl Bootstrapper<'_> {
+    /// 向注册表中注册一个新值
+    ///
+    /// 与 `Registry::register` 相同，如果相同的键已存在，那么旧值将会被新值替换，旧值会在 `bootstrap` 释放锁之后被销毁；
+    /// 如果该类型已被冻结、键位于已被封存的前缀之下或该类型对应的表已中毒，则返回包含被拒绝的值的 `RegisterError`
+    pub fn register<T: 'static + Send + Sync>(
+        &mut self,
+        name: &str,
+        value: T,
+    ) -> Result<(), RegisterError<T>> {
+        if is_sealed(name) {
+            return Err(RegisterError::new(
+                name,
+                RegisterErrorKind::SealedNamespace,
+                value,
+            ));
+        }
+        let type_table = self
+            .map
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Arc::new(TypeTable::new::<T>()));
+        if type_table.is_frozen() {
+            return Err(RegisterError::new(name, RegisterErrorKind::Frozen, value));
+        }
+        // 其他线程可能缓存着该表，因而同样通过分片的锁写入；其他线程此时从缓存的表中查找到的结果会因版本号的改变而被丢弃
+        let Ok(mut type_map) = type_table.write_shard(name) else {
+            return Err(RegisterError::new(name, RegisterErrorKind::Poisoned, value));
+        };
+        if let Some(old) = insert_slot(&mut type_map, intern(name), Box::new(value)) {
+            self.discarded.push(old);
+        }
+        Ok(())
+    }
+}
+
+/// 在整个闭包函数执行期间持有注册表的写锁，从而批量注册大量值
+///
+/// 与逐个调用 `Registry::register` 相比，无论注册多少个键、涉及多少种类型，都只需获取一次锁。
+/// 在闭包函数中调用任何其他访问注册表的函数都会导致线程死锁（调试模式下会发生 panic）；
+/// 如果当前线程正处于 `with`、`apply` 等闭包或另一个 `bootstrap` 中，则返回 `BootstrapError::WouldDeadlock` 并且不会执行闭包函数。
+/// 闭包函数发生 panic 时，锁会在 panic 继续传播之前被正常释放而不会中毒
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{bootstrap, Registry};
+///
+/// struct Texture(u32);
+///
+/// bootstrap(|b| {
+///     for i in 0..1000u32 {
+///         b.register(&format!("level.{}", i), i).unwrap();
+///         b.register(&format!("level.{}", i), format!("object {}", i)).unwrap();
+///         b.register(&format!("level.{}", i), Texture(i * 2)).unwrap();
+///     }
+/// })
+/// .unwrap();
+///
+/// assert_eq!(Registry::<u32>::len(), 1000);
+/// assert_eq!(Registry::<String>::get("level.42"), Some(String::from("object 42")));
+/// assert_eq!(Registry::<Texture>::with("level.999", |t| t.0), Some(1998));
+/// ```
+///
+/// 在 `with` 等闭包中调用会返回错误：
+///
+/// ```rust
+/// use gom::{bootstrap, BootstrapError, Registry};
+///
+/// Registry::register("key", 1u8).unwrap();
+/// let result = Registry::<u8>::with("key", |_| bootstrap(|b| b.register("other", 2u8).is_ok()));
+/// assert_eq!(result, Some(Err(BootstrapError::WouldDeadlock)));
+/// ```
+///
+/// 在闭包函数中调用其他访问注册表的函数会导致线程死锁（调试模式下）：
+///
+/// ```rust
+/// use gom::{bootstrap, Registry};
+///
+/// # if cfg!(any(debug_assertions, feature = "deadlock-detection")) {
+/// let result = std::panic::catch_unwind(|| {
+///     bootstrap(|b| {
+///         b.register("a", 1i32).unwrap();
+///         Registry::register("b", 2i32).unwrap();
+///     })
+/// });
+/// assert!(result.is_err());
+/// // 锁已被正常释放
+/// assert_eq!(Registry::<i32>::get("a"), Some(1));
+/// assert!(!Registry::<i32>::exists("b"));
+/// # }
+/// ```
+pub fn bootstrap<R, F: FnOnce(&mut Bootstrapper) -> R>(func: F) -> Result<R, BootstrapError> {
+    if BOOTSTRAPPING.get() || CONTEXT.with_borrow(|v| !v.is_empty()) {
+        return Err(BootstrapError::WouldDeadlock);
+    }
+    let mut map = _TABLE.write_batch().map_err(|_| BootstrapError::Poisoned)?;
+    let mut bootstrapper = Bootstrapper {
+        map: &mut map,
+        discarded: Vec::new(),
+    };
+    BOOTSTRAPPING.set(true);
+    let ret = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| func(&mut bootstrapper)));
+    BOOTSTRAPPING.set(false);
+    let discarded = bootstrapper.discarded;
+    drop(map);
+    // 被替换的旧值在释放锁之后销毁
+    drop(discarded);
+    match ret {
+        Ok(ret) => Ok(ret),
+        Err(e) => std::panic::resume_unwind(e),
+    }
+}
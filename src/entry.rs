@@ -0,0 +1,154 @@
+use std::{
+    cell::OnceCell,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    sync::{Arc, PoisonError},
+    thread,
+};
+
+use crate::{
+    notify_registered, sync::RwLockWriteGuard, Context, ContextOperator, Record, RecordData,
+    TypeMap, WriteAccess,
+};
+
+/// 注册表中某个键的条目，由 `Registry::entry` 提供
+///
+/// 键已存在时，条目持有该键对应的值的写锁；键不存在时，条目持有该类型对应的表中该键所在分片的写锁，直至插入新值。
+/// 条目的生命周期被限制在 `Registry::entry` 的闭包之内，因而无法在闭包之外保存，也无法跨越其他对注册表的调用
+pub struct Entry<'a, T: 'static + Send + Sync> {
+    key: Arc<str>,
+    state: State<'a, T>,
+}
+
+enum State<'a, T: 'static + Send + Sync> {
+    Occupied(ValueMut<'a, T>),
+    Vacant(Vacant<'a>),
+}
+
+// 键不存在时持有的分片的写锁；插入的新条目保存在 `slot` 中，从而在释放分片的锁之后仍可持有其值的锁
+struct Vacant<'a> {
+    type_map: RwLockWriteGuard<'a, TypeMap>,
+    slot: &'a OnceCell<Arc<RecordData>>,
+    // 持有分片的锁期间推入的上下文，释放该锁时移除
+    context: Context,
+}
+
+impl Drop for Vacant<'_> {
+    fn drop(&mut self) {
+        ContextOperator::remove(&self.context);
+    }
+}
+
+impl<'a, T: 'static + Send + Sync> Entry<'a, T> {
+    // 已存在的键
+    pub(crate) fn occupied(key: Arc<str>, value: WriteAccess<'a, T>) -> Self {
+        Self {
+            key,
+            state: State::Occupied(ValueMut::new(value, false)),
+        }
+    }
+
+    // 不存在的键；`context` 为调用方持有分片的锁期间已推入的上下文
+    pub(crate) fn vacant(
+        key: Arc<str>,
+        type_map: RwLockWriteGuard<'a, TypeMap>,
+        slot: &'a OnceCell<Arc<RecordData>>,
+        context: Context,
+    ) -> Self {
+        Self {
+            key,
+            state: State::Vacant(Vacant {
+                type_map,
+                slot,
+                context,
+            }),
+        }
+    }
+
+    /// 获取条目对应的键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 如果键不存在，则注册 `default`；返回该键对应的值的可变引用
+    pub fn or_insert(self, default: T) -> ValueMut<'a, T> {
+        self.or_insert_with(|| default)
+    }
+
+    /// 如果键不存在，则注册 `default` 的返回值；返回该键对应的值的可变引用
+    pub fn or_insert_with<F: FnOnce() -> T>(self, default: F) -> ValueMut<'a, T> {
+        match self.state {
+            State::Occupied(value) => value,
+            State::Vacant(mut vacant) => {
+                let record = Record::new(Box::new(default()));
+                let data = vacant.slot.get_or_init(|| Arc::clone(&record.data));
+                vacant.type_map.insert(self.key, record);
+                // 新条目的锁尚未被其他线程获取，因而总能立即获取且不会中毒；获取之后再释放分片的锁
+                let guard = data.write().unwrap_or_else(PoisonError::into_inner);
+                drop(vacant);
+                notify_registered();
+                ValueMut::new(WriteAccess::locked(data, guard), true)
+            }
+        }
+    }
+
+    /// 如果键已存在，则修改其对应的值
+    pub fn and_modify<F: FnOnce(&mut T)>(mut self, f: F) -> Self {
+        if let State::Occupied(value) = &mut self.state {
+            f(value);
+        }
+        self
+    }
+}
+
+impl<'a, T: 'static + Send + Sync + Default> Entry<'a, T> {
+    /// 如果键不存在，则注册 `T::default()`；返回该键对应的值的可变引用
+    pub fn or_default(self) -> ValueMut<'a, T> {
+        self.or_insert_with(T::default)
+    }
+}
+
+/// 条目对应的值的可变引用，由 `Entry::or_insert` 等函数返回
+///
+/// 持有该值的写锁，从而与共享该值的 `Handle` 互斥；插入了新值或以可变引用访问过该值时，销毁时记录一次修改。
+/// 以 `Registry::register_read_mostly` 注册的值持有其修改的互斥锁，修改在当前值的副本上进行，并在销毁时整体替换
+pub struct ValueMut<'a, T: 'static + Send + Sync> {
+    // 仅在 `drop` 中被取出
+    value: ManuallyDrop<WriteAccess<'a, T>>,
+    modified: bool,
+}
+
+impl<'a, T: 'static + Send + Sync> ValueMut<'a, T> {
+    fn new(value: WriteAccess<'a, T>, modified: bool) -> Self {
+        Self {
+            value: ManuallyDrop::new(value),
+            modified,
+        }
+    }
+}
+
+impl<T: 'static + Send + Sync> Deref for ValueMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: 'static + Send + Sync> DerefMut for ValueMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.modified = true;
+        &mut self.value
+    }
+}
+
+impl<T: 'static + Send + Sync> Drop for ValueMut<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: `value` 仅在此处被取出，之后不再被访问
+        let value = unsafe { ManuallyDrop::take(&mut self.value) };
+        // 栈展开时不记录修改，副本上的修改随之被丢弃
+        if self.modified && !thread::panicking() {
+            value.commit();
+        }
+    }
+}
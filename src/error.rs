@@ -0,0 +1,585 @@
+use std::{any::type_name, error::Error, fmt, sync::TryLockError};
+
+use crate::AccessError;
+
+/// 注册失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterErrorKind {
+    /// 指定键已存在
+    AlreadyExists,
+    /// 该类型已被冻结
+    Frozen,
+    /// 指定键位于已被封存的前缀之下
+    SealedNamespace,
+    /// 注册表的锁已中毒
+    Poisoned,
+}
+
+/// `Registry::try_register` 的错误类型，其中包含被拒绝注册的值
+pub struct RegisterError<T> {
+    key: String,
+    kind: RegisterErrorKind,
+    value: T,
+}
+
+impl<T> RegisterError<T> {
+    pub(crate) fn new(key: &str, kind: RegisterErrorKind, value: T) -> Self {
+        Self {
+            key: String::from(key),
+            kind,
+            value,
+        }
+    }
+
+    /// 注册失败的键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 注册失败的原因
+    pub fn kind(&self) -> RegisterErrorKind {
+        self.kind
+    }
+
+    /// 取回被拒绝注册的值
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+impl<T> fmt::Debug for RegisterError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegisterError")
+            .field("key", &self.key)
+            .field("type", &type_name::<T>())
+            .field("kind", &self.kind)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for RegisterError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            RegisterErrorKind::AlreadyExists => write!(
+                f,
+                "key `{}` is already registered for type `{}`",
+                self.key,
+                type_name::<T>()
+            ),
+            RegisterErrorKind::Frozen => write!(
+                f,
+                "cannot register key `{}` for type `{}`: type is frozen",
+                self.key,
+                type_name::<T>()
+            ),
+            RegisterErrorKind::SealedNamespace => write!(
+                f,
+                "cannot register key `{}` for type `{}`: namespace is sealed",
+                self.key,
+                type_name::<T>()
+            ),
+            RegisterErrorKind::Poisoned => write!(
+                f,
+                "cannot register key `{}` for type `{}`: lock poisoned",
+                self.key,
+                type_name::<T>()
+            ),
+        }
+    }
+}
+
+impl<T> Error for RegisterError<T> {}
+
+/// `Registry::rename` 的错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameError {
+    /// 原键不存在
+    SourceMissing,
+    /// 新键已存在
+    DestinationExists,
+    /// 新键位于已被封存的前缀之下
+    SealedNamespace,
+    /// 该类型已被冻结
+    Frozen,
+    /// 注册表的锁已中毒
+    Poisoned,
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::SourceMissing => write!(f, "source key does not exist"),
+            RenameError::DestinationExists => write!(f, "destination key already exists"),
+            RenameError::SealedNamespace => write!(f, "namespace is sealed"),
+            RenameError::Frozen => write!(f, "type is frozen"),
+            RenameError::Poisoned => write!(f, "lock poisoned"),
+        }
+    }
+}
+
+impl Error for RenameError {}
+
+/// `Registry::swap` 的错误类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwapError {
+    /// 指定键不存在
+    Missing(String),
+    /// 该类型已被冻结
+    Frozen,
+    /// 注册表的锁已中毒
+    Poisoned,
+}
+
+impl fmt::Display for SwapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwapError::Missing(key) => write!(f, "key `{}` does not exist", key),
+            SwapError::Frozen => write!(f, "type is frozen"),
+            SwapError::Poisoned => write!(f, "lock poisoned"),
+        }
+    }
+}
+
+impl Error for SwapError {}
+
+/// `Registry::rename_prefix` 与 `rename_prefix_all` 的错误类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenamePrefixError {
+    /// 移动后的新键与不被移动的已有键冲突
+    DestinationExists(String),
+    /// 移动后的新键位于已被封存的前缀之下
+    SealedNamespace(String),
+    /// 该类型已被冻结
+    Frozen,
+    /// 注册表的锁已中毒
+    Poisoned,
+}
+
+impl fmt::Display for RenamePrefixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenamePrefixError::DestinationExists(key) => {
+                write!(f, "destination key `{}` already exists", key)
+            }
+            RenamePrefixError::SealedNamespace(key) => {
+                write!(f, "destination key `{}` is in a sealed namespace", key)
+            }
+            RenamePrefixError::Frozen => write!(f, "type is frozen"),
+            RenamePrefixError::Poisoned => write!(f, "lock poisoned"),
+        }
+    }
+}
+
+impl Error for RenamePrefixError {}
+
+/// `bootstrap` 的错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootstrapError {
+    /// 当前线程正处于 `with`、`apply` 等闭包或另一个 `bootstrap` 中，获取注册表的写锁会导致死锁
+    WouldDeadlock,
+    /// 注册表的锁已中毒
+    Poisoned,
+}
+
+impl fmt::Display for BootstrapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BootstrapError::WouldDeadlock => write!(f, "bootstrap would deadlock"),
+            BootstrapError::Poisoned => write!(f, "lock poisoned"),
+        }
+    }
+}
+
+impl Error for BootstrapError {}
+
+/// 键匹配模式的错误类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// 模式为空
+    Empty,
+    /// 模式中存在非法的段，`*` 与 `**` 只能单独构成一个段
+    InvalidSegment(String),
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Empty => write!(f, "pattern is empty"),
+            PatternError::InvalidSegment(segment) => {
+                write!(f, "invalid pattern segment `{}`", segment)
+            }
+        }
+    }
+}
+
+impl Error for PatternError {}
+
+/// `Registry::compare_and_swap` 的错误类型，其中包含被拒绝写入的新值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CasError<T> {
+    /// 指定键不存在
+    Missing {
+        /// 被拒绝写入的新值
+        new: T,
+    },
+    /// 当前值与期望值不相等
+    Mismatch {
+        /// 当前值的副本
+        current: T,
+        /// 被拒绝写入的新值
+        new: T,
+    },
+    /// 该类型已被冻结
+    Frozen {
+        /// 被拒绝写入的新值
+        new: T,
+    },
+    /// 注册表的锁已中毒
+    Poisoned {
+        /// 被拒绝写入的新值
+        new: T,
+    },
+}
+
+impl<T> CasError<T> {
+    /// 取回被拒绝写入的新值
+    pub fn into_new(self) -> T {
+        match self {
+            CasError::Missing { new } => new,
+            CasError::Mismatch { new, .. } => new,
+            CasError::Frozen { new } => new,
+            CasError::Poisoned { new } => new,
+        }
+    }
+}
+
+impl<T> fmt::Display for CasError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CasError::Missing { .. } => write!(f, "key does not exist"),
+            CasError::Mismatch { .. } => {
+                write!(f, "current value does not match the expected value")
+            }
+            CasError::Frozen { .. } => write!(f, "type is frozen"),
+            CasError::Poisoned { .. } => write!(f, "lock poisoned"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for CasError<T> {}
+
+/// `Registry::with_override` 的错误类型，其中包含未被使用的临时值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverrideError<T> {
+    /// 该类型已被冻结
+    Frozen {
+        /// 未被使用的临时值
+        temp: T,
+    },
+    /// 键不存在且位于已被封存的前缀之下
+    SealedNamespace {
+        /// 未被使用的临时值
+        temp: T,
+    },
+    /// 注册表的锁已中毒
+    Poisoned {
+        /// 未被使用的临时值
+        temp: T,
+    },
+}
+
+impl<T> OverrideError<T> {
+    /// 取回未被使用的临时值
+    pub fn into_temp(self) -> T {
+        match self {
+            OverrideError::Frozen { temp } => temp,
+            OverrideError::SealedNamespace { temp } => temp,
+            OverrideError::Poisoned { temp } => temp,
+        }
+    }
+}
+
+impl<T> fmt::Display for OverrideError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverrideError::Frozen { .. } => write!(f, "type is frozen"),
+            OverrideError::SealedNamespace { .. } => write!(f, "namespace is sealed"),
+            OverrideError::Poisoned { .. } => write!(f, "lock poisoned"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for OverrideError<T> {}
+
+/// `Registry::copy` 的错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyError {
+    /// 原键与新键相同
+    SameKey,
+    /// 原键不存在
+    SourceMissing,
+    /// 新键已存在且未允许覆盖
+    DestinationExists,
+    /// 该类型已被冻结
+    Frozen,
+    /// 新键位于已被封存的前缀之下
+    SealedNamespace,
+    /// 注册表的锁已中毒
+    Poisoned,
+}
+
+impl fmt::Display for CopyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CopyError::SameKey => write!(f, "source and destination keys are the same"),
+            CopyError::SourceMissing => write!(f, "source key does not exist"),
+            CopyError::DestinationExists => write!(f, "destination key already exists"),
+            CopyError::Frozen => write!(f, "type is frozen"),
+            CopyError::SealedNamespace => write!(f, "namespace is sealed"),
+            CopyError::Poisoned => write!(f, "lock poisoned"),
+        }
+    }
+}
+
+impl Error for CopyError {}
+
+/// `register_boxed` 的错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterBoxedError {
+    /// 值的实际类型与给定的 `TypeId` 不一致
+    TypeMismatch,
+    /// 该类型已被冻结
+    Frozen,
+    /// 指定键位于已被封存的前缀之下
+    SealedNamespace,
+    /// 注册表的锁已中毒
+    Poisoned,
+}
+
+impl fmt::Display for RegisterBoxedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegisterBoxedError::TypeMismatch => {
+                write!(f, "value type does not match the given type id")
+            }
+            RegisterBoxedError::Frozen => write!(f, "type is frozen"),
+            RegisterBoxedError::SealedNamespace => write!(f, "namespace is sealed"),
+            RegisterBoxedError::Poisoned => write!(f, "lock poisoned"),
+        }
+    }
+}
+
+impl Error for RegisterBoxedError {}
+
+/// `Registry::try_with` 与 `Registry::try_apply` 的错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryAccessError {
+    /// 指定键不存在
+    KeyMissing,
+    /// 该类型已被冻结，仅在可写访问时出现
+    Frozen,
+    /// 所需的锁正被其他访问持有，立即获取会阻塞
+    WouldBlock,
+    /// 注册表的锁已中毒
+    Poisoned,
+}
+
+impl<T> From<TryLockError<T>> for TryAccessError {
+    fn from(e: TryLockError<T>) -> Self {
+        match e {
+            TryLockError::WouldBlock => TryAccessError::WouldBlock,
+            TryLockError::Poisoned(_) => TryAccessError::Poisoned,
+        }
+    }
+}
+
+impl From<AccessError> for TryAccessError {
+    fn from(e: AccessError) -> Self {
+        match e {
+            AccessError::WouldBlock => TryAccessError::WouldBlock,
+            AccessError::Poisoned => TryAccessError::Poisoned,
+            // 值无法以该类型访问时视同该类型下不存在该键
+            AccessError::Downcast => TryAccessError::KeyMissing,
+        }
+    }
+}
+
+impl fmt::Display for TryAccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryAccessError::KeyMissing => write!(f, "key does not exist"),
+            TryAccessError::Frozen => write!(f, "type is frozen"),
+            TryAccessError::WouldBlock => write!(f, "lock is held by another access"),
+            TryAccessError::Poisoned => write!(f, "lock poisoned"),
+        }
+    }
+}
+
+impl Error for TryAccessError {}
+
+/// `Registry::try_replace` 的错误类型，其中包含未被写入的新值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryReplaceError<T> {
+    kind: TryAccessError,
+    value: T,
+}
+
+impl<T> TryReplaceError<T> {
+    pub(crate) fn new(kind: TryAccessError, value: T) -> Self {
+        Self { kind, value }
+    }
+
+    /// 替换失败的原因
+    pub fn kind(&self) -> TryAccessError {
+        self.kind
+    }
+
+    /// 取回未被写入的新值
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+impl<T> fmt::Display for TryReplaceError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot replace value: {}", self.kind)
+    }
+}
+
+impl<T: fmt::Debug> Error for TryReplaceError<T> {}
+
+/// `Registry::with_timeout` 与 `Registry::apply_timeout` 的错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutError {
+    /// 指定键不存在
+    KeyMissing,
+    /// 该类型已被冻结，仅在可写访问时出现
+    Frozen,
+    /// 在指定的时长内未能获取所需的锁
+    TimedOut,
+    /// 注册表的锁已中毒
+    Poisoned,
+}
+
+impl From<TryAccessError> for TimeoutError {
+    fn from(e: TryAccessError) -> Self {
+        match e {
+            TryAccessError::KeyMissing => TimeoutError::KeyMissing,
+            TryAccessError::Frozen => TimeoutError::Frozen,
+            TryAccessError::WouldBlock => TimeoutError::TimedOut,
+            TryAccessError::Poisoned => TimeoutError::Poisoned,
+        }
+    }
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutError::KeyMissing => write!(f, "key does not exist"),
+            TimeoutError::Frozen => write!(f, "type is frozen"),
+            TimeoutError::TimedOut => write!(f, "timed out waiting for lock"),
+            TimeoutError::Poisoned => write!(f, "lock poisoned"),
+        }
+    }
+}
+
+impl Error for TimeoutError {}
+
+/// `Registry` 的 `*_checked` 系列函数的错误类型，其中记录了出错的键与值的类型名称
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    /// 该类型在注册表中没有对应的表
+    TypeNotRegistered {
+        key: String,
+        type_name: &'static str,
+    },
+    /// 该类型下不存在指定键
+    KeyNotFound {
+        key: String,
+        type_name: &'static str,
+    },
+    /// 该类型已被冻结
+    Frozen {
+        key: String,
+        type_name: &'static str,
+    },
+    /// 指定键位于已被封存的前缀之下
+    SealedNamespace {
+        key: String,
+        type_name: &'static str,
+    },
+    /// 注册表、该类型对应的表或该值自身的锁已中毒
+    Poisoned {
+        key: String,
+        type_name: &'static str,
+    },
+    /// 当前线程已持有所需的锁，继续执行会导致线程死锁
+    WouldDeadlock {
+        key: String,
+        type_name: &'static str,
+    },
+    /// 值不是所请求的类型，例如初始化失败的延迟初始化的值
+    Downcast {
+        key: String,
+        type_name: &'static str,
+    },
+    /// 键已被移除，但其值仍被 `Handle` 或守卫共享，无法取回其所有权
+    Shared {
+        key: String,
+        type_name: &'static str,
+    },
+}
+
+impl RegistryError {
+    /// 出错的键
+    pub fn key(&self) -> &str {
+        match self {
+            RegistryError::TypeNotRegistered { key, .. }
+            | RegistryError::KeyNotFound { key, .. }
+            | RegistryError::Frozen { key, .. }
+            | RegistryError::SealedNamespace { key, .. }
+            | RegistryError::Poisoned { key, .. }
+            | RegistryError::WouldDeadlock { key, .. }
+            | RegistryError::Downcast { key, .. }
+            | RegistryError::Shared { key, .. } => key,
+        }
+    }
+
+    /// 值的类型名称，由 `std::any::type_name` 给出
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RegistryError::TypeNotRegistered { type_name, .. }
+            | RegistryError::KeyNotFound { type_name, .. }
+            | RegistryError::Frozen { type_name, .. }
+            | RegistryError::SealedNamespace { type_name, .. }
+            | RegistryError::Poisoned { type_name, .. }
+            | RegistryError::WouldDeadlock { type_name, .. }
+            | RegistryError::Downcast { type_name, .. }
+            | RegistryError::Shared { type_name, .. } => type_name,
+        }
+    }
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            RegistryError::TypeNotRegistered { .. } => "type is not registered",
+            RegistryError::KeyNotFound { .. } => "key does not exist",
+            RegistryError::Frozen { .. } => "type is frozen",
+            RegistryError::SealedNamespace { .. } => "namespace is sealed",
+            RegistryError::Poisoned { .. } => "lock poisoned",
+            RegistryError::WouldDeadlock { .. } => "would deadlock",
+            RegistryError::Downcast { .. } => "value is not of the requested type",
+            RegistryError::Shared { .. } => "value is still shared by a handle",
+        };
+        write!(
+            f,
+            "key `{}` of type `{}`: {}",
+            self.key(),
+            self.type_name(),
+            reason
+        )
+    }
+}
+
+impl Error for RegistryError {}
@@ -0,0 +1,305 @@
+use std::{
+    any::{Any, TypeId},
+    fmt,
+    marker::PhantomData,
+    mem::{self, ManuallyDrop},
+    ops::{Deref, DerefMut},
+    sync::Arc,
+    thread,
+};
+
+use crate::{
+    insert_slot, intern, Context, ContextOperator, ReadAccess, RecordData, Registry, Shared, Value,
+    Wait, WriteAccess, _TABLE,
+};
+
+/// 在被销毁时从注册表中移除对应键的守卫，由 `Registry::register_guarded` 提供
+///
+/// 移除通过 `Registry::remove` 完成，因而即使发生 panic，栈展开时守卫也会移除其注册的键
+pub struct RegistrationGuard<T: 'static + Send + Sync + Any> {
+    name: Arc<str>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static + Send + Sync + Any> RegistrationGuard<T> {
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: intern(name),
+            _marker: PhantomData,
+        }
+    }
+
+    /// 获取守卫对应的键
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 销毁守卫而不移除其注册的键
+    pub fn forget(self) {
+        let mut this = ManuallyDrop::new(self);
+        drop(std::mem::take(&mut this.name));
+    }
+
+    /// 立即移除守卫注册的键并取回其对应的值
+    ///
+    /// 如果该键已被其他代码移除，则返回 `None`
+    pub fn take(self) -> Option<T> {
+        let mut this = ManuallyDrop::new(self);
+        let name = std::mem::take(&mut this.name);
+        Registry::<T>::remove(&name)
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> Drop for RegistrationGuard<T> {
+    fn drop(&mut self) {
+        Registry::<T>::remove(&self.name);
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> fmt::Debug for RegistrationGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegistrationGuard")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+// `Registry::with_override` 使用的守卫，销毁时将键恢复为被覆盖之前的状态
+pub(crate) struct OverrideGuard<T: 'static + Send + Sync + Any> {
+    name: Arc<str>,
+    previous: Option<Value>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static + Send + Sync + Any> OverrideGuard<T> {
+    pub(crate) fn new(name: &str, previous: Option<Value>) -> Self {
+        Self {
+            name: intern(name),
+            previous,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> Drop for OverrideGuard<T> {
+    fn drop(&mut self) {
+        let type_id = TypeId::of::<T>();
+        // 被覆盖的值在释放锁之后销毁；恢复不受冻结的影响
+        let Some(previous) = self.previous.take() else {
+            let _discarded = {
+                let Ok(Some(type_table)) = _TABLE.get(type_id) else {
+                    return;
+                };
+                let Ok(mut type_map) = type_table.write_shard(&self.name) else {
+                    return;
+                };
+                type_map.remove(&self.name)
+            };
+            return;
+        };
+        let slot = {
+            let Ok(Some(type_table)) = _TABLE.get(type_id) else {
+                return;
+            };
+            let Ok(type_map) = type_table.read_shard(&self.name) else {
+                return;
+            };
+            type_map.get(&self.name).map(|slot| Arc::clone(&slot.data))
+        };
+        // 键仍存在时在原有的锁中放回原值，否则重新插入
+        #[cfg(feature = "arc-swap")]
+        let previous = match &slot {
+            Some(slot) if slot.is_attached() => match slot.read_mostly::<T>() {
+                Some(read_mostly) => match previous.downcast::<T>() {
+                    Ok(previous) => {
+                        let _writer = read_mostly.lock();
+                        read_mostly.swap(*previous);
+                        slot.touch();
+                        return;
+                    }
+                    Err(previous) => previous,
+                },
+                None => previous,
+            },
+            _ => previous,
+        };
+        let previous = match slot {
+            Some(slot) => match slot.write() {
+                Ok(mut value) if slot.is_attached() => {
+                    let _temp = mem::replace(&mut *value, previous);
+                    drop(value);
+                    slot.touch();
+                    return;
+                }
+                _ => previous,
+            },
+            None => previous,
+        };
+        let _discarded = loop {
+            let Ok(Some(type_table)) = _TABLE.get(type_id) else {
+                return;
+            };
+            let Some(Ok(mut type_map)) = type_table.write_shard_attached(&self.name) else {
+                if type_table.is_detached() {
+                    continue;
+                }
+                return;
+            };
+            break insert_slot(&mut type_map, self.name.clone(), previous);
+        };
+    }
+}
+
+/// 持有注册表中某个值的读锁的守卫，由 `Registry::read_guard` 提供
+///
+/// 守卫仅持有该值自身的读锁，而不持有注册表及该类型对应的表的锁，因而同一类型的其他键在守卫存续期间仍可正常访问。
+/// 在调试模式下，守卫存续期间视同处于对该键的 `with` 闭包中，会导致死锁的嵌套写操作同样会被检查出来
+pub struct ReadGuard<T: 'static + Send + Sync + Any> {
+    // 借用 `data` 中的锁，因而必须先于 `data` 销毁
+    guard: ReadAccess<'static, T>,
+    data: Shared,
+    name: Arc<str>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static + Send + Sync + Any> ReadGuard<T> {
+    pub(crate) fn new(name: &str, data: Arc<RecordData>) -> Option<Self> {
+        let data = Shared::new(data);
+        let guard = data.read_as::<T>(Wait::Block).ok()?;
+        // SAFETY: 锁位于 `Arc` 所管理的堆内存中，其地址不会改变；守卫与 `Arc` 存放在同一个结构体中，且先于 `Arc` 销毁
+        let guard = unsafe { mem::transmute::<ReadAccess<'_, T>, ReadAccess<'static, T>>(guard) };
+        ContextOperator::push(Context::With(
+            intern(name),
+            TypeId::of::<T>(),
+            std::any::type_name::<T>(),
+        ));
+        Some(Self {
+            guard,
+            data,
+            name: intern(name),
+            _marker: PhantomData,
+        })
+    }
+
+    /// 获取守卫对应的键
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 判断该值是否已不在注册表中，即已被移除或被其他值替换
+    pub fn is_detached(&self) -> bool {
+        !self.data.is_attached()
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> Deref for ReadGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> Drop for ReadGuard<T> {
+    fn drop(&mut self) {
+        ContextOperator::remove(&Context::With(
+            mem::take(&mut self.name),
+            TypeId::of::<T>(),
+            std::any::type_name::<T>(),
+        ));
+    }
+}
+
+impl<T: 'static + Send + Sync + Any + fmt::Debug> fmt::Debug for ReadGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadGuard")
+            .field("name", &self.name)
+            .field("value", &**self)
+            .finish()
+    }
+}
+
+/// 持有注册表中某个值的写锁的守卫，由 `Registry::write_guard` 提供
+///
+/// 守卫仅持有该值自身的写锁，而不持有注册表及该类型对应的表的锁，因而同一类型的其他键在守卫存续期间仍可正常访问。
+/// 在调试模式下，守卫存续期间视同处于对该键的 `apply` 闭包中；守卫被销毁时（包括 panic 导致的栈展开）会记录一次修改并移除对应的上下文。
+/// 以 `Registry::register_read_mostly` 注册的值的修改在守卫被销毁时发布，panic 导致的栈展开时则被丢弃
+pub struct WriteGuard<T: 'static + Send + Sync + Any> {
+    // 借用 `data` 中的锁，因而必须先于 `data` 销毁；仅在 `drop` 中被取出
+    guard: ManuallyDrop<WriteAccess<'static, T>>,
+    data: Shared,
+    name: Arc<str>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static + Send + Sync + Any> WriteGuard<T> {
+    pub(crate) fn new(name: &str, data: Arc<RecordData>) -> Option<Self> {
+        let data = Shared::new(data);
+        let guard = data.write_as::<T>(Wait::Block).ok()?;
+        // SAFETY: 锁位于 `Arc` 所管理的堆内存中，其地址不会改变；守卫与 `Arc` 存放在同一个结构体中，且先于 `Arc` 销毁
+        let guard = unsafe { mem::transmute::<WriteAccess<'_, T>, WriteAccess<'static, T>>(guard) };
+        ContextOperator::push(Context::Apply(
+            intern(name),
+            TypeId::of::<T>(),
+            std::any::type_name::<T>(),
+        ));
+        Some(Self {
+            guard: ManuallyDrop::new(guard),
+            data,
+            name: intern(name),
+            _marker: PhantomData,
+        })
+    }
+
+    /// 获取守卫对应的键
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 判断该值是否已不在注册表中，即已被移除或被其他值替换
+    pub fn is_detached(&self) -> bool {
+        !self.data.is_attached()
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> Deref for WriteGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> DerefMut for WriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> Drop for WriteGuard<T> {
+    fn drop(&mut self) {
+        // SAFETY: `guard` 仅在此处被取出，之后不再被访问
+        let guard = unsafe { ManuallyDrop::take(&mut self.guard) };
+        if thread::panicking() {
+            // 值自身的锁中的修改无法撤销，仍记录一次修改；副本上的修改随 `guard` 一同被丢弃
+            self.data.touch();
+            drop(guard);
+        } else {
+            guard.commit();
+        }
+        ContextOperator::remove(&Context::Apply(
+            mem::take(&mut self.name),
+            TypeId::of::<T>(),
+            std::any::type_name::<T>(),
+        ));
+    }
+}
+
+impl<T: 'static + Send + Sync + Any + fmt::Debug> fmt::Debug for WriteGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteGuard")
+            .field("name", &self.name)
+            .field("value", &**self)
+            .finish()
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    any::{Any, TypeId},
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+};
+
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+use crate::Lock;
+use crate::{intern, Context, ContextOperator, RecordData, Shared, Wait};
+
+/// 注册表中某个值的句柄，由 `Registry::handle` 提供
+///
+/// 句柄与注册表共享该值，访问时无需获取注册表及该类型对应的表的锁，也无需查找键；
+/// 访问时仍会获取该值自身的锁，并与通过注册表访问该值时一样参与死锁检查
+pub struct Handle<T> {
+    name: Arc<str>,
+    data: Shared,
+    frozen: Arc<AtomicBool>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub(crate) fn new(name: &str, data: Arc<RecordData>, frozen: Arc<AtomicBool>) -> Self {
+        Self {
+            name: intern(name),
+            data: Shared::new(data),
+            frozen,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 获取句柄对应的键
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 判断该值是否已不在注册表中，即已被移除或被其他值替换
+    pub fn is_detached(&self) -> bool {
+        !self.data.is_attached()
+    }
+
+    /// 创建该值的弱句柄，弱句柄不会使该值在被移除后继续存活
+    pub fn downgrade(&self) -> WeakHandle<T> {
+        WeakHandle {
+            name: self.name.clone(),
+            data: Arc::downgrade(&self.data),
+            frozen: Arc::clone(&self.frozen),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> Handle<T> {
+    /// 向句柄对应的值应用一个函数，该函数仅能读取该值
+    ///
+    /// 如果锁已中毒，则返回 `None`；否则，返回闭包函数的返回值
+    pub fn with<R, F: FnOnce(&T) -> R>(&self, func: F) -> Option<R> {
+        let type_id = TypeId::of::<T>();
+        check_deadlock!(ref T:&self.name);
+        let value = self.data.read_as::<T>(Wait::Block).ok()?;
+        let frame = ContextOperator::enter(Context::With(
+            self.name.clone(),
+            type_id,
+            std::any::type_name::<T>(),
+        ));
+        let ret = Some(func(&value));
+        drop(frame);
+        ret
+    }
+
+    /// 向句柄对应的值应用一个函数，该函数可以修改该值
+    ///
+    /// 如果该类型已被冻结或锁已中毒，则返回 `None`；否则，返回闭包函数的返回值
+    pub fn apply<R, F: FnOnce(&mut T) -> R>(&self, func: F) -> Option<R> {
+        if self.frozen.load(Ordering::Acquire) {
+            return None;
+        }
+        let type_id = TypeId::of::<T>();
+        check_deadlock!(mut T:&self.name;Lock::Key);
+        let mut value = self.data.write_as::<T>(Wait::Block).ok()?;
+        let frame = ContextOperator::enter(Context::Apply(
+            self.name.clone(),
+            type_id,
+            std::any::type_name::<T>(),
+        ));
+        let ret = Some(func(&mut value));
+        drop(frame);
+        value.commit();
+        ret
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            data: self.data.clone(),
+            frozen: Arc::clone(&self.frozen),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("name", &self.name)
+            .field("detached", &self.is_detached())
+            .finish_non_exhaustive()
+    }
+}
+
+/// 注册表中某个值的弱句柄，由 `Registry::weak_handle` 或 `Handle::downgrade` 提供
+///
+/// 与 `Handle` 不同，弱句柄不会使该值存活：该值从注册表中被移除（或被替换）且不存在任何 `Handle` 时，通过弱句柄的访问将返回 `None`
+pub struct WeakHandle<T> {
+    name: Arc<str>,
+    data: Weak<RecordData>,
+    frozen: Arc<AtomicBool>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> WeakHandle<T> {
+    /// 获取弱句柄对应的键
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 尝试将弱句柄升级为句柄；如果该值已被销毁，则返回 `None`
+    pub fn upgrade(&self) -> Option<Handle<T>> {
+        Some(Handle {
+            name: self.name.clone(),
+            data: Shared::new(self.data.upgrade()?),
+            frozen: Arc::clone(&self.frozen),
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> WeakHandle<T> {
+    /// 与 `Handle::with` 相同；如果该值已被销毁，则返回 `None`
+    pub fn with<R, F: FnOnce(&T) -> R>(&self, func: F) -> Option<R> {
+        self.upgrade()?.with(func)
+    }
+
+    /// 与 `Handle::apply` 相同；如果该值已被销毁，则返回 `None`
+    pub fn apply<R, F: FnOnce(&mut T) -> R>(&self, func: F) -> Option<R> {
+        self.upgrade()?.apply(func)
+    }
+}
+
+impl<T> Clone for WeakHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            data: Weak::clone(&self.data),
+            frozen: Arc::clone(&self.frozen),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for WeakHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakHandle")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Weak,
+    },
+};
+
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+use crate::Lock;
+use crate::{intern, Context, ContextOperator, RecordData, Wait, _TABLE};
+
+// 热键缓存的条目，以及判断其是否仍然有效所需的信息
+struct Cached {
+    // 缓存时该类型对应的表的修改计数
+    generation: u64,
+    table_generation: Arc<AtomicU64>,
+    frozen: Arc<AtomicBool>,
+    data: Weak<RecordData>,
+}
+
+/// 缓存了所对应条目的键，由 `Registry::hot_key` 提供
+///
+/// 缓存仍然有效时，访问该值无需获取注册表及该类型对应的表的锁，也无需查找类型与键；
+/// 该类型对应的表发生任何修改后缓存失效，下一次访问时重新查找该键。缓存仅持有条目的弱引用，不会使被移除的值继续存活。
+/// 热键可以被移动到其他线程，但不能在线程之间共享
+pub struct HotKey<T> {
+    name: Arc<str>,
+    cache: RefCell<Option<Cached>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> HotKey<T> {
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: intern(name),
+            cache: RefCell::new(None),
+            _marker: PhantomData,
+        }
+    }
+
+    /// 获取热键对应的键
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> HotKey<T> {
+    // 查找该键当前对应的条目及该类型是否已被冻结；缓存仍然有效时不获取注册表及该类型对应的表的锁
+    fn lookup(&self) -> Option<(Arc<RecordData>, bool)> {
+        let mut cache = self.cache.borrow_mut();
+        if let Some(cached) = &*cache {
+            if cached.generation == cached.table_generation.load(Ordering::Acquire) {
+                // 条目已不在表中时（如整个表被移除），其状态同样为已分离
+                if let Some(data) = cached.data.upgrade().filter(|data| data.is_attached()) {
+                    return Some((data, cached.frozen.load(Ordering::Acquire)));
+                }
+            }
+        }
+        *cache = None;
+        let (data, cached, frozen) = _TABLE
+            .with_table(TypeId::of::<T>(), |table| {
+                let type_map = table.read_shard(&self.name).ok()?;
+                let data = Arc::clone(&type_map.get(&self.name)?.data);
+                let cached = Cached {
+                    generation: table.generation(),
+                    table_generation: Arc::clone(&table.generation),
+                    frozen: Arc::clone(&table.frozen),
+                    data: Arc::downgrade(&data),
+                };
+                Some((data, cached, table.is_frozen()))
+            })
+            .ok()
+            .flatten()
+            .flatten()?;
+        *cache = Some(cached);
+        Some((data, frozen))
+    }
+
+    /// 向该键对应的值应用一个函数，该函数仅能读取该值
+    ///
+    /// 如果键不存在或锁已中毒，则返回 `None`；否则，返回闭包函数的返回值
+    pub fn with<R, F: FnOnce(&T) -> R>(&self, func: F) -> Option<R> {
+        let type_id = TypeId::of::<T>();
+        check_deadlock!(ref T:&self.name);
+        let (data, _) = self.lookup()?;
+        let value = data.read_as::<T>(Wait::Block).ok()?;
+        let frame = ContextOperator::enter(Context::With(
+            self.name.clone(),
+            type_id,
+            std::any::type_name::<T>(),
+        ));
+        let ret = Some(func(&value));
+        drop(frame);
+        ret
+    }
+
+    /// 向该键对应的值应用一个函数，该函数可以修改该值
+    ///
+    /// 如果键不存在、该类型已被冻结或锁已中毒，则返回 `None`；否则，返回闭包函数的返回值
+    pub fn apply<R, F: FnOnce(&mut T) -> R>(&self, func: F) -> Option<R> {
+        let type_id = TypeId::of::<T>();
+        check_deadlock!(mut T:&self.name;Lock::Key);
+        let (data, frozen) = self.lookup()?;
+        if frozen {
+            return None;
+        }
+        let mut value = data.write_as::<T>(Wait::Block).ok()?;
+        let frame = ContextOperator::enter(Context::Apply(
+            self.name.clone(),
+            type_id,
+            std::any::type_name::<T>(),
+        ));
+        let ret = Some(func(&mut value));
+        drop(frame);
+        value.commit();
+        ret
+    }
+}
+
+impl<T> Clone for HotKey<T> {
+    fn clone(&self) -> Self {
+        Self::new(&self.name)
+    }
+}
+
+impl<T> fmt::Debug for HotKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HotKey").field(&self.name).finish()
+    }
+}
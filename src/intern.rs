@@ -0,0 +1,43 @@
+use std::{collections::HashSet, sync::Arc};
+
+use crate::sync::{lazy_static, RwLock};
+
+// 驻留的键；各类型对应的表、上下文访问栈与条目的元数据共享同一个键的同一份分配
+struct Interner {
+    names: HashSet<Arc<str>>,
+    // 集合达到该大小时清理不再被使用的键，之后将其设为清理后大小的两倍，从而清理的开销均摊到每次驻留
+    sweep_at: usize,
+}
+
+const MIN_SWEEP_AT: usize = 64;
+
+lazy_static! {
+    static ref _INTERNED: RwLock<Interner> = RwLock::new(Interner {
+        names: HashSet::new(),
+        sweep_at: MIN_SWEEP_AT,
+    });
+}
+
+// 获取与 `name` 相等的共享键；该键已被驻留时只增加其引用计数而不分配内存
+pub(crate) fn intern(name: &str) -> Arc<str> {
+    if let Some(interned) = _INTERNED
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .names
+        .get(name)
+    {
+        return Arc::clone(interned);
+    }
+    let mut interner = _INTERNED.write().unwrap_or_else(|e| e.into_inner());
+    if let Some(interned) = interner.names.get(name) {
+        return Arc::clone(interned);
+    }
+    if interner.names.len() >= interner.sweep_at {
+        // 仅被驻留集合引用的键已不在任何表或上下文中
+        interner.names.retain(|name| Arc::strong_count(name) > 1);
+        interner.sweep_at = (interner.names.len() * 2).max(MIN_SWEEP_AT);
+    }
+    let interned: Arc<str> = Arc::from(name);
+    interner.names.insert(Arc::clone(&interned));
+    interned
+}
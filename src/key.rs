@@ -0,0 +1,106 @@
+use std::{any::Any, fmt, marker::PhantomData};
+
+use crate::Registry;
+
+/// 带有值类型的键
+///
+/// 将键与其对应的值的类型绑定在一起，从而在编译期避免以错误的类型访问键对应的值
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{id, Key};
+///
+/// struct Note {
+///     text: String,
+/// }
+///
+/// const ROOT: &str = id!(app);
+/// const NOTE: Key<Note> = Key::new(id!(@ROOT.note));
+/// const COUNT: Key<i32> = Key::new(id!(@ROOT.count));
+///
+/// NOTE.register(Note { text: String::from("hello") }).unwrap();
+/// COUNT.register(0).unwrap();
+///
+/// COUNT.apply(|v| *v += 1);
+/// assert_eq!(COUNT.with(|v| *v), Some(1));
+/// assert_eq!(NOTE.with(|n| n.text.len()), Some(5));
+/// assert_eq!(NOTE.name(), ".app.note");
+///
+/// assert!(NOTE.exists());
+/// assert_eq!(NOTE.remove().map(|n| n.text), Some(String::from("hello")));
+/// assert!(!NOTE.exists());
+/// ```
+///
+/// 以错误的类型访问键对应的值会导致编译错误，而不是在运行时返回 `None`：
+///
+/// ```rust,compile_fail
+/// use gom::{id, Key};
+///
+/// const COUNT: Key<i32> = Key::new(id!(app.count));
+///
+/// COUNT.register(0).unwrap();
+/// let value: Option<f64> = COUNT.with(|v: &f64| *v);
+/// ```
+pub struct Key<T> {
+    name: &'static str,
+    // 使用 `fn() -> T` 使得 `Key<T>` 始终满足 `Send`、`Sync` 与 `Copy`，而与 `T` 无关
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Key<T> {
+    /// 使用给定的字符串创建键
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 获取键对应的字符串
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T> Clone for Key<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Key<T> {}
+
+impl<T> fmt::Debug for Key<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Key").field(&self.name).finish()
+    }
+}
+
+impl<T: 'static + Send + Sync + Any> Key<T> {
+    /// 与 `Registry::<T>::register` 相同
+    #[allow(clippy::result_unit_err)]
+    pub fn register(&self, value: T) -> Result<(), ()> {
+        Registry::register(self.name, value)
+    }
+
+    /// 与 `Registry::<T>::with` 相同
+    pub fn with<R, F: FnOnce(&T) -> R>(&self, func: F) -> Option<R> {
+        Registry::<T>::with(self.name, func)
+    }
+
+    /// 与 `Registry::<T>::apply` 相同
+    pub fn apply<R, F: FnOnce(&mut T) -> R>(&self, func: F) -> Option<R> {
+        Registry::<T>::apply(self.name, func)
+    }
+
+    /// 与 `Registry::<T>::remove` 相同
+    pub fn remove(&self) -> Option<T> {
+        Registry::<T>::remove(self.name)
+    }
+
+    /// 与 `Registry::<T>::exists` 相同
+    pub fn exists(&self) -> bool {
+        Registry::<T>::exists(self.name)
+    }
+}
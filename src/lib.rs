@@ -1,331 +1,6825 @@
 use core::panic;
 use std::{
     any::{Any, TypeId},
-    cell::RefCell,
-    collections::HashMap,
+    cell::{Cell, OnceCell, RefCell},
+    collections::{
+        hash_map::{self, RandomState},
+        HashMap, HashSet,
+    },
+    fmt,
+    hash::{BuildHasherDefault, Hasher},
     marker::PhantomData,
-    sync::RwLock,
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, LockResult, PoisonError, TryLockError, TryLockResult, Weak,
+    },
+    time::{Duration, Instant},
 };
 
-use lazy_static::lazy_static;
+mod access;
+#[cfg(feature = "async")]
+mod async_registry;
+mod entry;
+mod error;
+mod guard;
+mod intern;
+mod key;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+mod numeric;
+mod pattern;
+mod read_cache;
+#[cfg(feature = "arc-swap")]
+mod read_mostly;
+mod shard;
+mod slot;
+mod slow;
+mod sync;
+mod type_registry;
+use access::{AccessError, Held, ReadAccess, Wait, WriteAccess};
+#[cfg(feature = "async")]
+pub use async_registry::{AsyncRegistry, ChangeEvent, Changed};
+pub use entry::{Entry, ValueMut};
+pub use error::*;
+use guard::OverrideGuard;
+pub use guard::{ReadGuard, RegistrationGuard, WriteGuard};
+use intern::intern;
+pub use key::Key;
+pub use numeric::Numeric;
+use pattern::Pattern;
+#[cfg(feature = "arc-swap")]
+use read_mostly::{Published, ReadMostly};
+use shard::{shard_index, Shards, SHARDS};
+pub use slot::Slot;
+use slow::Stopwatch;
+pub use slow::{clear_slow_access_hook, set_slow_access_hook, SlowAccessInfo, SlowAccessKind};
+#[cfg(all(loom, any(debug_assertions, feature = "deadlock-detection")))]
+use sync::LocalCell;
+#[cfg(loom)]
+use sync::LocalRefCell;
+use sync::{
+    lazy_static, thread_local, yield_now, Condvar, Mutex, RwLock, RwLockReadGuard,
+    RwLockWriteGuard, TimedLock,
+};
+pub use type_registry::TypeRegistry;
 
 macro_rules! thread_deadlock {
-    () => {
-        panic!("Thread deadlock!")
+    ($($arg:tt)+) => {
+        panic!("{}", $crate::deadlock_message(&format!($($arg)+)))
     };
 }
 
-lazy_static! {
-    static ref _TABLE: RwLock<HashMap<TypeId, RwLock<HashMap<String, RwLock<Box<dyn Any + Send + Sync>>>>>> =
-        RwLock::new(HashMap::new());
+// 以指定键与类型构造 `RegistryError` 的指定变体
+macro_rules! registry_error {
+    ($kind:ident, $name:expr, $type:ty) => {
+        RegistryError::$kind {
+            key: String::from($name),
+            type_name: std::any::type_name::<$type>(),
+        }
+    };
 }
 
-thread_local! {
-    static _LOCAL_TABLE: RefCell<HashMap<TypeId, HashMap<String, Box<dyn Any>>>> =
-        RefCell::new(HashMap::new());
-}
+type Value = Box<dyn Any + Send + Sync>;
+type TypeMap = HashMap<Arc<str>, Record>;
+type OnRemove = Box<dyn FnOnce(&str, &mut Value) + Send>;
+type Init = Box<dyn FnOnce() -> Value + Send>;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Context {
-    With(String, TypeId),
-    Apply(String, TypeId),
-}
+// 尚未初始化的值，由 `Registry::register_lazy` 注册
+struct Lazy(Mutex<Option<Init>>);
 
-enum Lock {
-    Global,
-    Type,
-    Key,
-}
+// 构造函数，由 `Registry::register_factory` 注册在与值相互独立的表中
+struct Factory<T>(Box<dyn Fn() -> T + Send + Sync>);
+type LocalTypeMap = HashMap<String, Box<dyn Any>>;
 
-thread_local! {
-    // 上下文访问栈
-    static CONTEXT: RefCell<Vec<Context>> = RefCell::new(Vec::new());
+// 全局递增的修改计数
+static TICK: AtomicU64 = AtomicU64::new(0);
+// 全局递增的注册序号
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+// 全局递增的表修改计数，每次获取某一类型对应的表的写锁时更新；`HotKey` 据此判断其缓存的条目是否仍然有效
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+// 启用优先写入时，新的读取者为等待中的写入者让出执行的最长时间
+const WRITE_PRIORITY_WAIT: Duration = Duration::from_millis(10);
+
+// 注册表中某个键对应的值，同时记录其最后修改的时间、修改计数、版本号与注册序号；`Handle` 通过 `Arc` 共享该数据
+struct RecordData {
+    value: RwLock<Value>,
+    modified: Mutex<(Instant, u64)>,
+    version: AtomicU64,
+    registered: u64,
+    attached: AtomicBool,
+    // 值是否仍为尚未初始化的 `Lazy`
+    pending: AtomicBool,
+    // 条目离开注册表时执行的回调函数，以及回调函数将收到的键
+    on_remove: Mutex<Option<(Arc<str>, OnRemove)>>,
+    // 共享该数据的 `Handle` 与守卫的数量
+    shared: AtomicUsize,
+    // 是否优先写入，以及正在等待写锁的写入者的数量
+    write_priority: AtomicBool,
+    writers_waiting: AtomicUsize,
+    // 该值的类型的锁竞争计数器
+    #[cfg(feature = "metrics")]
+    metrics: Arc<metrics::Counters>,
+    // 以 `register_read_mostly` 注册的值，此时 `value` 中仅为占位值 `Swapped`
+    #[cfg(feature = "arc-swap")]
+    read_mostly: Option<Box<dyn Published>>,
 }
 
-struct ContextOperator;
-impl ContextOperator {
-    fn push(ctx: Context) {
-        CONTEXT.with(|ctx_cell| {
-            ctx_cell.borrow_mut().push(ctx);
-        });
+// 值的所有权已被 `remove` 等函数取回后留在原处的占位值
+struct Taken;
+
+// 以 `register_read_mostly` 注册的条目留在值自身的锁中的占位值
+#[cfg(feature = "arc-swap")]
+struct Swapped;
+
+impl RecordData {
+    fn now() -> (Instant, u64) {
+        (Instant::now(), TICK.fetch_add(1, Ordering::Relaxed) + 1)
     }
 
-    fn pop() {
-        CONTEXT.with(|ctx_cell| ctx_cell.borrow_mut().pop());
+    // 在写锁下执行尚未初始化的值的初始化函数，因而即使多个线程同时首次访问，初始化函数也只会执行一次
+    fn force(&self) {
+        if !self.pending.load(Ordering::Acquire) {
+            return;
+        }
+        let Ok(mut value) = self.value.write() else {
+            return;
+        };
+        self.force_locked(&mut value);
     }
 
-    fn cannot_lock_write_lock<T: 'static>(name: &str, lock: Lock) -> bool {
-        match lock {
-            Lock::Global => CONTEXT.with_borrow(|v| !v.is_empty()),
-            Lock::Type => CONTEXT.with_borrow(|v| {
-                v.iter().any(|x| match x {
-                    Context::With(_, type_id) | Context::Apply(_, type_id) => {
-                        type_id == &TypeId::of::<T>()
-                    }
-                })
-            }),
-            Lock::Key => CONTEXT.with_borrow(|v| {
-                v.iter().any(|x| match x {
-                    Context::With(key, type_id) | Context::Apply(key, type_id) => {
-                        key == name && type_id == &TypeId::of::<T>()
-                    }
-                })
-            }),
+    // 与 `force` 相同，但调用方已持有写锁
+    fn force_locked(&self, value: &mut Value) {
+        if let Some(init) = value
+            .downcast_mut::<Lazy>()
+            .and_then(|lazy| lazy.0.get_mut().ok()?.take())
+        {
+            *value = init();
+        }
+        self.pending.store(false, Ordering::Release);
+    }
+
+    fn read(&self) -> LockResult<RwLockReadGuard<'_, Value>> {
+        self.force();
+        self.yield_to_writers();
+        self.lock_read()
+    }
+
+    fn write(&self) -> LockResult<RwLockWriteGuard<'_, Value>> {
+        self.force();
+        if !self.write_priority.load(Ordering::Relaxed) {
+            return self.lock_write();
+        }
+        self.writers_waiting.fetch_add(1, Ordering::SeqCst);
+        let value = self.lock_write();
+        self.writers_waiting.fetch_sub(1, Ordering::SeqCst);
+        value
+    }
+
+    // 获取值自身的锁；启用 `metrics` 特性时记录获取锁的次数与阻塞等待的时间
+    fn lock_read(&self) -> LockResult<RwLockReadGuard<'_, Value>> {
+        #[cfg(feature = "metrics")]
+        return self
+            .metrics
+            .acquire(|| self.value.try_read(), || self.value.read());
+        #[cfg(not(feature = "metrics"))]
+        self.value.read()
+    }
+
+    fn lock_write(&self) -> LockResult<RwLockWriteGuard<'_, Value>> {
+        #[cfg(feature = "metrics")]
+        return self
+            .metrics
+            .acquire(|| self.value.try_write(), || self.value.write());
+        #[cfg(not(feature = "metrics"))]
+        self.value.write()
+    }
+
+    // 启用优先写入且有写入者正在等待写锁时，新的读取者让出执行，直至写入者获取写锁；
+    // 等待的时间有上限，因而当前线程已持有该值的读锁（如嵌套的 `with`）时不会与写入者相互等待
+    fn yield_to_writers(&self) {
+        if !self.write_priority.load(Ordering::Relaxed) {
+            return;
+        }
+        let deadline = Instant::now() + WRITE_PRIORITY_WAIT;
+        while self.writers_waiting.load(Ordering::SeqCst) > 0 && Instant::now() < deadline {
+            yield_now();
+        }
+    }
+
+    // 与 `read` 相同，但锁被占用时立即返回；尚未初始化的值仅在能够立即获取写锁时初始化
+    fn try_read(&self) -> TryLockResult<RwLockReadGuard<'_, Value>> {
+        if self.pending.load(Ordering::Acquire) {
+            match self.value.try_write() {
+                Ok(mut value) => self.force_locked(&mut value),
+                Err(TryLockError::WouldBlock) => return Err(TryLockError::WouldBlock),
+                // 中毒的锁由随后的 `try_read` 报告
+                Err(TryLockError::Poisoned(_)) => {}
+            }
+        }
+        self.value.try_read()
+    }
+
+    // 与 `write` 相同，但锁被占用时立即返回
+    fn try_write(&self) -> TryLockResult<RwLockWriteGuard<'_, Value>> {
+        let mut value = self.value.try_write()?;
+        self.force_locked(&mut value);
+        Ok(value)
+    }
+
+    // 与 `try_read` 相同，但锁被占用时等待至截止时间
+    fn try_read_until(&self, deadline: Instant) -> TryLockResult<RwLockReadGuard<'_, Value>> {
+        if self.pending.load(Ordering::Acquire) {
+            match self.value.try_write_until(deadline) {
+                Ok(mut value) => self.force_locked(&mut value),
+                Err(TryLockError::WouldBlock) => return Err(TryLockError::WouldBlock),
+                Err(TryLockError::Poisoned(_)) => {}
+            }
         }
+        self.value.try_read_until(deadline)
+    }
+
+    // 与 `try_write` 相同，但锁被占用时等待至截止时间
+    fn try_write_until(&self, deadline: Instant) -> TryLockResult<RwLockWriteGuard<'_, Value>> {
+        let mut value = self.value.try_write_until(deadline)?;
+        self.force_locked(&mut value);
+        Ok(value)
+    }
+
+    // 以 `register_read_mostly` 注册的 `T` 类型的值
+    #[cfg(feature = "arc-swap")]
+    fn read_mostly<T: 'static>(&self) -> Option<&ReadMostly<T>> {
+        self.read_mostly.as_ref()?.as_any().downcast_ref()
+    }
+
+    // 记录一次修改，应在持有写锁时调用
+    fn touch(&self) {
+        *self.modified.lock().unwrap_or_else(|e| e.into_inner()) = Self::now();
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    fn modified(&self) -> (Instant, u64) {
+        *self.modified.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_attached(&self) -> bool {
+        self.attached.load(Ordering::Acquire)
+    }
+
+    // 取出条目离开注册表时执行的回调函数及其将收到的键
+    fn take_on_remove(&self) -> Option<(Arc<str>, OnRemove)> {
+        self.on_remove
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
+
+    fn has_on_remove(&self) -> bool {
+        self.on_remove
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some()
     }
 }
 
-// 检查如果获取写锁是否会导致死锁
-fn check_write_deadlock<T: 'static>(name: &str, lock: Lock) {
-    if ContextOperator::cannot_lock_write_lock::<T>(name, lock) {
-        thread_deadlock!();
+// `Handle` 与守卫对条目数据的共享引用；与 `with` 等函数执行期间临时持有的引用不同，仍存在共享引用时 `remove` 等函数不会取回值的所有权
+struct Shared(Arc<RecordData>);
+
+impl Shared {
+    fn new(data: Arc<RecordData>) -> Self {
+        data.shared.fetch_add(1, Ordering::AcqRel);
+        Self(data)
     }
 }
 
-// 检查如果获取读锁是否会导致死锁
-fn check_read_deadlock<T: 'static>(name: &str) {
-    if CONTEXT.with_borrow(|v| {
-        v.iter().any(|x| match x {
-            Context::Apply(s, type_id) => s == name && type_id == &TypeId::of::<T>(),
-            _ => false,
-        })
-    }) {
-        thread_deadlock!();
+impl Clone for Shared {
+    fn clone(&self) -> Self {
+        Self::new(Arc::clone(&self.0))
     }
 }
 
-#[cfg(debug_assertions)]
-macro_rules! check_deadlock {
-    (mut $type:ty : $name:expr ; $em:expr) => {
-        $crate::check_write_deadlock::<$type>($name, $em);
-    };
-    (ref $type:ty : $name:expr) => {
-        $crate::check_read_deadlock::<$type>($name);
-    };
+impl std::ops::Deref for Shared {
+    type Target = Arc<RecordData>;
+
+    fn deref(&self) -> &Arc<RecordData> {
+        &self.0
+    }
 }
 
-#[cfg(not(debug_assertions))]
-macro_rules! check_deadlock {
-    (mut $type:ty : $name:expr ; $em:expr) => {};
-    (ref $type:ty : $name:expr) => {};
+impl Drop for Shared {
+    fn drop(&mut self) {
+        self.0.shared.fetch_sub(1, Ordering::AcqRel);
+    }
 }
 
-/// 用于访问注册表的类型
-///
-/// # 注解
-///
-/// + 其索引方式是：`类型-键` 唯一，因而同一个键可以对应多个不同类型的值
-/// + 如果闭包中使用了不恰当的嵌套，可能会导致线程死锁
-pub struct Registry<T> {
-    _marker: PhantomData<T>,
+// 注册表中某个键对应的条目；条目被移出注册表并销毁时，共享该值的 `Handle` 将被标记为已分离
+struct Record {
+    data: Arc<RecordData>,
+    // 通过 `Registry::acquire` 取得该条目的所有者数量，仅在持有该类型对应的表的写锁时修改
+    refs: usize,
+    // 通过 `Registry::slot_of` 为该条目分配的编号，以及编号所在的表
+    arena: Option<(Weak<RwLock<Arena>>, usize)>,
 }
 
-impl<T: 'static + Send + Sync + Any> Registry<T> {
-    fn _register(name: &str, value: T) -> Option<()> {
-        let type_id = TypeId::of::<T>();
-        let has_type = {
-            let map = _TABLE.read().ok()?;
-            map.contains_key(&type_id)
-        };
-        if !has_type {
-            check_deadlock!(mut T:name;Lock::Global);
-            let mut map = _TABLE.write().ok()?;
-            map.insert(type_id, RwLock::new(HashMap::new()));
+impl Record {
+    fn new(value: Value) -> Self {
+        Self::with_version(value, 0)
+    }
+
+    fn with_version(value: Value, version: u64) -> Self {
+        #[cfg(feature = "metrics")]
+        let type_id = (*value).type_id();
+        Self {
+            data: Arc::new(RecordData {
+                value: RwLock::new(value),
+                modified: Mutex::new(RecordData::now()),
+                version: AtomicU64::new(version),
+                registered: SEQUENCE.fetch_add(1, Ordering::Relaxed),
+                attached: AtomicBool::new(true),
+                pending: AtomicBool::new(false),
+                on_remove: Mutex::new(None),
+                shared: AtomicUsize::new(0),
+                write_priority: AtomicBool::new(false),
+                writers_waiting: AtomicUsize::new(0),
+                #[cfg(feature = "metrics")]
+                metrics: metrics::counters(type_id),
+                #[cfg(feature = "arc-swap")]
+                read_mostly: None,
+            }),
+            refs: 0,
+            arena: None,
         }
-        let map = _TABLE.read().ok()?;
-        check_deadlock!(mut T:name;Lock::Type);
-        let mut type_map = map.get(&type_id)?.write().ok()?;
-        type_map.insert(String::from(name), RwLock::new(Box::new(value)));
-        Some(())
     }
 
-    /// 向注册表中注册一个新值
-    ///
-    /// 如果相同的键已存在，那么旧值将会被新值替换
-    ///
-    /// # 示例
-    ///
-    /// ```rust
-    /// use gom::Registry;
-    ///
-    /// Registry::<i32>::register("my_key", 42);
-    /// Registry::register("my_key", 64);
-    /// ```
-    pub fn register(name: &str, value: T) -> Result<(), ()> {
-        Self::_register(name, value).ok_or(())
+    // 创建尚未初始化的条目，值在首次被访问时由初始化函数生成
+    fn lazy<T: 'static>(init: Init) -> Self {
+        let slot = Self::new(Box::new(Lazy(Mutex::new(Some(init))))).typed::<T>();
+        slot.pending.store(true, Ordering::Release);
+        slot
     }
 
-    /// 从注册表中移除指定键对应的值
-    ///
-    /// 如果键不存在，则返回 `None`
-    ///
-    /// # 示例
-    ///
-    /// ```rust
-    /// use gom::Registry;
-    ///
-    /// Registry::<i32>::register("my_key", 42);
-    /// assert_eq!(Registry::<i32>::remove("my_key"), Some(42));
-    /// assert_eq!(Registry::<i32>::remove("my_key"), None);
-    /// ```
-    pub fn remove(name: &str) -> Option<T> {
-        let type_id = TypeId::of::<T>();
-        let lock_value = {
-            let map = _TABLE.read().ok()?;
-            let type_map = map.get(&type_id)?;
-            check_deadlock!(mut T:name;Lock::Type);
-            let mut type_map = type_map.write().ok()?;
-            type_map.remove(name)?
-        };
-        let value = lock_value.into_inner().ok()?;
-        let type_value = value.downcast::<T>().ok()?;
-        Some(*type_value)
+    // 创建以 `ArcSwap` 保存值的条目
+    #[cfg(feature = "arc-swap")]
+    fn read_mostly<T: 'static + Send + Sync + Clone>(value: T) -> Self {
+        let mut slot = Self::new(Box::new(Swapped)).typed::<T>();
+        Arc::get_mut(&mut slot.data)
+            .expect("record is not shared yet")
+            .read_mostly = Some(Box::new(ReadMostly::new(value)));
+        slot
     }
 
-    fn _exists(name: &str) -> Option<bool> {
-        let type_id = TypeId::of::<T>();
-        let map = _TABLE.read().ok()?;
-        let lock_type_map = map.get(&type_id)?;
-        let type_map = lock_type_map.read().ok()?;
-        Some(type_map.contains_key(name))
+    // 值中暂存的是占位值时，按实际的类型记录锁竞争指标
+    #[cfg(feature = "metrics")]
+    fn typed<T: 'static>(mut self) -> Self {
+        Arc::get_mut(&mut self.data)
+            .expect("record is not shared yet")
+            .metrics = metrics::counters(TypeId::of::<T>());
+        self
     }
 
-    /// 判断指定键是否存在于注册表中
-    ///
-    /// # 示例
-    ///
-    /// ```rust
-    /// use gom::Registry;
-    ///
-    /// Registry::<i32>::register("my_key", 42);
-    /// assert_eq!(Registry::<i32>::exists("my_key"), true);
-    /// assert_eq!(Registry::<i32>::exists("other_key"), false);
-    /// ```
-    pub fn exists(name: &str) -> bool {
-        Self::_exists(name).unwrap_or(false)
+    #[cfg(not(feature = "metrics"))]
+    #[allow(clippy::extra_unused_type_parameters)]
+    fn typed<T: 'static>(self) -> Self {
+        self
     }
 
-    /// 向注册表中的指定键应用一个函数，该函数可以修改注册表中的值
-    ///
-    /// 如果键不存在，则返回 `None`；否则，返回闭包函数的返回值
-    ///
-    /// # 示例
-    /// ```rust
-    /// use gom::Registry;
-    ///
-    /// Registry::<i32>::register("my_key", 42);
-    /// assert_eq!(Registry::<i32>::apply("my_key", |v| { *v += 1; *v }), Some(43));
-    /// assert_eq!(Registry::<i32>::apply("other_key", |v| *v += 1), None);
-    /// ```
-    pub fn apply<R, F: FnOnce(&mut T) -> R>(name: &str, func: F) -> Option<R> {
-        let type_id = TypeId::of::<T>();
-        let type_map = _TABLE.read().ok()?;
-        let type_map = type_map.get(&type_id)?.read().ok()?;
-        check_deadlock!(mut T:name;Lock::Key);
-        let mut value = type_map.get(name)?.write().ok()?;
-        let var = value.downcast_mut::<T>()?;
-        ContextOperator::push(Context::Apply(String::from(name), type_id));
-        let ret = Some(func(var));
-        ContextOperator::pop();
-        ret
+    // 设置条目离开注册表时执行的回调函数
+    fn with_on_remove(self, name: &str, func: OnRemove) -> Self {
+        *self.on_remove.lock().unwrap_or_else(|e| e.into_inner()) = Some((intern(name), func));
+        self
     }
 
-    /// 向注册表中的指定键应用一个函数，该函数仅能读取注册表中的值
-    ///
-    /// 如果键不存在，则返回 `None`；否则，返回闭包函数的返回值
-    ///
-    /// # 示例
-    /// ```rust
-    /// use gom::Registry;
-    ///
-    /// Registry::<i32>::register("my_key", 42);
-    /// assert_eq!(Registry::<i32>::with("my_key", |v| *v), Some(42));
-    /// assert_eq!(Registry::<i32>::with("other_key", |v| *v), None);
-    /// ```
-    pub fn with<R, F: FnOnce(&T) -> R>(name: &str, func: F) -> Option<R> {
-        let type_id = TypeId::of::<T>();
-        let type_map = _TABLE.read().ok()?;
-        let type_map = type_map.get(&type_id)?.read().ok()?;
-        check_deadlock!(ref T:name);
-        let value = type_map.get(name)?.read().ok()?;
-        let var = value.downcast_ref::<T>()?;
-        ContextOperator::push(Context::With(String::from(name), type_id));
-        let ret = Some(func(var));
-        ContextOperator::pop();
-        ret
+    // 条目被移动到新键下时，更新回调函数将收到的键以及编号对应的键
+    fn renamed(self, name: &str) -> Self {
+        let name = intern(name);
+        // 原键下 `cached_get` 缓存的副本随之失效
+        self.version.fetch_add(1, Ordering::Release);
+        if let Some((key, _)) = &mut *self.on_remove.lock().unwrap_or_else(|e| e.into_inner()) {
+            *key = Arc::clone(&name);
+        }
+        if let Some((arena, index)) = &self.arena {
+            if let Some(arena) = arena.upgrade() {
+                let mut arena = arena.write().unwrap_or_else(|e| e.into_inner());
+                if let Some((key, _)) = &mut arena.entries[*index] {
+                    *key = name;
+                }
+            }
+        }
+        self
     }
 
-    /// 使用新值替换注册表中的指定键对应的值
-    ///
-    /// 如果键不存在，则返回 `None` 并且不会注册新值；否则，返回旧值
-    ///
-    /// # 示例
-    /// ```rust
-    /// use gom::Registry;
-    ///
-    /// Registry::<i32>::register("my_key", 42);
-    /// assert_eq!(Registry::<i32>::replace("my_key", 64), Some(42));
-    /// assert_eq!(Registry::<i32>::replace("other_key", 32), None);
-    /// ```
-    pub fn replace(name: &str, value: T) -> Option<T> {
-        let type_id = TypeId::of::<T>();
-        let type_map = _TABLE.read().ok()?;
-        let type_map = type_map.get(&type_id)?;
-        let value = {
-            check_deadlock!(mut T:name;Lock::Type);
-            let mut type_map = type_map.write().ok()?;
-            let ret = type_map.remove(name)?;
-            type_map.insert(String::from(name), RwLock::new(Box::new(value)));
-            ret
+    // 取回值的所有权；如果仍有 `Handle` 或守卫共享该值，则返回 `None`，值将在最后一个 `Handle` 被销毁时销毁
+    //
+    // 其他线程正通过 `with` 等函数临时访问该值时，等待其访问结束后取出该值，并在原处留下占位值；应在释放该类型对应的表的锁之后调用
+    fn into_inner(self) -> Option<LockResult<Value>> {
+        let data = Arc::clone(&self.data);
+        drop(self);
+        let data = match Arc::try_unwrap(data) {
+            Ok(data) => {
+                #[cfg(feature = "arc-swap")]
+                if let Some(read_mostly) = data.read_mostly {
+                    return Some(Ok(read_mostly.into_value()));
+                }
+                return Some(data.value.into_inner());
+            }
+            Err(data) => data,
         };
-        let value = value.into_inner().ok()?;
-        let type_value = value.downcast::<T>().ok()?;
-        Some(*type_value)
+        if data.shared.load(Ordering::Acquire) > 0 {
+            return None;
+        }
+        #[cfg(feature = "arc-swap")]
+        if let Some(read_mostly) = &data.read_mostly {
+            return Some(Ok(read_mostly.snapshot()));
+        }
+        let taken = |value: &mut Value| std::mem::replace(value, Box::new(Taken));
+        let value = match data.value.write() {
+            Ok(mut value) => Ok(taken(&mut value)),
+            Err(e) => Err(PoisonError::new(taken(&mut e.into_inner()))),
+        };
+        Some(value)
     }
+}
 
-    /// 与 `replace` 相同，但已弃用，请使用 `replace` 替代
-    #[deprecated(since = "0.1.6", note = "use `replace` instead")]
-    pub fn take(name: &str, value: T) -> Option<T> {
-        Self::replace(name, value)
+impl std::ops::Deref for Record {
+    type Target = RecordData;
+
+    fn deref(&self) -> &RecordData {
+        &self.data
     }
 }
 
-/// 针对于线程局部变量的注册表
-pub struct LocalRegistry<T> {
-    _marker: PhantomData<T>,
+impl Drop for Record {
+    fn drop(&mut self) {
+        self.data.attached.store(false, Ordering::Release);
+        // 使 `cached_get` 缓存的副本失效
+        self.data.version.fetch_add(1, Ordering::Release);
+        // 回收编号，使指向该条目的 `Slot` 失效
+        if let Some((arena, index)) = self.arena.take() {
+            if let Some(arena) = arena.upgrade() {
+                arena.write().unwrap_or_else(|e| e.into_inner()).free(index);
+            }
+        }
+        // 被移出注册表的条目均在释放锁之后销毁，因而回调函数中可以访问注册表
+        if let Some((name, func)) = self.take_on_remove() {
+            #[cfg(feature = "arc-swap")]
+            if let Some(read_mostly) = &self.data.read_mostly {
+                func(&name, &mut read_mostly.snapshot());
+                return;
+            }
+            let mut value = self.data.value.write().unwrap_or_else(|e| e.into_inner());
+            func(&name, &mut value);
+        }
+    }
 }
 
-impl<T: 'static> LocalRegistry<T> {
-    /// 向注册表中注册一个新值
-    ///
-    /// 如果相同的键已存在，那么旧值将会被新值替换
-    ///
-    /// # 示例
-    /// ```rust
-    /// use gom::LocalRegistry;
-    ///
-    /// LocalRegistry::<i32>::register("my_key", 42);
-    /// ```
-    pub fn register(name: &str, value: T) {
-        let type_id = TypeId::of::<T>();
-        let has_type = _LOCAL_TABLE.with_borrow(|table| table.contains_key(&type_id));
-        if !has_type {
-            _LOCAL_TABLE.with_borrow_mut(|table| {
-                table.insert(type_id, HashMap::new());
-            });
+// 某一类型下通过 `Registry::slot_of` 编号的条目，`Slot` 以编号为下标直接访问；被回收的编号会被重新分配
+#[derive(Default)]
+struct Arena {
+    entries: Vec<Option<(Arc<str>, Arc<RecordData>)>>,
+    free: Vec<usize>,
+}
+
+impl Arena {
+    fn alloc(&mut self, name: Arc<str>, data: Arc<RecordData>) -> usize {
+        let entry = Some((name, data));
+        match self.free.pop() {
+            Some(index) => {
+                self.entries[index] = entry;
+                index
+            }
+            None => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
         }
-        _LOCAL_TABLE.with_borrow_mut(|table| {
-            let type_map = table.get_mut(&type_id).unwrap();
-            type_map.insert(String::from(name), Box::new(value));
-        })
     }
 
-    /// 从注册表中移除指定键对应的值
-    ///
-    /// 如果键不存在，则返回 `None`
-    ///
-    /// # 示例
-    /// ```rust
+    fn free(&mut self, index: usize) {
+        self.entries[index] = None;
+        self.free.push(index);
+    }
+}
+
+// 某一类型对应的表，同时记录该类型的名称
+struct TypeTable {
+    type_name: &'static str,
+    // 键的哈希值决定其所在的分片，每个分片有各自的锁，因而同一类型下不同键的写操作通常不会相互等待
+    shards: Box<[RwLock<TypeMap>]>,
+    hasher: RandomState,
+    frozen: Arc<AtomicBool>,
+    generation: Arc<AtomicU64>,
+    arena: Arc<RwLock<Arena>>,
+    // 是否已被移出注册表；各线程缓存的该表随之失效，向表中写入条目的函数在获取分片的写锁之后检查该标记
+    detached: AtomicBool,
+}
+
+impl TypeTable {
+    fn new<T: 'static>() -> Self {
+        Self::with_name(TypeId::of::<T>(), std::any::type_name::<T>())
+    }
+
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn with_name(type_id: TypeId, type_name: &'static str) -> Self {
+        #[cfg(feature = "metrics")]
+        metrics::name(type_id, type_name);
+        Self {
+            type_name,
+            shards: (0..SHARDS).map(|_| RwLock::default()).collect(),
+            hasher: RandomState::new(),
+            frozen: Arc::new(AtomicBool::new(false)),
+            generation: Arc::new(AtomicU64::new(Self::next_generation())),
+            arena: Arc::default(),
+            detached: AtomicBool::new(false),
+        }
+    }
+
+    fn next_generation() -> u64 {
+        GENERATION.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    // 表的修改计数；持有某一分片的读锁期间，该分片中的条目不会改变，但其他分片的写操作仍会更新该计数
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    fn is_frozen(&self) -> bool {
+        self.frozen.load(Ordering::Acquire)
+    }
+
+    // 该表是否已为空；不等待任何锁，任一分片的锁无法立即获取或已中毒时视为非空
+    fn is_empty(&self) -> bool {
+        self.shards
+            .iter()
+            .all(|shard| shard.try_read().is_ok_and(|type_map| type_map.is_empty()))
+    }
+
+    // 如果该表已被冻结，则返回 `None`；应在获取写锁之前调用，从而避免等待不可能成功的写操作
+    fn writable(&self) -> Option<&Self> {
+        (!self.is_frozen()).then_some(self)
+    }
+
+    // 该表是否已被移出注册表
+    //
+    // 表在被移出时先设置该标记，再获取各分片的写锁取出其中的条目，因而持有某一分片的写锁时若该标记仍未设置，
+    // 写入该分片的条目会被移出该表的操作取出而不会丢失；否则应重新查找该类型对应的表
+    fn is_detached(&self) -> bool {
+        self.detached.load(Ordering::SeqCst)
+    }
+
+    // 如果该表已为空，则在持有其全部分片的写锁时将其标记为已被移出注册表并返回 `true`；
+    // 不等待任何锁，任一分片的锁无法立即获取或已中毒时视为非空
+    fn detach_if_empty(&self) -> bool {
+        let shards: Option<Vec<_>> = self
+            .shards
+            .iter()
+            .map(|shard| {
+                shard
+                    .try_write()
+                    .ok()
+                    .filter(|type_map| type_map.is_empty())
+            })
+            .collect();
+        let Some(_shards) = shards else {
+            return false;
+        };
+        self.detached.store(true, Ordering::SeqCst);
+        true
+    }
+
+    fn shard(&self, name: &str) -> &RwLock<TypeMap> {
+        &self.shards[shard_index(&self.hasher, name)]
+    }
+
+    // 按分片顺序获取全部分片的锁；任一分片的锁已中毒时返回 `Err`，其中仍包含全部分片
+    fn lock_all<'a, G: Deref<Target = TypeMap>>(
+        &'a self,
+        lock: impl Fn(&'a RwLock<TypeMap>) -> LockResult<G>,
+    ) -> LockResult<Shards<'a, G>> {
+        let mut poisoned = false;
+        let guards = self
+            .shards
+            .iter()
+            .map(|shard| {
+                lock(shard).unwrap_or_else(|e| {
+                    poisoned = true;
+                    e.into_inner()
+                })
+            })
+            .collect();
+        let shards = Shards::new(guards, &self.hasher);
+        if poisoned {
+            Err(PoisonError::new(shards))
+        } else {
+            Ok(shards)
+        }
+    }
+
+    fn read(&self) -> LockResult<Shards<'_, RwLockReadGuard<'_, TypeMap>>> {
+        self.lock_all(RwLock::read)
+    }
+
+    fn write(&self) -> LockResult<Shards<'_, RwLockWriteGuard<'_, TypeMap>>> {
+        let guard = self.lock_all(RwLock::write);
+        self.generation
+            .store(Self::next_generation(), Ordering::Release);
+        guard
+    }
+
+    // 仅获取指定键所在分片的读锁
+    fn read_shard(&self, name: &str) -> LockResult<RwLockReadGuard<'_, TypeMap>> {
+        self.shard(name).read()
+    }
+
+    fn try_read_shard(&self, name: &str) -> TryLockResult<RwLockReadGuard<'_, TypeMap>> {
+        self.shard(name).try_read()
+    }
+
+    fn try_read_shard_until(
+        &self,
+        name: &str,
+        deadline: Instant,
+    ) -> TryLockResult<RwLockReadGuard<'_, TypeMap>> {
+        self.shard(name).try_read_until(deadline)
+    }
+
+    // 仅获取指定键所在分片的写锁
+    fn write_shard(&self, name: &str) -> LockResult<RwLockWriteGuard<'_, TypeMap>> {
+        let guard = self.shard(name).write();
+        self.generation
+            .store(Self::next_generation(), Ordering::Release);
+        guard
+    }
+
+    // 与 `write_shard` 相同，但该表已被移出注册表时返回 `None`；向表中插入新键之前应以此获取锁，
+    // 并在返回 `None` 时重新查找该类型对应的表，否则插入的条目会随被移出的表一同被丢弃
+    fn write_shard_attached(
+        &self,
+        name: &str,
+    ) -> Option<LockResult<RwLockWriteGuard<'_, TypeMap>>> {
+        let guard = self.write_shard(name);
+        (!self.is_detached()).then_some(guard)
+    }
+
+    // 与 `write` 相同，但该表已被移出注册表时返回 `None`
+    #[allow(clippy::type_complexity)]
+    fn write_attached(&self) -> Option<LockResult<Shards<'_, RwLockWriteGuard<'_, TypeMap>>>> {
+        let guard = self.write();
+        (!self.is_detached()).then_some(guard)
+    }
+
+    // 合并全部分片；任一分片的锁已中毒时返回 `Err`，其中仍包含全部条目
+    //
+    // 其他线程仍缓存着该表时无法取得其所有权，此时将其标记为已被移出注册表，再在各分片的写锁下取出全部条目
+    fn into_inner(self: Arc<Self>) -> LockResult<TypeMap> {
+        self.detached.store(true, Ordering::SeqCst);
+        let mut poisoned = false;
+        let mut map = HashMap::new();
+        match Arc::try_unwrap(self) {
+            Ok(type_table) => {
+                for shard in type_table.shards.into_vec() {
+                    map.extend(shard.into_inner().unwrap_or_else(|e| {
+                        poisoned = true;
+                        e.into_inner()
+                    }));
+                }
+            }
+            Err(type_table) => {
+                for shard in type_table.shards.iter() {
+                    let mut type_map = shard.write().unwrap_or_else(|e| {
+                        poisoned = true;
+                        e.into_inner()
+                    });
+                    map.extend(std::mem::take(&mut *type_map));
+                }
+            }
+        }
+        if poisoned {
+            Err(PoisonError::new(map))
+        } else {
+            Ok(map)
+        }
+    }
+}
+
+// 向表中插入新的条目；如果键已存在，则新条目的版本号在旧条目的基础上递增，并返回旧条目
+fn insert_slot(type_map: &mut TypeMap, name: Arc<str>, value: Value) -> Option<Record> {
+    match type_map.entry(name) {
+        hash_map::Entry::Occupied(mut entry) => {
+            Some(replace_slot(entry.get_mut(), Record::new(value)))
+        }
+        hash_map::Entry::Vacant(entry) => {
+            entry.insert(Record::new(value));
+            notify_registered();
+            None
+        }
+    }
+}
+
+// 与 `insert_slot` 相同，但插入给定的条目；仅在键不存在时驻留该键
+fn put_slot(type_map: &mut TypeMap, name: &str, slot: Record) -> Option<Record> {
+    match type_map.get_mut(name) {
+        Some(old) => Some(replace_slot(old, slot)),
+        None => {
+            type_map.insert(intern(name), slot);
+            notify_registered();
+            None
+        }
+    }
+}
+
+// 在原位以新条目替换旧条目，新条目的版本号在旧条目的基础上递增，并返回旧条目
+fn replace_slot(old: &mut Record, slot: Record) -> Record {
+    slot.version
+        .store(old.version().wrapping_add(1), Ordering::Release);
+    slot.write_priority.store(
+        old.write_priority.load(Ordering::Relaxed),
+        Ordering::Relaxed,
+    );
+    std::mem::replace(old, slot)
+}
+
+// 以 `TypeId` 为键的表无需再次哈希：`TypeId` 本身即由类型计算出的哈希值，直接取其写入的 64 位作为哈希值
+#[derive(Default)]
+struct TypeIdHasher(u64);
+
+impl Hasher for TypeIdHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut buf = [0; 8];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            self.0 = self.0.rotate_left(5) ^ u64::from_ne_bytes(buf);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = self.0.rotate_left(5) ^ n;
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+// 全局注册表中各类型对应的表；表以 `Arc` 共享，从而各线程可以在不获取注册表的锁的情况下访问其缓存的表
+type TypeTables = HashMap<TypeId, Arc<TypeTable>, BuildHasherDefault<TypeIdHasher>>;
+
+// 全局注册表；在调试模式下或启用 `deadlock-detection` 特性时，获取锁之前会检查当前线程是否正在执行 `bootstrap` 从而已持有其写锁
+//
+// 泛型函数中的 `static` 由该函数的所有实例共享，无法为每个类型各自提供一个静态的表，因而各类型对应的表仍保存在以 `TypeId`
+// 为键的表中，仅在首次访问某一类型、创建或移除某一类型对应的表以及跨类型的操作中获取其锁。各线程缓存其访问过的表，
+// 被移出注册表的表会被标记（见 `TypeTable::is_detached`），缓存的表只要未被标记即可直接访问。
+// `layout` 为 `bootstrap` 的版本号，持有其写锁期间为奇数，释放时递增为偶数；缓存的表同时记录获取时的版本号，
+// 只读的查找在完成后再次检查版本号，从而不会观察到 `bootstrap` 的中间状态
+struct Table {
+    map: RwLock<TypeTables>,
+    layout: AtomicU64,
+}
+
+// `bootstrap` 持有的注册表写锁守卫，创建时与销毁时各递增一次版本号
+pub(crate) struct TableWriteGuard<'a> {
+    layout: &'a AtomicU64,
+    guard: RwLockWriteGuard<'a, TypeTables>,
+}
+
+impl<'a> TableWriteGuard<'a> {
+    fn new(layout: &'a AtomicU64, guard: RwLockWriteGuard<'a, TypeTables>) -> Self {
+        layout.fetch_add(1, Ordering::SeqCst);
+        Self { layout, guard }
+    }
+}
+
+impl Deref for TableWriteGuard<'_> {
+    type Target = TypeTables;
+
+    fn deref(&self) -> &TypeTables {
+        &self.guard
+    }
+}
+
+impl DerefMut for TableWriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut TypeTables {
+        &mut self.guard
+    }
+}
+
+impl Drop for TableWriteGuard<'_> {
+    // 在释放写锁之前递增版本号
+    fn drop(&mut self) {
+        self.layout.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// 当前线程缓存的各类型对应的表，以及获取时 `bootstrap` 的版本号
+type CachedTables = HashMap<TypeId, (u64, Arc<TypeTable>), BuildHasherDefault<TypeIdHasher>>;
+
+thread_local! {
+    static TABLES: RefCell<CachedTables> = RefCell::new(HashMap::default());
+}
+
+impl Table {
+    fn read(&self) -> LockResult<RwLockReadGuard<'_, TypeTables>> {
+        #[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+        check_bootstrap_deadlock();
+        self.map.read()
+    }
+
+    // 获取写锁以创建或移除某一类型对应的表；不改变版本号，因而各线程缓存的其他表仍然有效
+    fn write(&self) -> LockResult<RwLockWriteGuard<'_, TypeTables>> {
+        #[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+        check_bootstrap_deadlock();
+        self.map.write()
+    }
+
+    // 获取 `bootstrap` 的写锁，持有期间各线程的只读查找都将等待其释放
+    pub(crate) fn write_batch(&self) -> LockResult<TableWriteGuard<'_>> {
+        #[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+        check_bootstrap_deadlock();
+        match self.map.write() {
+            Ok(guard) => Ok(TableWriteGuard::new(&self.layout, guard)),
+            Err(e) => Err(PoisonError::new(TableWriteGuard::new(
+                &self.layout,
+                e.into_inner(),
+            ))),
+        }
+    }
+
+    // 锁被占用时立即返回，因而无需检查是否正在执行 `bootstrap`
+    fn try_read(&self) -> TryLockResult<RwLockReadGuard<'_, TypeTables>> {
+        self.map.try_read()
+    }
+
+    fn try_read_until(&self, deadline: Instant) -> TryLockResult<RwLockReadGuard<'_, TypeTables>> {
+        self.map.try_read_until(deadline)
+    }
+
+    // 在注册表的读锁下查找指定类型对应的表，返回时已释放读锁；找到的表连同此时的版本号存入当前线程的缓存，
+    // 同时从缓存中移除已被移出注册表的表
+    fn lookup<'a, E>(
+        &'a self,
+        type_id: TypeId,
+        read: impl FnOnce(&'a Self) -> Result<RwLockReadGuard<'a, TypeTables>, E>,
+    ) -> Result<Option<(u64, Arc<TypeTable>)>, E> {
+        let (layout, type_table) = {
+            let map = read(self)?;
+            // 持有读锁期间版本号不会改变
+            let layout = self.layout.load(Ordering::SeqCst);
+            let Some(type_table) = map.get(&type_id) else {
+                return Ok(None);
+            };
+            (layout, Arc::clone(type_table))
+        };
+        let evicted = TABLES.with(|tables| {
+            let Ok(mut tables) = tables.try_borrow_mut() else {
+                return Vec::new();
+            };
+            let stale: Vec<_> = tables
+                .iter()
+                .filter(|(_, (_, type_table))| type_table.is_detached())
+                .map(|(type_id, _)| *type_id)
+                .collect();
+            tables.insert(type_id, (layout, Arc::clone(&type_table)));
+            stale
+                .into_iter()
+                .filter_map(|type_id| tables.remove(&type_id))
+                .collect()
+        });
+        // 被移除的表在释放缓存的借用之后销毁
+        drop(evicted);
+        Ok(Some((layout, type_table)))
+    }
+
+    // 注册表中当前所有类型对应的表，用于跨类型的操作；返回时已释放注册表的锁，因而随后获取各表的锁时符合加锁顺序
+    fn tables(&self) -> Result<Vec<(TypeId, Arc<TypeTable>)>, ()> {
+        let map = self.read().map_err(drop)?;
+        Ok(map
+            .iter()
+            .map(|(type_id, type_table)| (*type_id, Arc::clone(type_table)))
+            .collect())
+    }
+
+    // 从当前线程的缓存中查找指定类型对应的表；缓存的表已被移出注册表或 `bootstrap` 正在执行或已执行过时返回 `None`
+    fn cached(&self, type_id: TypeId) -> Option<Arc<TypeTable>> {
+        let layout = self.layout.load(Ordering::SeqCst);
+        TABLES.with(|tables| {
+            let tables = tables.try_borrow().ok()?;
+            let (cached, type_table) = tables.get(&type_id)?;
+            (*cached == layout && !type_table.is_detached()).then(|| Arc::clone(type_table))
+        })
+    }
+
+    fn get_with<'a, E>(
+        &'a self,
+        type_id: TypeId,
+        read: impl FnOnce(&'a Self) -> Result<RwLockReadGuard<'a, TypeTables>, E>,
+    ) -> Result<Option<Arc<TypeTable>>, E> {
+        if let Some(type_table) = self.cached(type_id) {
+            return Ok(Some(type_table));
+        }
+        Ok(self
+            .lookup(type_id, read)?
+            .map(|(_, type_table)| type_table))
+    }
+
+    // 返回指定类型对应的表，该类型没有对应的表时返回 `Ok(None)`，注册表的锁已中毒时返回 `Err`
+    //
+    // 当前线程缓存的表仍然有效时不获取注册表的锁；返回的表可能随即被其他线程移出注册表，
+    // 因此向其中写入条目的函数应在获取分片的写锁之后检查 `TypeTable::is_detached`
+    fn get(&self, type_id: TypeId) -> Result<Option<Arc<TypeTable>>, ()> {
+        self.get_with(type_id, |table| table.read().map_err(drop))
+    }
+
+    // 与 `get` 相同，但不会等待注册表的锁
+    fn try_get(&self, type_id: TypeId) -> Result<Option<Arc<TypeTable>>, TryLockError<()>> {
+        self.get_with(type_id, |table| table.try_read().map_err(drop_guard))
+    }
+
+    // 与 `get` 相同，但注册表的锁被占用时等待至截止时间
+    fn get_until(
+        &self,
+        type_id: TypeId,
+        deadline: Instant,
+    ) -> Result<Option<Arc<TypeTable>>, TryLockError<()>> {
+        self.get_with(type_id, |table| {
+            table.try_read_until(deadline).map_err(drop_guard)
+        })
+    }
+
+    // 返回指定类型对应的表，不存在时以 `create` 创建；注册表的锁已中毒时返回 `Err`
+    //
+    // 表不存在时需要获取注册表的写锁，调用者应事先检查这是否会导致死锁
+    fn get_or_create(
+        &self,
+        type_id: TypeId,
+        create: impl FnOnce() -> TypeTable,
+    ) -> Result<Arc<TypeTable>, ()> {
+        if let Some(type_table) = self.get(type_id)? {
+            return Ok(type_table);
+        }
+        let type_table = {
+            let mut map = self.write().map_err(drop)?;
+            Arc::clone(map.entry(type_id).or_insert_with(|| Arc::new(create())))
+        };
+        Ok(type_table)
+    }
+
+    // 以指定类型对应的表执行 `func`，该类型没有对应的表时返回 `Ok(None)`，注册表的锁已中毒时返回 `Err`
+    //
+    // 用于只读的查找：`func` 执行期间 `bootstrap` 开始执行时丢弃其结果并重新执行，因而 `func` 不应有副作用
+    fn with_table<R>(
+        &self,
+        type_id: TypeId,
+        func: impl Fn(&TypeTable) -> R,
+    ) -> Result<Option<R>, ()> {
+        loop {
+            let layout = self.layout.load(Ordering::SeqCst);
+            let ret = TABLES.with(|tables| {
+                let tables = tables.try_borrow().ok()?;
+                let (cached, type_table) = tables.get(&type_id)?;
+                (*cached == layout && !type_table.is_detached()).then(|| func(type_table))
+            });
+            let (layout, ret) = match ret {
+                Some(ret) => (layout, ret),
+                None => {
+                    let Some((layout, type_table)) =
+                        self.lookup(type_id, |table| table.read().map_err(drop))?
+                    else {
+                        return Ok(None);
+                    };
+                    (layout, func(&type_table))
+                }
+            };
+            if self.layout.load(Ordering::SeqCst) == layout {
+                return Ok(Some(ret));
+            }
+        }
+    }
+}
+
+// 丢弃 `TryLockError` 中的守卫，从而在释放锁之后返回错误
+fn drop_guard<G>(e: TryLockError<G>) -> TryLockError<()> {
+    match e {
+        TryLockError::WouldBlock => TryLockError::WouldBlock,
+        TryLockError::Poisoned(_) => TryLockError::Poisoned(PoisonError::new(())),
+    }
+}
+
+lazy_static! {
+    static ref _TABLE: Table = Table {
+        map: RwLock::new(TypeTables::default()),
+        layout: AtomicU64::new(0),
+    };
+    static ref _SEALED: RwLock<Vec<String>> = RwLock::new(Vec::new());
+    static ref _REGISTERED: Registered = Registered {
+        generation: Mutex::new(0),
+        cond: Condvar::new(),
+        waiters: AtomicUsize::new(0),
+    };
+}
+
+// 等待键被注册的线程；注册新键时唤醒所有等待者，由等待者自行检查其等待的键是否已存在
+//
+// 互斥锁只保护计数，不会在持有时获取其他锁，因而可以在持有各类型对应的表的锁时通知
+struct Registered {
+    // 每次注册新键时递增；等待者在检查键是否存在之前记下该值，值未变化时才开始等待，从而不会错过检查之后发生的注册
+    generation: Mutex<u64>,
+    cond: Condvar,
+    // 没有等待者时注册新键只需读取该计数
+    waiters: AtomicUsize,
+}
+
+// 通知等待者有新键被注册，应在新键被插入表中之后调用
+fn notify_registered() {
+    if _REGISTERED.waiters.load(Ordering::SeqCst) == 0 {
+        return;
+    }
+    *_REGISTERED
+        .generation
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) += 1;
+    _REGISTERED.cond.notify_all();
+}
+
+thread_local! {
+    static _LOCAL_TABLE: RefCell<HashMap<TypeId, LocalTypeMap>> =
+        RefCell::new(HashMap::new());
+}
+
+// 类型名称仅用于检查出死锁时的 panic 信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Context {
+    With(Arc<str>, TypeId, &'static str),
+    Apply(Arc<str>, TypeId, &'static str),
+    // 闭包执行期间仍持有注册表及该类型对应的表的锁
+    Type(TypeId, &'static str),
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Context::With(key, _, type_name) => write!(f, "with `{key}` of type `{type_name}`"),
+            Context::Apply(key, _, type_name) => write!(f, "apply `{key}` of type `{type_name}`"),
+            Context::Type(_, type_name) => write!(f, "table of type `{type_name}`"),
+        }
+    }
+}
+
+// 未启用死锁检查时仅用于 `would_deadlock_write`
+#[cfg_attr(
+    not(any(debug_assertions, feature = "deadlock-detection")),
+    allow(dead_code)
+)]
+enum Lock {
+    Global,
+    Type,
+    // 获取该类型对应的表中指定键所在分片的写锁，并且只会插入、替换或移除指定键的条目
+    TypeKey,
+    Key,
+}
+
+thread_local! {
+    // 上下文访问栈
+    static CONTEXT: RefCell<Vec<Context>> = const { RefCell::new(Vec::new()) };
+    // 当前线程是否正在执行 `bootstrap`
+    static BOOTSTRAPPING: Cell<bool> = const { Cell::new(false) };
+}
+
+// 上下文访问栈中的一帧，由 `ContextOperator::enter` 创建；各帧总是按推入的逆序销毁，因而直接弹出栈顶
+#[must_use]
+struct Frame;
+
+impl Drop for Frame {
+    fn drop(&mut self) {
+        ContextOperator::pop();
+    }
+}
+
+struct ContextOperator;
+impl ContextOperator {
+    fn push(ctx: Context) {
+        #[cfg(feature = "deadlock-detection")]
+        wait_graph::push(&ctx);
+        CONTEXT.with(|ctx_cell| {
+            ctx_cell.borrow_mut().push(ctx);
+        });
+    }
+
+    fn pop() {
+        #[cfg(feature = "deadlock-detection")]
+        wait_graph::pop();
+        CONTEXT.with(|ctx_cell| ctx_cell.borrow_mut().pop());
+    }
+
+    // 推入上下文，并返回在被销毁时将其弹出的守卫；闭包函数发生 panic 时，栈展开同样会弹出该上下文
+    fn enter(ctx: Context) -> Frame {
+        Self::push(ctx);
+        Frame
+    }
+
+    // 移除最后一个与之相等的上下文；守卫可能不按创建的逆序销毁，因而不能直接弹出栈顶
+    fn remove(ctx: &Context) {
+        #[cfg(feature = "deadlock-detection")]
+        wait_graph::remove(ctx);
+        CONTEXT.with_borrow_mut(|v| {
+            if let Some(index) = v.iter().rposition(|x| x == ctx) {
+                v.remove(index);
+            }
+        });
+    }
+
+    // 当前线程是否仍持有某一类型对应的表的锁；`with`、`apply` 等闭包执行期间不持有注册表及各类型对应的表的锁
+    fn holds_table_lock() -> bool {
+        CONTEXT.with_borrow(|v| v.iter().any(|x| matches!(x, Context::Type(..))))
+    }
+
+    fn cannot_lock_read_lock(type_id: TypeId, name: &str) -> bool {
+        CONTEXT.with_borrow(|v| {
+            v.iter().any(|x| match x {
+                Context::Apply(s, id, _) => id == &type_id && &**s == name,
+                _ => false,
+            })
+        })
+    }
+
+    fn cannot_lock_write_lock(type_id: TypeId, name: &str, lock: Lock) -> bool {
+        match lock {
+            // 只有仍持有某一类型对应的表的锁时，获取注册表的写锁才会死锁
+            Lock::Global => Self::holds_table_lock(),
+            Lock::Type => CONTEXT.with_borrow(|v| {
+                v.iter().any(|x| match x {
+                    Context::With(_, id, _) | Context::Apply(_, id, _) | Context::Type(id, _) => {
+                        id == &type_id
+                    }
+                })
+            }),
+            Lock::TypeKey => CONTEXT.with_borrow(|v| {
+                v.iter().any(|x| match x {
+                    Context::With(key, id, _) | Context::Apply(key, id, _) => {
+                        id == &type_id && &**key == name
+                    }
+                    Context::Type(id, _) => id == &type_id,
+                })
+            }),
+            Lock::Key => CONTEXT.with_borrow(|v| {
+                v.iter().any(|x| match x {
+                    Context::With(key, id, _) | Context::Apply(key, id, _) => {
+                        id == &type_id && &**key == name
+                    }
+                    Context::Type(..) => false,
+                })
+            }),
+        }
+    }
+}
+
+// 检查如果获取写锁是否会导致死锁
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+fn check_write_deadlock<T: 'static>(name: &str, lock: Lock) {
+    check_write_deadlock_of(TypeId::of::<T>(), std::any::type_name::<T>(), name, lock);
+}
+
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+fn check_write_deadlock_of(type_id: TypeId, type_name: &str, name: &str, lock: Lock) {
+    if ContextOperator::cannot_lock_write_lock(type_id, name, lock) {
+        thread_deadlock!("cannot write key `{name}` of type `{type_name}`");
+    }
+}
+
+// 检查如果获取读锁是否会导致死锁
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+fn check_read_deadlock<T: 'static>(name: &str) {
+    check_read_deadlock_of(TypeId::of::<T>(), std::any::type_name::<T>(), name);
+}
+
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+fn check_read_deadlock_of(type_id: TypeId, type_name: &str, name: &str) {
+    if ContextOperator::cannot_lock_read_lock(type_id, name) {
+        thread_deadlock!("cannot read key `{name}` of type `{type_name}`");
+    }
+}
+
+// 检查当前线程是否正在执行 `bootstrap`，此时获取注册表的任何锁都会导致死锁
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+fn check_bootstrap_deadlock() {
+    if BOOTSTRAPPING.get() {
+        thread_deadlock!("cannot access the registry inside `bootstrap`");
+    }
+}
+
+// 检查如果获取所有类型对应的表的写锁是否会导致死锁
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+fn check_global_write_deadlock() {
+    if CONTEXT.with_borrow(|v| !v.is_empty()) {
+        thread_deadlock!("cannot write the whole registry");
+    }
+}
+
+// 检查如果获取该类型下所有值的读锁是否会导致死锁
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+fn check_type_read_deadlock<T: 'static>() {
+    if CONTEXT.with_borrow(|v| {
+        v.iter().any(|x| match x {
+            Context::Apply(_, type_id, _) => type_id == &TypeId::of::<T>(),
+            _ => false,
+        })
+    }) {
+        thread_deadlock!(
+            "cannot read all values of type `{}`",
+            std::any::type_name::<T>()
+        );
+    }
+}
+
+// 构造检查出死锁时的 panic 信息，包含所尝试的操作以及当前线程的上下文访问栈；只在检查出死锁时调用，不影响正常访问的开销
+#[cold]
+#[inline(never)]
+fn deadlock_message(attempt: &str) -> String {
+    CONTEXT.with_borrow(|v| {
+        if v.is_empty() {
+            return format!("Thread deadlock! {attempt}");
+        }
+        let stack: Vec<_> = v.iter().map(Context::to_string).collect();
+        format!(
+            "Thread deadlock! {attempt} while holding (outermost first): {}",
+            stack.join(" -> ")
+        )
+    })
+}
+
+// 从注册表中移除满足条件且已为空的类型对应的表，返回被移除的表的数量；已被冻结的类型保留其表，从而不会丢失冻结状态
+//
+// 当前线程仍持有某一类型对应的表的锁时，获取注册表的写锁会导致死锁，此时保留所有表而不做任何操作
+fn collect_empty(mut select: impl FnMut(&TypeId) -> bool) -> usize {
+    if ContextOperator::holds_table_lock() {
+        return 0;
+    }
+    let Ok(mut map) = _TABLE.write() else {
+        return 0;
+    };
+    let before = map.len();
+    map.retain(|type_id, type_table| {
+        !(select(type_id) && !type_table.is_frozen() && type_table.detach_if_empty())
+    });
+    before - map.len()
+}
+
+// 判断键是否位于指定前缀之下，前缀只能匹配完整的段
+fn has_prefix(name: &str, prefix: &str) -> bool {
+    match name.strip_prefix(prefix) {
+        Some(rest) => {
+            prefix.is_empty() || prefix.ends_with('.') || rest.is_empty() || rest.starts_with('.')
+        }
+        None => false,
+    }
+}
+
+// 启用 `ordered` 特性时，将枚举结果按键的字典序排列；否则保持表中的原有顺序
+#[cfg(feature = "ordered")]
+fn order_by_key<V>(items: &mut [V], key: impl Fn(&V) -> &str) {
+    items.sort_unstable_by(|a, b| key(a).cmp(key(b)));
+}
+
+#[cfg(not(feature = "ordered"))]
+fn order_by_key<V>(_items: &mut [V], _key: impl Fn(&V) -> &str) {}
+
+// 计算将位于 `old` 前缀之下的键移动到 `new` 前缀之下时的 (原键, 新键) 列表；如果任一新键与不被移动的键冲突，则返回错误
+fn plan_rename_prefix<G: Deref<Target = TypeMap>>(
+    type_map: &Shards<'_, G>,
+    old: &str,
+    new: &str,
+) -> Result<Vec<(Arc<str>, String)>, RenamePrefixError> {
+    let plan: Vec<_> = type_map
+        .keys()
+        .filter(|name| has_prefix(name, old))
+        .map(|name| (Arc::clone(name), format!("{}{}", new, &name[old.len()..])))
+        .collect();
+    for (src, dst) in &plan {
+        if type_map.contains_key(dst) && !has_prefix(dst, old) {
+            return Err(RenamePrefixError::DestinationExists(dst.clone()));
+        }
+        if **src != **dst && is_sealed(dst) {
+            return Err(RenamePrefixError::SealedNamespace(dst.clone()));
+        }
+    }
+    Ok(plan)
+}
+
+// 按照 `plan_rename_prefix` 给出的列表移动键，值连同其锁一起被移动
+fn apply_rename_prefix<G: DerefMut<Target = TypeMap>>(
+    type_map: &mut Shards<'_, G>,
+    plan: Vec<(Arc<str>, String)>,
+) -> usize {
+    let slots: Vec<_> = plan
+        .into_iter()
+        .filter_map(|(src, dst)| {
+            let slot = type_map.remove(&src)?.renamed(&dst);
+            Some((intern(&dst), slot))
+        })
+        .collect();
+    let moved = slots.len();
+    for (dst, slot) in slots {
+        type_map.insert(dst, slot);
+    }
+    if moved > 0 {
+        notify_registered();
+    }
+    moved
+}
+
+// 启用 `deadlock-detection` 特性时，在等待图中记录当前线程将要等待的值的锁，直至所在的作用域结束；等待该锁会与其他线程形成环时引发 panic
+#[cfg(feature = "deadlock-detection")]
+macro_rules! mark_waiting {
+    (mut $type:ty : $name:expr ; $em:expr) => {
+        let _waiting = matches!($em, $crate::Lock::Key).then(|| {
+            $crate::wait_graph::wait(
+                std::any::TypeId::of::<$type>(),
+                std::any::type_name::<$type>(),
+                $name,
+                true,
+            )
+        });
+    };
+    (ref $type:ty : $name:expr) => {
+        let _waiting = $crate::wait_graph::wait(
+            std::any::TypeId::of::<$type>(),
+            std::any::type_name::<$type>(),
+            $name,
+            false,
+        );
+    };
+    (ref dyn $type_id:expr, $type_name:expr, $name:expr) => {
+        let _waiting = $crate::wait_graph::wait($type_id, $type_name, $name, false);
+    };
+}
+
+#[cfg(not(feature = "deadlock-detection"))]
+macro_rules! mark_waiting {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+macro_rules! check_deadlock {
+    (mut *) => {
+        $crate::check_global_write_deadlock();
+    };
+    (mut dyn $type_id:expr, $type_name:expr, $name:expr ; $em:expr) => {
+        $crate::check_write_deadlock_of($type_id, $type_name, $name, $em);
+    };
+    (ref dyn $type_id:expr, $type_name:expr, $name:expr) => {
+        $crate::check_read_deadlock_of($type_id, $type_name, $name);
+        mark_waiting!(ref dyn $type_id, $type_name, $name);
+    };
+    (mut $type:ty : $name:expr ; $em:expr) => {
+        $crate::check_write_deadlock::<$type>($name, $em);
+        mark_waiting!(mut $type : $name ; $em);
+    };
+    (ref $type:ty : $name:expr) => {
+        $crate::check_read_deadlock::<$type>($name);
+        mark_waiting!(ref $type : $name);
+    };
+    (ref $type:ty) => {
+        $crate::check_type_read_deadlock::<$type>();
+    };
+}
+
+#[cfg(not(any(debug_assertions, feature = "deadlock-detection")))]
+macro_rules! check_deadlock {
+    (mut *) => {};
+    (mut dyn $type_id:expr, $type_name:expr, $name:expr ; $em:expr) => {
+        let _ = ($type_id, $type_name, $name);
+    };
+    (ref dyn $type_id:expr, $type_name:expr, $name:expr) => {
+        let _ = ($type_id, $type_name, $name);
+    };
+    (mut $type:ty : $name:expr ; $em:expr) => {
+        let _ = $name;
+    };
+    (ref $type:ty : $name:expr) => {
+        let _ = $name;
+    };
+    (ref $type:ty) => {};
+}
+
+// 与 `check_deadlock` 相同，但不引发 panic，而是返回是否会导致死锁；供 `*_checked` 系列函数返回 `RegistryError::WouldDeadlock`
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+macro_rules! would_deadlock {
+    (mut $type:ty : $name:expr ; $em:expr) => {
+        $crate::ContextOperator::cannot_lock_write_lock(TypeId::of::<$type>(), $name, $em)
+    };
+    (ref $type:ty : $name:expr) => {
+        $crate::ContextOperator::cannot_lock_read_lock(TypeId::of::<$type>(), $name)
+    };
+}
+
+#[cfg(not(any(debug_assertions, feature = "deadlock-detection")))]
+macro_rules! would_deadlock {
+    (mut $type:ty : $name:expr ; $em:expr) => {
+        false
+    };
+    (ref $type:ty : $name:expr) => {
+        false
+    };
+}
+
+// 将 `*_checked` 系列函数的结果转换为原有接口的返回值；检测到死锁时与原有接口相同，引发 panic，`op` 为所尝试的操作
+fn unchecked<R>(op: &str, ret: Result<R, RegistryError>) -> Option<R> {
+    match ret {
+        Ok(ret) => Some(ret),
+        Err(RegistryError::WouldDeadlock { key, type_name }) => {
+            thread_deadlock!("cannot {op} key `{key}` of type `{type_name}`")
+        }
+        Err(_) => None,
+    }
+}
+
+mod bootstrap;
+mod handle;
+mod hot_key;
+mod transaction;
+#[cfg(feature = "deadlock-detection")]
+mod wait_graph;
+pub use bootstrap::*;
+pub use handle::{Handle, WeakHandle};
+pub use hot_key::HotKey;
+pub use transaction::*;
+
+/// 用于访问注册表的类型
+///
+/// # 注解
+///
+/// + 其索引方式是：`类型-键` 唯一，因而同一个键可以对应多个不同类型的值
+/// + 如果闭包中使用了不恰当的嵌套，可能会导致线程死锁
+pub struct Registry<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T: 'static + Send + Sync + Any> Registry<T> {
+    // 返回该类型对应的表，不存在时创建该表；返回时已释放注册表的锁
+    fn _ensure_type(name: &str) -> Option<Arc<TypeTable>> {
+        let type_id = TypeId::of::<T>();
+        if let Some(type_table) = _TABLE.get(type_id).ok()? {
+            return Some(type_table);
+        }
+        check_deadlock!(mut T:name;Lock::Global);
+        _TABLE.get_or_create(type_id, TypeTable::new::<T>).ok()
+    }
+
+    // 获取指定键所在分片的写锁并执行闭包函数，该类型尚无对应的表时创建该表，`arg` 将原样传递给闭包函数；
+    // 锁已中毒、该类型已被冻结或获取锁会导致死锁时不会执行闭包函数，并连同错误原样返回 `arg`
+    //
+    // 取得的表在获取分片的写锁之后被发现已被移出注册表时重新查找，因而闭包函数插入的条目不会随被移出的表一同被丢弃
+    fn _with_shard<A, R>(
+        name: &str,
+        arg: A,
+        func: impl FnOnce(&mut TypeMap, A) -> R,
+    ) -> Result<R, (RegistryError, A)> {
+        let type_id = TypeId::of::<T>();
+        loop {
+            let type_table = match _TABLE.get(type_id) {
+                Ok(Some(type_table)) => type_table,
+                Ok(None) => {
+                    if would_deadlock!(mut T:name;Lock::Global) {
+                        return Err((registry_error!(WouldDeadlock, name, T), arg));
+                    }
+                    let Ok(type_table) = _TABLE.get_or_create(type_id, TypeTable::new::<T>) else {
+                        return Err((registry_error!(Poisoned, name, T), arg));
+                    };
+                    type_table
+                }
+                Err(()) => return Err((registry_error!(Poisoned, name, T), arg)),
+            };
+            if type_table.is_frozen() {
+                return Err((registry_error!(Frozen, name, T), arg));
+            }
+            if would_deadlock!(mut T:name;Lock::TypeKey) {
+                return Err((registry_error!(WouldDeadlock, name, T), arg));
+            }
+            let Some(type_map) = type_table.write_shard_attached(name) else {
+                continue;
+            };
+            let Ok(mut type_map) = type_map else {
+                return Err((registry_error!(Poisoned, name, T), arg));
+            };
+            return Ok(func(&mut type_map, arg));
+        }
+    }
+
+    // 查找指定键当前对应的条目，返回其数据以及该类型是否已被冻结
+    //
+    // 返回时已释放注册表及该类型对应的表的锁，因而随后获取该值自身的锁并执行闭包函数期间，同一类型的其他键仍可被注册、移除与访问
+    fn _record(name: &str) -> Option<(Arc<RecordData>, bool)> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                let type_map = type_table.read_shard(name).ok()?;
+                let data = Arc::clone(&type_map.get(name)?.data);
+                Some((data, type_table.is_frozen()))
+            })
+            .ok()??
+    }
+
+    // 与 `_record` 相同，但区分查找失败的原因，并同时返回表中驻留的键，从而推入上下文访问栈时无需再次查找或分配
+    fn _checked_record(name: &str) -> Result<(Arc<str>, Arc<RecordData>, bool), RegistryError> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                let type_map = type_table
+                    .read_shard(name)
+                    .map_err(|_| registry_error!(Poisoned, name, T))?;
+                let (key, record) = type_map
+                    .get_key_value(name)
+                    .ok_or_else(|| registry_error!(KeyNotFound, name, T))?;
+                Ok((
+                    Arc::clone(key),
+                    Arc::clone(&record.data),
+                    type_table.is_frozen(),
+                ))
+            })
+            .map_err(|_| registry_error!(Poisoned, name, T))?
+            .ok_or_else(|| registry_error!(TypeNotRegistered, name, T))?
+    }
+
+    // 查找该类型下所有满足条件的键当前对应的条目；与 `_record` 相同，返回时已释放所有锁
+    fn _records<P: Fn(&str) -> bool>(predicate: P) -> Option<Vec<(Arc<str>, Arc<RecordData>)>> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                let type_map = type_table.read().ok()?;
+                let records = type_map
+                    .iter()
+                    .filter(|(name, _)| predicate(name))
+                    .map(|(name, slot)| (Arc::clone(name), Arc::clone(&slot.data)))
+                    .collect();
+                Some(records)
+            })
+            .ok()??
+    }
+
+    // 与 `_record` 相同，但该类型已被冻结时返回 `None`
+    fn _writable_record(name: &str) -> Option<Arc<RecordData>> {
+        Self::_record(name)
+            .filter(|(_, frozen)| !frozen)
+            .map(|(data, _)| data)
+    }
+
+    // 与 `_record` 相同，但不会等待任何锁；`writable` 为真时，该类型已被冻结则返回 `TryAccessError::Frozen`
+    fn _try_record(name: &str, writable: bool) -> Result<Arc<RecordData>, TryAccessError> {
+        let type_table = _TABLE
+            .try_get(TypeId::of::<T>())?
+            .ok_or(TryAccessError::KeyMissing)?;
+        if writable && type_table.is_frozen() {
+            return Err(TryAccessError::Frozen);
+        }
+        let type_map = type_table.try_read_shard(name)?;
+        let record = type_map.get(name).ok_or(TryAccessError::KeyMissing)?;
+        Ok(Arc::clone(&record.data))
+    }
+
+    // 与 `_try_record` 相同，但锁被占用时等待至截止时间；超时返回 `TimeoutError::TimedOut`
+    fn _record_until(
+        name: &str,
+        writable: bool,
+        deadline: Instant,
+    ) -> Result<Arc<RecordData>, TimeoutError> {
+        let type_table = _TABLE
+            .get_until(TypeId::of::<T>(), deadline)
+            .map_err(TryAccessError::from)?
+            .ok_or(TimeoutError::KeyMissing)?;
+        if writable && type_table.is_frozen() {
+            return Err(TimeoutError::Frozen);
+        }
+        let type_map = type_table
+            .try_read_shard_until(name, deadline)
+            .map_err(TryAccessError::from)?;
+        let record = type_map.get(name).ok_or(TimeoutError::KeyMissing)?;
+        Ok(Arc::clone(&record.data))
+    }
+
+    fn _register(name: &str, value: T) -> Result<Option<T>, RegistryError> {
+        Self::_register_slot(name, Record::new(Box::new(value)))
+    }
+
+    // 注册给定的条目，并返回被替换的旧值；旧值仍被 `Handle` 共享或尚未初始化时，无法取回其所有权，此时同样返回 `Ok(None)`
+    fn _register_slot(name: &str, slot: Record) -> Result<Option<T>, RegistryError> {
+        if is_sealed(name) {
+            return Err(registry_error!(SealedNamespace, name, T));
+        }
+        let old = Self::_with_shard(name, slot, |type_map, slot| put_slot(type_map, name, slot))
+            .map_err(|(e, _)| e)?;
+        let Some(old) = old.and_then(Record::into_inner) else {
+            return Ok(None);
+        };
+        let old = old.unwrap_or_else(|e| e.into_inner());
+        Ok(old.downcast::<T>().ok().map(|old| *old))
+    }
+
+    /// 向注册表中注册一个新值
+    ///
+    /// 如果相同的键已存在，那么旧值将会被新值替换
+    ///
+    /// 键在所有类型对应的表之间共享同一份分配，仅在该键尚未被任何表使用时为其分配内存
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::<i32>::register("my_key", 42);
+    /// Registry::register("my_key", 64);
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn register(name: &str, value: T) -> Result<(), ()> {
+        unchecked("write", Self::register_checked(name, value)).ok_or(())
+    }
+
+    /// 与 `register` 相同，但失败时返回 `RegistryError` 说明失败的原因
+    ///
+    /// 键位于已被封存的前缀之下时返回 `RegistryError::SealedNamespace`，该类型已被冻结时返回 `RegistryError::Frozen`，
+    /// 锁已中毒时返回 `RegistryError::Poisoned`；在调试模式下，当前线程已持有所需的锁时返回 `RegistryError::WouldDeadlock`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{seal_prefix, Registry, RegistryError};
+    ///
+    /// assert_eq!(Registry::register_checked("user.name", String::from("alice")), Ok(()));
+    ///
+    /// seal_prefix("system");
+    /// let err = Registry::register_checked("system.name", String::from("x")).unwrap_err();
+    /// assert!(matches!(err, RegistryError::SealedNamespace { .. }));
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "key `system.name` of type `alloc::string::String`: namespace is sealed"
+    /// );
+    ///
+    /// Registry::<u16>::register("port", 80).unwrap();
+    /// Registry::<u16>::freeze();
+    /// assert!(matches!(
+    ///     Registry::register_checked("port", 8080u16),
+    ///     Err(RegistryError::Frozen { .. })
+    /// ));
+    /// ```
+    pub fn register_checked(name: &str, value: T) -> Result<(), RegistryError> {
+        Self::_register(name, value).map(|_| ())
+    }
+
+    /// 向注册表中注册一个新值，并返回被替换的旧值
+    ///
+    /// 与 `register` 相同，但如果相同的键已存在，则返回 `Ok(Some(旧值))`；否则返回 `Ok(None)`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// assert_eq!(Registry::<i32>::register_replacing("my_key", 42), Ok(None));
+    /// assert_eq!(Registry::<i32>::register_replacing("my_key", 64), Ok(Some(42)));
+    /// assert_eq!(Registry::<i32>::with("my_key", |v| *v), Some(64));
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn register_replacing(name: &str, value: T) -> Result<Option<T>, ()> {
+        unchecked("write", Self::_register(name, value)).ok_or(())
+    }
+
+    /// 向注册表中注册一个新值，并附加在该值离开注册表时执行的回调函数
+    ///
+    /// 与 `register` 相同，如果相同的键已存在，那么旧值将会被新值替换。无论该值通过 `remove`、`replace`、`clear`、`drain`、
+    /// 再次注册同一个键还是其他任何方式离开注册表，回调函数都会在该值被返回或销毁之前收到其当前对应的键与该值的可变引用。
+    /// 回调函数在释放注册表的锁之后执行，因而可以在其中访问注册表；该值被移动到其他键下时不会执行回调函数
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::sync::Mutex;
+    ///
+    /// static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    ///
+    /// struct Resource(u32);
+    ///
+    /// fn register(name: &str, id: u32) {
+    ///     Registry::register_with_drop(name, Resource(id), |key, res: &mut Resource| {
+    ///         LOG.lock().unwrap().push(format!("{}:{}", key, res.0));
+    ///     })
+    ///     .unwrap();
+    /// }
+    /// fn log() -> Vec<String> {
+    ///     std::mem::take(&mut *LOG.lock().unwrap())
+    /// }
+    ///
+    /// register("a", 1);
+    /// assert_eq!(Registry::<Resource>::remove("a").map(|r| r.0), Some(1));
+    /// assert_eq!(log(), vec!["a:1"]);
+    ///
+    /// register("a", 2);
+    /// assert_eq!(Registry::replace("a", Resource(3)).map(|r| r.0), Some(2));
+    /// assert_eq!(log(), vec!["a:2"]);
+    ///
+    /// register("a", 4);
+    /// register("a", 5);
+    /// assert_eq!(log(), vec!["a:4"]);
+    ///
+    /// register("b", 6);
+    /// Registry::<Resource>::rename("b", "c").unwrap();
+    /// assert_eq!(Registry::<Resource>::drain().len(), 2);
+    /// let mut drained = log();
+    /// drained.sort();
+    /// assert_eq!(drained, vec!["a:5", "c:6"]);
+    ///
+    /// register("d", 7);
+    /// assert_eq!(Registry::<Resource>::clear(), 1);
+    /// assert_eq!(log(), vec!["d:7"]);
+    /// ```
+    ///
+    /// 回调函数中可以访问注册表：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Connection(u32);
+    ///
+    /// Registry::register_with_drop("primary", Connection(1), |_, conn: &mut Connection| {
+    ///     Registry::register("fallback", Connection(conn.0 + 1)).unwrap();
+    /// })
+    /// .unwrap();
+    ///
+    /// Registry::<Connection>::remove("primary");
+    /// assert_eq!(Registry::<Connection>::with("fallback", |c| c.0), Some(2));
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn register_with_drop<F>(name: &str, value: T, on_remove: F) -> Result<(), ()>
+    where
+        F: FnOnce(&str, &mut T) + Send + 'static,
+    {
+        let func: OnRemove = Box::new(move |key, value| {
+            if let Some(value) = value.downcast_mut::<T>() {
+                on_remove(key, value);
+            }
+        });
+        let slot = Record::new(Box::new(value)).with_on_remove(name, func);
+        unchecked("write", Self::_register_slot(name, slot))
+            .map(|_| ())
+            .ok_or(())
+    }
+
+    /// 向注册表中注册一个延迟构造的值
+    ///
+    /// 注册时仅保存初始化函数；该键首次以任何方式被访问（`with`、`apply`、`get`、句柄、守卫等）时，
+    /// 初始化函数在该键的写锁下执行并以其返回值替换自身，因而即使多个线程同时首次访问，初始化函数也只会执行一次；
+    /// 此后该键与通过 `register` 注册的键没有区别。初始化函数不应访问同一类型的注册表，否则可能导致死锁
+    ///
+    /// 尚未初始化的键同样被视为存在，`exists` 返回 `true`。在首次访问之前移除该键（包括 `remove`、`drain` 等）不会执行初始化函数，
+    /// 而是直接丢弃它，此时 `remove` 返回 `None`
+    ///
+    /// 与 `register` 相同，如果相同的键已存在，那么旧值将会被替换
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Barrier;
+    /// use std::thread;
+    ///
+    /// static BUILT: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// struct Expensive(Vec<u64>);
+    ///
+    /// Registry::register_lazy("table", || {
+    ///     BUILT.fetch_add(1, Ordering::SeqCst);
+    ///     Expensive((0..1000).collect())
+    /// })
+    /// .unwrap();
+    /// assert!(Registry::<Expensive>::exists("table"));
+    /// assert_eq!(BUILT.load(Ordering::SeqCst), 0);
+    ///
+    /// // 多个线程同时首次访问，初始化函数只执行一次
+    /// let barrier = Barrier::new(8);
+    /// thread::scope(|s| {
+    ///     for _ in 0..8 {
+    ///         s.spawn(|| {
+    ///             barrier.wait();
+    ///             assert_eq!(Registry::<Expensive>::with("table", |t| t.0.len()), Some(1000));
+    ///         });
+    ///     }
+    /// });
+    /// assert_eq!(BUILT.load(Ordering::SeqCst), 1);
+    ///
+    /// // 此后与普通的键没有区别
+    /// Registry::<Expensive>::apply("table", |t| t.0.clear());
+    /// assert_eq!(Registry::<Expensive>::remove("table").map(|t| t.0.len()), Some(0));
+    ///
+    /// // 首次访问之前移除，初始化函数不会执行
+    /// Registry::register_lazy("unused", || -> Expensive { unreachable!() }).unwrap();
+    /// assert!(Registry::<Expensive>::remove("unused").is_none());
+    /// assert!(!Registry::<Expensive>::exists("unused"));
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn register_lazy<F>(name: &str, init: F) -> Result<(), ()>
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let init: Init = Box::new(move || Box::new(init()));
+        unchecked("write", Self::_register_slot(name, Record::lazy::<T>(init)))
+            .map(|_| ())
+            .ok_or(())
+    }
+
+    /// 向注册表中注册一个构造函数
+    ///
+    /// 构造函数保存在与值相互独立的表中，因而同一个键可以同时对应一个值与一个构造函数；
+    /// 通过 `create` 调用构造函数得到的新实例不会被注册。如果相同的键已存在构造函数，那么旧的构造函数将会被替换
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Enemy {
+    ///     hp: u32,
+    /// }
+    ///
+    /// Registry::register_factory("goblin", || Enemy { hp: 10 }).unwrap();
+    ///
+    /// let mut a = Registry::<Enemy>::create("goblin").unwrap();
+    /// let b = Registry::<Enemy>::create("goblin").unwrap();
+    /// let c = Registry::<Enemy>::create("goblin").unwrap();
+    /// a.hp -= 4;
+    /// assert_eq!((a.hp, b.hp, c.hp), (6, 10, 10));
+    ///
+    /// // 构造函数与值互不影响
+    /// assert!(!Registry::<Enemy>::exists("goblin"));
+    /// Registry::register("goblin", Enemy { hp: 1 }).unwrap();
+    /// assert_eq!(Registry::<Enemy>::create("goblin").map(|e| e.hp), Some(10));
+    ///
+    /// Registry::<Enemy>::create_and_register("goblin", "goblin.1").unwrap();
+    /// assert_eq!(Registry::<Enemy>::with("goblin.1", |e| e.hp), Some(10));
+    /// assert!(Registry::<Enemy>::create("orc").is_none());
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn register_factory<F>(name: &str, factory: F) -> Result<(), ()>
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        Registry::<Factory<T>>::register(name, Factory(Box::new(factory)))
+    }
+
+    /// 调用指定键对应的构造函数，返回新的实例
+    ///
+    /// 新的实例不会被注册；如果该键没有对应的构造函数，则返回 `None`
+    pub fn create(name: &str) -> Option<T> {
+        Registry::<Factory<T>>::with(name, |factory| (factory.0)())
+    }
+
+    /// 调用指定键对应的构造函数，并将新的实例注册在 `instance_key` 下
+    ///
+    /// 与 `register` 相同，如果 `instance_key` 已存在，那么旧值将会被新的实例替换。
+    /// 如果该键没有对应的构造函数或注册失败，则返回 `None`
+    pub fn create_and_register(name: &str, instance_key: &str) -> Option<()> {
+        Self::register(instance_key, Self::create(name)?).ok()
+    }
+
+    fn _acquire<F: FnOnce() -> T>(name: &str, init: F) -> Option<usize> {
+        let sealed = is_sealed(name);
+        let type_table = Self::_ensure_type(name)?;
+        check_deadlock!(mut T:name;Lock::TypeKey);
+        let Some(type_map) = type_table.writable()?.write_shard_attached(name) else {
+            return Self::_acquire(name, init);
+        };
+        let mut type_map = type_map.ok()?;
+        let slot = match type_map.entry(intern(name)) {
+            hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            hash_map::Entry::Vacant(_) if sealed => return None,
+            hash_map::Entry::Vacant(entry) => {
+                // 与 `get_or_register_with` 相同，记录所持有的分片的写锁，从而检查初始化函数中的嵌套访问
+                let frame = ContextOperator::enter(Context::Type(
+                    TypeId::of::<T>(),
+                    std::any::type_name::<T>(),
+                ));
+                let value = init();
+                drop(frame);
+                let slot = entry.insert(Record::new(Box::new(value)));
+                notify_registered();
+                slot
+            }
+        };
+        slot.refs += 1;
+        Some(slot.refs)
+    }
+
+    /// 取得指定键对应的值的一份所有权，并返回取得后的所有者数量
+    ///
+    /// 如果键不存在，则使用初始化函数创建并注册该值，所有者数量为 1；否则，仅将所有者数量加 1，初始化函数不会执行。
+    /// 通过 `register` 等方式注册的值的所有者数量为 0。检查与注册在同一个写锁下完成，初始化函数同样在该写锁下执行，
+    /// 在其中写入同一类型的值会导致线程死锁（调试模式下会发生 panic）
+    ///
+    /// 如果该类型已被冻结、键不存在且位于已被封存的前缀之下或锁已中毒，则返回 0
+    ///
+    /// 所有者数量仅影响 `release`：`remove`、`clear` 等函数无论所有者数量如何都会移除该值，
+    /// 再次注册同一个键同样会替换该值并将所有者数量重置为 0
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Pool(Vec<u32>);
+    ///
+    /// assert_eq!(Registry::acquire("pool", || Pool(vec![1, 2, 3])), 1);
+    /// assert_eq!(Registry::<Pool>::acquire("pool", || unreachable!()), 2);
+    /// assert_eq!(Registry::<Pool>::acquire("pool", || unreachable!()), 3);
+    ///
+    /// // 任意顺序释放，该值存活到最后一次释放
+    /// assert!(Registry::<Pool>::release("pool").is_none());
+    /// assert!(Registry::<Pool>::exists("pool"));
+    /// assert!(Registry::<Pool>::release("pool").is_none());
+    /// assert!(Registry::<Pool>::exists("pool"));
+    /// assert_eq!(Registry::<Pool>::release("pool").map(|p| p.0), Some(vec![1, 2, 3]));
+    /// assert!(!Registry::<Pool>::exists("pool"));
+    ///
+    /// // `remove` 无论所有者数量如何都会移除该值
+    /// Registry::acquire("pool", || Pool(vec![]));
+    /// Registry::acquire("pool", || Pool(vec![]));
+    /// assert!(Registry::<Pool>::remove("pool").is_some());
+    /// assert!(Registry::<Pool>::release("pool").is_none());
+    /// ```
+    ///
+    /// 三个线程分别取得并释放同一个值：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::sync::Barrier;
+    /// use std::thread;
+    ///
+    /// struct Device;
+    ///
+    /// let acquired = Barrier::new(3);
+    /// let released = thread::scope(|s| {
+    ///     let owners: Vec<_> = (0..3)
+    ///         .map(|_| {
+    ///             s.spawn(|| {
+    ///                 Registry::acquire("device", || Device);
+    ///                 acquired.wait();
+    ///                 Registry::<Device>::release("device").is_some()
+    ///             })
+    ///         })
+    ///         .collect();
+    ///     owners.into_iter().map(|o| o.join().unwrap()).collect::<Vec<_>>()
+    /// });
+    /// assert_eq!(released.iter().filter(|r| **r).count(), 1);
+    /// assert!(!Registry::<Device>::exists("device"));
+    /// ```
+    pub fn acquire<F: FnOnce() -> T>(name: &str, init: F) -> usize {
+        Self::_acquire(name, init).unwrap_or(0)
+    }
+
+    /// 释放指定键对应的值的一份所有权
+    ///
+    /// 所有者数量减 1；当所有者数量降为 0 时移除该值并返回它，否则返回 `None`。
+    /// 所有者数量为 0 的值（如通过 `register` 注册的值）会被直接移除。
+    /// 如果键不存在、该类型已被冻结或锁已中毒，则不会修改注册表并返回 `None`；
+    /// 与 `remove` 相同，如果被移除的值仍被 `Handle` 共享，则同样返回 `None`
+    pub fn release(name: &str) -> Option<T> {
+        let type_id = TypeId::of::<T>();
+        let slot = {
+            let type_table = _TABLE.get(type_id).ok()??;
+            let type_map = type_table.writable()?;
+            check_deadlock!(mut T:name;Lock::TypeKey);
+            let mut type_map = type_map.write_shard(name).ok()?;
+            let slot = type_map.get_mut(name)?;
+            if slot.refs > 1 {
+                slot.refs -= 1;
+                return None;
+            }
+            type_map.remove(name)?
+        };
+        let value = slot.into_inner()?.ok()?;
+        let type_value = value.downcast::<T>().ok()?;
+        Some(*type_value)
+    }
+
+    /// 向注册表中注册一个新值，并返回在被销毁时移除该键的守卫
+    ///
+    /// 与 `register` 相同，如果相同的键已存在，那么旧值将会被新值替换。守卫被销毁（包括 panic 导致的栈展开）时会通过 `remove` 移除该键；
+    /// 调用 `RegistrationGuard::forget` 可以保留该键，调用 `RegistrationGuard::take` 可以提前移除该键并取回其对应的值。
+    /// 守卫实现了 `Send`，因而可以被移动到负责该键生命周期的线程中
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// let guard = Registry::register_guarded("temp", 42).unwrap();
+    /// assert!(Registry::<i32>::exists("temp"));
+    /// drop(guard);
+    /// assert!(!Registry::<i32>::exists("temp"));
+    ///
+    /// // 发生 panic 时同样会移除该键
+    /// let result = std::panic::catch_unwind(|| {
+    ///     let _guard = Registry::register_guarded("temp", 1).unwrap();
+    ///     panic!("test failed");
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(!Registry::<i32>::exists("temp"));
+    ///
+    /// // 守卫可以被移动到其他线程
+    /// let guard = Registry::register_guarded("worker", 2).unwrap();
+    /// thread::spawn(move || drop(guard)).join().unwrap();
+    /// assert!(!Registry::<i32>::exists("worker"));
+    ///
+    /// Registry::register_guarded("kept", 3).unwrap().forget();
+    /// assert_eq!(Registry::<i32>::with("kept", |v| *v), Some(3));
+    ///
+    /// let guard = Registry::register_guarded("taken", 4).unwrap();
+    /// assert_eq!(guard.take(), Some(4));
+    /// assert!(!Registry::<i32>::exists("taken"));
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn register_guarded(name: &str, value: T) -> Result<RegistrationGuard<T>, ()> {
+        Self::register(name, value)?;
+        Ok(RegistrationGuard::new(name))
+    }
+
+    /// 仅当指定键不存在时，向注册表中注册一个新值
+    ///
+    /// 检查与注册在同一个写锁下完成；如果键已存在（或锁已中毒），则不会修改注册表，并将传入的值通过 `Err` 返回
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// assert_eq!(Registry::<i32>::register_if_absent("my_key", 42), Ok(()));
+    /// assert_eq!(Registry::<i32>::register_if_absent("my_key", 64), Err(64));
+    /// assert_eq!(Registry::<i32>::with("my_key", |v| *v), Some(42));
+    /// ```
+    ///
+    /// 多个线程同时注册同一个键时，只有一个线程会成功：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// let workers: Vec<_> = (0..8usize)
+    ///     .map(|i| thread::spawn(move || Registry::register_if_absent("plugin", i).is_ok()))
+    ///     .collect();
+    /// let succeeded = workers
+    ///     .into_iter()
+    ///     .map(|worker| worker.join().unwrap())
+    ///     .filter(|ok| *ok)
+    ///     .count();
+    /// assert_eq!(succeeded, 1);
+    /// ```
+    pub fn register_if_absent(name: &str, value: T) -> Result<(), T> {
+        Self::try_register(name, value).map_err(RegisterError::into_value)
+    }
+
+    /// 仅当指定键不存在时，向注册表中注册一个新值
+    ///
+    /// 与 `register_if_absent` 相同，但失败时返回包含键、原因与被拒绝的值的 `RegisterError`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, RegisterErrorKind};
+    ///
+    /// Registry::<i32>::try_register("my_key", 42).unwrap();
+    ///
+    /// let err = Registry::<i32>::try_register("my_key", 64).unwrap_err();
+    /// assert_eq!(err.kind(), RegisterErrorKind::AlreadyExists);
+    /// assert_eq!(err.key(), "my_key");
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "key `my_key` is already registered for type `i32`"
+    /// );
+    /// assert_eq!(err.into_value(), 64);
+    /// ```
+    pub fn try_register(name: &str, value: T) -> Result<(), RegisterError<T>> {
+        let error = |kind, value| Err(RegisterError::new(name, kind, value));
+        if is_sealed(name) {
+            return error(RegisterErrorKind::SealedNamespace, value);
+        }
+        Self::_with_shard(name, value, |type_map, value| {
+            match type_map.entry(intern(name)) {
+                hash_map::Entry::Occupied(_) => error(RegisterErrorKind::AlreadyExists, value),
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(Record::new(Box::new(value)));
+                    notify_registered();
+                    Ok(())
+                }
+            }
+        })
+        .unwrap_or_else(|(e, value)| match e {
+            RegistryError::WouldDeadlock { key, type_name } => {
+                thread_deadlock!("cannot write key `{key}` of type `{type_name}`")
+            }
+            RegistryError::Frozen { .. } => error(RegisterErrorKind::Frozen, value),
+            _ => error(RegisterErrorKind::Poisoned, value),
+        })
+    }
+
+    fn _extend<I: IntoIterator<Item = (String, T)>>(iter: I) -> Option<ExtendReport> {
+        let mut report = ExtendReport::default();
+        let mut replaced = Vec::new();
+        let mut discarded = Vec::new();
+        {
+            let type_table = Self::_ensure_type("")?;
+            check_deadlock!(mut T:"";Lock::Type);
+            let Some(type_map) = type_table.writable()?.write_attached() else {
+                return Self::_extend(iter);
+            };
+            let mut type_map = type_map.ok()?;
+            for (name, value) in iter {
+                if is_sealed(&name) {
+                    report.sealed += 1;
+                    discarded.push(value);
+                    continue;
+                }
+                match insert_slot(type_map.shard_mut(&name), intern(&name), Box::new(value)) {
+                    Some(old) => {
+                        report.replaced += 1;
+                        replaced.push(old);
+                    }
+                    None => report.inserted += 1,
+                }
+            }
+        }
+        // 被替换的旧值与未被注册的值在释放锁之后销毁
+        drop(replaced);
+        drop(discarded);
+        Some(report)
+    }
+
+    /// 向注册表中批量注册新值
+    ///
+    /// 所有值在同一个写锁下注册；如果相同的键已存在，那么旧值将会被新值替换。位于已被封存的前缀之下的键不会被注册，其值被丢弃。
+    /// 返回新建、被替换与因封存而未被注册的键的数量
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{ExtendReport, Registry};
+    ///
+    /// Registry::<i32>::register("a", 0).unwrap();
+    ///
+    /// let report = Registry::<i32>::extend([
+    ///     ("a".to_string(), 1),
+    ///     ("b".to_string(), 2),
+    ///     ("c".to_string(), 3),
+    ///     ("c".to_string(), 4),
+    /// ]);
+    /// assert_eq!(report, ExtendReport { inserted: 2, replaced: 2, sealed: 0 });
+    /// assert_eq!(Registry::<i32>::len(), 3);
+    /// assert_eq!(Registry::<i32>::with("a", |v| *v), Some(1));
+    /// assert_eq!(Registry::<i32>::with("c", |v| *v), Some(4));
+    /// ```
+    pub fn extend<I: IntoIterator<Item = (String, T)>>(iter: I) -> ExtendReport {
+        Self::_extend(iter).unwrap_or_default()
+    }
+
+    fn _register_many(
+        items: Vec<(String, T)>,
+        on_conflict: ConflictPolicy,
+    ) -> Result<RegisterManyReport<T>, Vec<(String, T)>> {
+        let mut report = RegisterManyReport::default();
+        let mut discarded: Vec<Value> = Vec::new();
+        let mut replaced = Vec::new();
+        if let Some((name, _)) = items.iter().find(|(name, _)| is_sealed(name)) {
+            report.skipped.push(name.clone());
+            report.remainder = items;
+            return Ok(report);
+        }
+        {
+            let Some(type_table) = Self::_ensure_type("") else {
+                return Err(items);
+            };
+            check_deadlock!(mut T:"";Lock::Type);
+            let Some(type_table) = type_table.writable() else {
+                return Err(items);
+            };
+            let Some(type_map) = type_table.write_attached() else {
+                return Self::_register_many(items, on_conflict);
+            };
+            let Ok(mut type_map) = type_map else {
+                return Err(items);
+            };
+            if on_conflict == ConflictPolicy::Fail {
+                let mut seen = HashSet::new();
+                let conflict = items
+                    .iter()
+                    .find(|(name, _)| type_map.contains_key(name) || !seen.insert(name.as_str()));
+                if let Some((name, _)) = conflict {
+                    report.skipped.push(name.clone());
+                    report.remainder = items;
+                    return Ok(report);
+                }
+            }
+            for (name, value) in items {
+                if on_conflict == ConflictPolicy::Skip && type_map.contains_key(&name) {
+                    report.skipped.push(name);
+                    discarded.push(Box::new(value));
+                    continue;
+                }
+                match insert_slot(type_map.shard_mut(&name), intern(&name), Box::new(value)) {
+                    Some(old) => {
+                        report.replaced.push(name);
+                        replaced.push(old);
+                    }
+                    None => report.inserted.push(name),
+                }
+            }
+        }
+        // 被替换或跳过的值在释放锁之后销毁
+        drop(discarded);
+        drop(replaced);
+        Ok(report)
+    }
+
+    /// 向注册表中批量注册新值，并按照给定的策略处理已存在的键
+    ///
+    /// 所有值在同一个写锁下注册，其他线程不会观察到只注册了一部分的中间状态；如果该类型从未注册过，则会先为其创建对应的表。
+    ///
+    /// - `ConflictPolicy::Overwrite`：使用新值替换旧值，键被记录在 `replaced` 中
+    /// - `ConflictPolicy::Skip`：保留旧值并丢弃新值，键被记录在 `skipped` 中
+    /// - `ConflictPolicy::Fail`：遇到第一个冲突的键（包括 `items` 中重复的键）时放弃整个操作，不注册任何值；
+    ///   冲突的键被记录在 `skipped` 中，所有值按原顺序返回在 `remainder` 中
+    ///
+    /// 如果任一键位于已被封存的前缀之下，则与 `ConflictPolicy::Fail` 相同，不注册任何值，该键被记录在 `skipped` 中，
+    /// 所有值返回在 `remainder` 中；如果该类型已被冻结或无法获取锁，则同样不注册任何值，所有值返回在 `remainder` 中
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{ConflictPolicy, Registry};
+    ///
+    /// struct Object(u32);
+    ///
+    /// // 该类型尚未注册过
+    /// let report = Registry::register_many(
+    ///     vec![("tree".to_string(), Object(1)), ("rock".to_string(), Object(2))],
+    ///     ConflictPolicy::Fail,
+    /// );
+    /// assert_eq!(report.inserted, vec!["tree", "rock"]);
+    /// assert!(report.remainder.is_empty());
+    ///
+    /// // 覆盖已存在的键
+    /// let report = Registry::register_many(
+    ///     vec![("tree".to_string(), Object(3)), ("bush".to_string(), Object(4))],
+    ///     ConflictPolicy::Overwrite,
+    /// );
+    /// assert_eq!(report.inserted, vec!["bush"]);
+    /// assert_eq!(report.replaced, vec!["tree"]);
+    /// assert_eq!(Registry::<Object>::with("tree", |o| o.0), Some(3));
+    ///
+    /// // 跳过已存在的键
+    /// let report = Registry::register_many(
+    ///     vec![("rock".to_string(), Object(5)), ("pond".to_string(), Object(6))],
+    ///     ConflictPolicy::Skip,
+    /// );
+    /// assert_eq!(report.inserted, vec!["pond"]);
+    /// assert_eq!(report.skipped, vec!["rock"]);
+    /// assert_eq!(Registry::<Object>::with("rock", |o| o.0), Some(2));
+    ///
+    /// // 遇到冲突时不注册任何值
+    /// let report = Registry::register_many(
+    ///     vec![("hill".to_string(), Object(7)), ("pond".to_string(), Object(8))],
+    ///     ConflictPolicy::Fail,
+    /// );
+    /// assert!(report.inserted.is_empty());
+    /// assert_eq!(report.skipped, vec!["pond"]);
+    /// let remainder: Vec<_> = report.remainder.iter().map(|(k, o)| (k.as_str(), o.0)).collect();
+    /// assert_eq!(remainder, vec![("hill", 7), ("pond", 8)]);
+    /// assert!(!Registry::<Object>::exists("hill"));
+    /// assert_eq!(Registry::<Object>::with("pond", |o| o.0), Some(6));
+    /// ```
+    pub fn register_many(
+        items: Vec<(String, T)>,
+        on_conflict: ConflictPolicy,
+    ) -> RegisterManyReport<T> {
+        Self::_register_many(items, on_conflict).unwrap_or_else(|items| RegisterManyReport {
+            remainder: items,
+            ..Default::default()
+        })
+    }
+
+    /// 从注册表中移除指定键对应的值
+    ///
+    /// 如果键不存在，则返回 `None`。其他线程正在 `with` 或 `apply` 的闭包中访问该值时，等待其访问结束后再取回该值。
+    /// 移除该类型下的最后一个键后，该类型对应的表也会被移除（参见 `gc`）
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::<i32>::register("my_key", 42);
+    /// assert_eq!(Registry::<i32>::remove("my_key"), Some(42));
+    /// assert_eq!(Registry::<i32>::remove("my_key"), None);
+    /// ```
+    ///
+    /// 移除最后一个键后，该类型不再出现在 `registered_types` 中：
+    ///
+    /// ```rust
+    /// use gom::{registered_types, Registry};
+    /// use std::any::TypeId;
+    ///
+    /// struct Plugin;
+    ///
+    /// let listed = || registered_types().iter().any(|info| info.type_id == TypeId::of::<Plugin>());
+    /// Registry::register("a", Plugin).unwrap();
+    /// Registry::register("b", Plugin).unwrap();
+    /// Registry::<Plugin>::remove("a");
+    /// assert!(listed());
+    /// Registry::<Plugin>::remove("b");
+    /// assert!(!listed());
+    /// ```
+    ///
+    /// 与其他线程的 `apply` 操作并发执行时，取回的值包含闭包函数所做的修改：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    ///
+    /// Registry::register("busy", 1u8).unwrap();
+    /// let (started, wait_started) = mpsc::channel();
+    /// let writer = thread::spawn(move || {
+    ///     Registry::<u8>::apply("busy", |v| {
+    ///         started.send(()).unwrap();
+    ///         thread::sleep(std::time::Duration::from_millis(50));
+    ///         *v += 1;
+    ///     })
+    /// });
+    /// wait_started.recv().unwrap();
+    /// assert_eq!(Registry::<u8>::remove("busy"), Some(2));
+    /// assert_eq!(writer.join().unwrap(), Some(()));
+    /// ```
+    pub fn remove(name: &str) -> Option<T> {
+        unchecked("write", Self::remove_checked(name))
+    }
+
+    /// 与 `remove` 相同，但失败时返回 `RegistryError` 说明失败的原因
+    ///
+    /// 该类型没有对应的表时返回 `RegistryError::TypeNotRegistered`，键不存在时返回 `RegistryError::KeyNotFound`，
+    /// 该类型已被冻结时返回 `RegistryError::Frozen`，锁已中毒时返回 `RegistryError::Poisoned`；
+    /// 键已被移除但其值仍被 `Handle` 共享时返回 `RegistryError::Shared`，值将在最后一个 `Handle` 被销毁时销毁。
+    /// 在调试模式下，当前线程已持有所需的锁时返回 `RegistryError::WouldDeadlock`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, RegistryError};
+    ///
+    /// Registry::register("a", 1u64).unwrap();
+    /// Registry::register("b", 2u64).unwrap();
+    /// assert_eq!(Registry::<u64>::remove_checked("a"), Ok(1));
+    /// assert!(matches!(
+    ///     Registry::<u64>::remove_checked("a"),
+    ///     Err(RegistryError::KeyNotFound { .. })
+    /// ));
+    ///
+    /// let handle = Registry::<u64>::handle("b").unwrap();
+    /// assert!(matches!(
+    ///     Registry::<u64>::remove_checked("b"),
+    ///     Err(RegistryError::Shared { .. })
+    /// ));
+    /// assert!(!Registry::<u64>::exists("b"));
+    /// assert_eq!(handle.with(|v| *v), Some(2));
+    /// ```
+    pub fn remove_checked(name: &str) -> Result<T, RegistryError> {
+        let type_id = TypeId::of::<T>();
+        let (lock_value, emptied) = {
+            let map = _TABLE
+                .read()
+                .map_err(|_| registry_error!(Poisoned, name, T))?;
+            let type_table = map
+                .get(&type_id)
+                .ok_or_else(|| registry_error!(TypeNotRegistered, name, T))?;
+            if type_table.is_frozen() {
+                return Err(registry_error!(Frozen, name, T));
+            }
+            if would_deadlock!(mut T:name;Lock::TypeKey) {
+                return Err(registry_error!(WouldDeadlock, name, T));
+            }
+            let mut type_map = type_table
+                .write_shard(name)
+                .map_err(|_| registry_error!(Poisoned, name, T))?;
+            let lock_value = type_map
+                .remove(name)
+                .ok_or_else(|| registry_error!(KeyNotFound, name, T))?;
+            // 仅在该键所在的分片变为空时才检查其他分片
+            let emptied = type_map.is_empty();
+            drop(type_map);
+            (lock_value, emptied && type_table.is_empty())
+        };
+        if emptied {
+            collect_empty(|id| *id == type_id);
+        }
+        let value = lock_value
+            .into_inner()
+            .ok_or_else(|| registry_error!(Shared, name, T))?
+            .map_err(|_| registry_error!(Poisoned, name, T))?;
+        let type_value = value
+            .downcast::<T>()
+            .map_err(|_| registry_error!(Downcast, name, T))?;
+        Ok(*type_value)
+    }
+
+    /// 从注册表中移除指定键对应的值，并将其所有权交给闭包函数做最后一次使用
+    ///
+    /// 移除在写锁下完成，闭包函数在释放所有锁之后执行，因而闭包函数开始执行时其他线程已无法观察到该键。
+    /// 如果闭包函数发生 panic，该值已被移除且会在栈展开时被销毁，panic 将继续向上传播。
+    /// 如果键不存在或锁已中毒，则返回 `None` 并且不会执行闭包函数；否则，返回闭包函数的返回值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::sync::{Arc, Barrier};
+    /// use std::thread;
+    ///
+    /// struct Connection {
+    ///     id: u32,
+    /// }
+    ///
+    /// Registry::register("conn", Connection { id: 7 }).unwrap();
+    ///
+    /// let started = Arc::new(Barrier::new(2));
+    /// let finish = Arc::new(Barrier::new(2));
+    /// let closer = {
+    ///     let (started, finish) = (started.clone(), finish.clone());
+    ///     thread::spawn(move || {
+    ///         Registry::<Connection>::apply_take("conn", |conn| {
+    ///             started.wait();
+    ///             finish.wait();
+    ///             conn.id
+    ///         })
+    ///     })
+    /// };
+    /// started.wait();
+    /// // 闭包函数已开始执行，该键对其他线程不可见
+    /// assert!(!Registry::<Connection>::exists("conn"));
+    /// assert_eq!(Registry::<Connection>::apply("conn", |c| c.id), None);
+    /// finish.wait();
+    ///
+    /// assert_eq!(closer.join().unwrap(), Some(7));
+    /// assert_eq!(Registry::<Connection>::apply_take("conn", |c| c.id), None);
+    /// ```
+    ///
+    /// 闭包函数发生 panic 时，值仍然已被移除：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("key", 1u8).unwrap();
+    /// let result = std::panic::catch_unwind(|| {
+    ///     Registry::<u8>::apply_take("key", |_| panic!("teardown failed"));
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(!Registry::<u8>::exists("key"));
+    /// ```
+    pub fn apply_take<R, F: FnOnce(T) -> R>(name: &str, func: F) -> Option<R> {
+        Self::remove(name).map(func)
+    }
+
+    fn _exists(name: &str) -> Option<bool> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                let type_map = type_table.read_shard(name).ok()?;
+                Some(type_map.contains_key(name))
+            })
+            .ok()??
+    }
+
+    /// 判断指定键是否存在于注册表中
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::<i32>::register("my_key", 42);
+    /// assert_eq!(Registry::<i32>::exists("my_key"), true);
+    /// assert_eq!(Registry::<i32>::exists("other_key"), false);
+    /// ```
+    pub fn exists(name: &str) -> bool {
+        Self::_exists(name).unwrap_or(false)
+    }
+
+    /// 阻塞当前线程，直至指定键在该类型下被注册，或者超过给定的等待时间
+    ///
+    /// 键已存在时立即返回 `true`；`timeout` 为 `None` 时一直等待。以 `register`、`set`、`rename` 等任何方式出现的新键都会唤醒等待者，
+    /// 因而在检查之后、开始等待之前发生的注册不会被错过，虚假唤醒也不会导致提前返回。超时返回 `false`。
+    /// 注册该键的线程可能需要当前线程持有的锁，因而不应在 `with`、`apply` 等闭包中等待
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::{thread, time::Duration};
+    ///
+    /// let loader = thread::spawn(|| {
+    ///     thread::sleep(Duration::from_millis(20));
+    ///     Registry::register(".app.config", String::from("loaded")).unwrap();
+    /// });
+    /// assert!(Registry::<String>::wait_for(".app.config", None));
+    /// assert_eq!(Registry::<String>::get(".app.config"), Some(String::from("loaded")));
+    /// assert!(!Registry::<String>::wait_for(".app.missing", Some(Duration::from_millis(10))));
+    /// loader.join().unwrap();
+    /// ```
+    pub fn wait_for(name: &str, timeout: Option<Duration>) -> bool {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let registered = &*_REGISTERED;
+        registered.waiters.fetch_add(1, Ordering::SeqCst);
+        let found = loop {
+            let seen = *registered
+                .generation
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if Self::exists(name) {
+                break true;
+            }
+            let generation = registered
+                .generation
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if *generation != seen {
+                continue;
+            }
+            match deadline {
+                None => drop(
+                    registered
+                        .cond
+                        .wait(generation)
+                        .unwrap_or_else(|e| e.into_inner()),
+                ),
+                Some(deadline) => {
+                    let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                        break Self::exists(name);
+                    };
+                    drop(
+                        registered
+                            .cond
+                            .wait_timeout(generation, remaining)
+                            .unwrap_or_else(|e| e.into_inner()),
+                    );
+                }
+            }
+        };
+        registered.waiters.fetch_sub(1, Ordering::SeqCst);
+        found
+    }
+
+    /// 与 `wait_for` 相同，并在键被注册后以闭包函数访问其对应的值
+    ///
+    /// 超时返回 `None`；与 `with` 相同，键在被注册后、访问之前又被移除时同样返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::{thread, time::Duration};
+    ///
+    /// let loader = thread::spawn(|| {
+    ///     thread::sleep(Duration::from_millis(20));
+    ///     Registry::register("port", 8080u16).unwrap();
+    /// });
+    /// let port = Registry::<u16>::wait_for_with("port", Some(Duration::from_secs(5)), |p| *p);
+    /// assert_eq!(port, Some(8080));
+    /// loader.join().unwrap();
+    /// ```
+    pub fn wait_for_with<R, F: FnOnce(&T) -> R>(
+        name: &str,
+        timeout: Option<Duration>,
+        func: F,
+    ) -> Option<R> {
+        if !Self::wait_for(name, timeout) {
+            return None;
+        }
+        Self::with(name, func)
+    }
+
+    fn _keys_with_prefix(prefix: &str) -> Option<Vec<String>> {
+        let mut ret = Self::_keys_where(|name| has_prefix(name, prefix))?;
+        order_by_key(&mut ret, String::as_str);
+        Some(ret)
+    }
+
+    // 获取该类型下所有满足条件的键，不排序
+    fn _keys_where(predicate: impl Fn(&str) -> bool) -> Option<Vec<String>> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                let type_map = type_table.read().ok()?;
+                let keys = type_map
+                    .keys()
+                    .filter(|name| predicate(name))
+                    .map(|name| name.to_string())
+                    .collect();
+                Some(keys)
+            })
+            .ok()??
+    }
+
+    /// 获取该类型下所有位于指定前缀之下的键
+    ///
+    /// 前缀按 `.` 分隔的完整段进行匹配，例如 `.ROOT` 能匹配 `.ROOT.note`，但不能匹配 `.ROOT2.x`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{id, Registry};
+    ///
+    /// const ROOT: &str = id!(ROOT);
+    ///
+    /// Registry::register(ROOT, 0).unwrap();
+    /// Registry::register(id!(@ROOT.note), 1).unwrap();
+    /// Registry::register(id!(@ROOT.note.text), 2).unwrap();
+    /// Registry::register(id!(ROOT2.x), 3).unwrap();
+    ///
+    /// let mut keys = Registry::<i32>::keys_with_prefix(ROOT);
+    /// keys.sort();
+    /// assert_eq!(keys, vec![".ROOT", ".ROOT.note", ".ROOT.note.text"]);
+    ///
+    /// assert_eq!(Registry::<i32>::keys_with_prefix(".ROOT.note.").len(), 1);
+    /// assert_eq!(Registry::<i32>::keys_with_prefix(".RO").len(), 0);
+    /// assert_eq!(Registry::<i32>::keys_with_prefix("").len(), 4);
+    /// ```
+    ///
+    /// 同一类型的键分布在多个分片中，查询结果包含所有分片中的键：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// for i in 0..500u32 {
+    ///     Registry::register(&format!("unit.{}", i), i).unwrap();
+    ///     Registry::register(&format!("item.{}", i), i).unwrap();
+    /// }
+    /// let mut keys = Registry::<u32>::keys_with_prefix("unit");
+    /// keys.sort_by_key(|key| key["unit.".len()..].parse::<u32>().unwrap());
+    /// let expected: Vec<_> = (0..500).map(|i| format!("unit.{}", i)).collect();
+    /// assert_eq!(keys, expected);
+    /// assert_eq!(Registry::<u32>::keys().len(), 1000);
+    /// ```
+    pub fn keys_with_prefix(prefix: &str) -> Vec<String> {
+        Self::_keys_with_prefix(prefix).unwrap_or_default()
+    }
+
+    /// 获取该类型下所有与指定模式匹配的键
+    ///
+    /// 模式按 `.` 分隔为段，其中 `*` 匹配任意一个段，`**` 匹配任意数量（包括零个）的段，其余段需完全相同；
+    /// 如果模式非法，则返回 `PatternError`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{PatternError, Registry};
+    ///
+    /// let cases = [
+    ///     (".app.*.config", ".app.net.config", true),
+    ///     (".app.*.config", ".app.config", false),
+    ///     (".app.*.config", ".app.net.tcp.config", false),
+    ///     (".app.**.config", ".app.config", true),
+    ///     (".app.**.config", ".app.net.tcp.config", true),
+    ///     (".plugins.**", ".plugins", true),
+    ///     (".plugins.**", ".plugins.a.b", true),
+    ///     (".plugins.**", ".plugins2.a", false),
+    ///     ("*", "name", true),
+    ///     ("*", ".name", false),
+    ///     ("*", "a.b", false),
+    ///     ("**", ".a.b.c", true),
+    ///     (".a.b", ".a.b", true),
+    ///     (".a.b", ".a.b.c", false),
+    /// ];
+    /// for (i, (pattern, key, expected)) in cases.iter().enumerate() {
+    ///     Registry::<usize>::register(key, i).unwrap();
+    ///     let matched = Registry::<usize>::keys_matching(pattern).unwrap();
+    ///     assert_eq!(matched.contains(&key.to_string()), *expected, "{} {}", pattern, key);
+    ///     Registry::<usize>::remove(key);
+    /// }
+    ///
+    /// assert_eq!(Registry::<usize>::keys_matching(""), Err(PatternError::Empty));
+    /// assert_eq!(
+    ///     Registry::<usize>::keys_matching(".app.net*"),
+    ///     Err(PatternError::InvalidSegment("net*".to_string()))
+    /// );
+    /// assert_eq!(
+    ///     Registry::<usize>::keys_matching(".app.***"),
+    ///     Err(PatternError::InvalidSegment("***".to_string()))
+    /// );
+    /// ```
+    pub fn keys_matching(pattern: &str) -> Result<Vec<String>, PatternError> {
+        let pattern = Pattern::parse(pattern)?;
+        let mut ret = Self::_keys_where(|name| pattern.matches(name)).unwrap_or_default();
+        order_by_key(&mut ret, String::as_str);
+        Ok(ret)
+    }
+
+    /// 获取该类型下所有与指定正则表达式匹配的键
+    ///
+    /// 仅涉及读锁；需要启用 `regex` 特性
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use regex::Regex;
+    ///
+    /// Registry::register(".user.1001", 1).unwrap();
+    /// Registry::register(".user.1002", 2).unwrap();
+    /// Registry::register(".user.admin", 3).unwrap();
+    ///
+    /// let mut keys = Registry::<i32>::keys_regex(&Regex::new(r"^\.user\.\d+$").unwrap());
+    /// keys.sort();
+    /// assert_eq!(keys, vec![".user.1001", ".user.1002"]);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn keys_regex(re: &regex::Regex) -> Vec<String> {
+        let mut ret = Self::_keys_where(|name| re.is_match(name)).unwrap_or_default();
+        order_by_key(&mut ret, String::as_str);
+        ret
+    }
+
+    fn _clear() -> Option<usize> {
+        let type_id = TypeId::of::<T>();
+        let values = {
+            let type_table = _TABLE.get(type_id).ok()??;
+            let type_map = type_table.writable()?;
+            check_deadlock!(mut T:"";Lock::Type);
+            let mut type_map = type_map.write().ok()?;
+            type_map.take()
+        };
+        collect_empty(|id| *id == type_id);
+        Some(values.len())
+    }
+
+    /// 从注册表中移除该类型下的所有值
+    ///
+    /// 返回被移除的值的数量；被移除的值会在释放锁之后被销毁。该类型对应的表也会被移除（参见 `gc`）
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{registered_types, Registry};
+    ///
+    /// Registry::register("a", 1u8).unwrap();
+    /// Registry::register("b", 2u8).unwrap();
+    /// assert_eq!(Registry::<u8>::clear(), 2);
+    /// assert!(Registry::<u8>::is_empty());
+    /// assert!(registered_types().iter().all(|info| info.type_name != "u8"));
+    /// assert_eq!(Registry::<u8>::clear(), 0);
+    /// ```
+    ///
+    /// 同一类型的键分布在多个分片中，所有分片中的键都会被移除：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// for i in 0..1000u32 {
+    ///     Registry::register(&format!("key.{}", i), i).unwrap();
+    /// }
+    /// assert_eq!(Registry::<u32>::clear(), 1000);
+    /// assert!(Registry::<u32>::keys().is_empty());
+    /// assert!((0..1000).all(|i| !Registry::<u32>::exists(&format!("key.{}", i))));
+    /// ```
+    ///
+    /// 与其他线程的注册操作并发执行：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// struct Item;
+    ///
+    /// let writer = thread::spawn(|| {
+    ///     for i in 0..100 {
+    ///         Registry::register(&format!("item.{}", i), Item).unwrap();
+    ///     }
+    /// });
+    /// let mut cleared = 0;
+    /// while !writer.is_finished() {
+    ///     cleared += Registry::<Item>::clear();
+    /// }
+    /// writer.join().unwrap();
+    /// cleared += Registry::<Item>::clear();
+    /// assert_eq!(cleared, 100);
+    /// assert!(Registry::<Item>::is_empty());
+    /// ```
+    ///
+    /// 在同类型的 `with` 或 `apply` 闭包中调用会导致线程死锁：
+    ///
+    /// ```rust,should_panic
+    /// use gom::Registry;
+    ///
+    /// Registry::register("key", 1u16).unwrap();
+    /// Registry::<u16>::with("key", |_| {
+    ///     Registry::<u16>::clear();
+    /// });
+    /// ```
+    pub fn clear() -> usize {
+        Self::_clear().unwrap_or(0)
+    }
+
+    fn _drain() -> Option<(Vec<(String, T)>, SkippedEntries)> {
+        let type_id = TypeId::of::<T>();
+        let values = {
+            let type_table = _TABLE.get(type_id).ok()??;
+            let type_map = type_table.writable()?;
+            check_deadlock!(mut T:"";Lock::Type);
+            let mut type_map = type_map.write().ok()?;
+            type_map.take()
+        };
+        collect_empty(|id| *id == type_id);
+        let mut skipped = SkippedEntries::default();
+        let mut ret: Vec<_> = values
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.to_string(), skipped.take(&key, value)?)))
+            .collect();
+        order_by_key(&mut ret, |(key, _)| key);
+        Some((ret, skipped))
+    }
+
+    /// 从注册表中移除该类型下的所有值，并返回这些值的所有权
+    ///
+    /// 返回的 `Vec` 中的元素顺序不确定（启用 `ordered` 特性时按键的字典序排列）；无法取回的条目将被跳过，
+    /// 需要知道被跳过的键时使用 `drain_with_skipped`。该类型对应的表也会被移除（参见 `gc`）
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("a", String::from("foo")).unwrap();
+    /// Registry::register("b", String::from("bar")).unwrap();
+    ///
+    /// let mut values = Registry::<String>::drain();
+    /// values.sort();
+    /// assert_eq!(
+    ///     values,
+    ///     vec![
+    ///         ("a".to_string(), "foo".to_string()),
+    ///         ("b".to_string(), "bar".to_string()),
+    ///     ]
+    /// );
+    /// assert!(Registry::<String>::is_empty());
+    /// assert!(gom::registered_types().is_empty());
+    /// ```
+    ///
+    /// 与其他线程的 `apply` 操作并发执行：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("counter", 0u64).unwrap();
+    ///
+    /// let worker = thread::spawn(|| {
+    ///     let mut applied = 0;
+    ///     while Registry::<u64>::apply("counter", |v| *v += 1).is_some() {
+    ///         applied += 1;
+    ///     }
+    ///     applied
+    /// });
+    /// while Registry::<u64>::with("counter", |v| *v).unwrap() < 10 {}
+    ///
+    /// let values = Registry::<u64>::drain();
+    /// let applied = worker.join().unwrap();
+    /// assert_eq!(values, vec![("counter".to_string(), applied)]);
+    /// ```
+    pub fn drain() -> Vec<(String, T)> {
+        Self::drain_with_skipped().0
+    }
+
+    /// 与 `drain` 相同，但同时返回被跳过的条目
+    ///
+    /// 锁已中毒、仍被 `Handle` 共享或值的类型不符的条目无法取回，它们同样被移除，其键按原因记录在 `SkippedEntries` 中；
+    /// 仍被共享的值在最后一个 `Handle` 被销毁时销毁
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("free", 1u16).unwrap();
+    /// Registry::register("held", 2u16).unwrap();
+    /// let handle = Registry::<u16>::handle("held").unwrap();
+    ///
+    /// let (values, skipped) = Registry::<u16>::drain_with_skipped();
+    /// assert_eq!(values, vec![("free".to_string(), 1)]);
+    /// assert_eq!(skipped.shared, vec!["held"]);
+    /// assert_eq!(skipped.len(), 1);
+    /// assert!(!Registry::<u16>::exists("held"));
+    /// assert_eq!(handle.with(|v| *v), Some(2));
+    /// ```
+    pub fn drain_with_skipped() -> (Vec<(String, T)>, SkippedEntries) {
+        Self::_drain().unwrap_or_default()
+    }
+
+    /// 遍历该类型下的所有值，移除所有使谓词返回 `false` 的条目
+    ///
+    /// 返回被移除的条目数量；每次仅锁定一个键对应的值，因而不会在整个遍历期间阻塞其他线程。
+    /// 所有条目都被移除时，该类型对应的表也会被移除（参见 `gc`）
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{registered_types, Registry};
+    ///
+    /// for i in 0..10u32 {
+    ///     Registry::register(&format!("n.{}", i), i).unwrap();
+    /// }
+    /// let removed = Registry::<u32>::retain(|_, v| {
+    ///     *v *= 10;
+    ///     *v % 20 == 0
+    /// });
+    /// assert_eq!(removed, 5);
+    /// assert_eq!(Registry::<u32>::len(), 5);
+    /// assert_eq!(Registry::<u32>::with("n.4", |v| *v), Some(40));
+    /// assert!(!Registry::<u32>::exists("n.5"));
+    ///
+    /// assert_eq!(Registry::<u32>::retain(|_, _| false), 5);
+    /// assert!(registered_types().iter().all(|info| info.type_name != "u32"));
+    /// ```
+    ///
+    /// 谓词中途发生 panic 时，已处理的条目保持其结果，未处理的条目保持不变：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// for i in 0..10i64 {
+    ///     Registry::register(&format!("n.{}", i), i).unwrap();
+    /// }
+    /// let result = thread::spawn(|| {
+    ///     let mut visited = 0;
+    ///     Registry::<i64>::retain(|_, _| {
+    ///         visited += 1;
+    ///         if visited == 5 {
+    ///             panic!("predicate failed");
+    ///         }
+    ///         false
+    ///     })
+    /// })
+    /// .join();
+    /// assert!(result.is_err());
+    ///
+    /// // 4 个条目已被移除，发生 panic 的条目仍然保留在注册表中
+    /// assert_eq!(Registry::<i64>::len(), 6);
+    /// assert_eq!(Registry::<i64>::keys().len(), 6);
+    /// ```
+    ///
+    /// 谓词与移除在该值的同一次写锁下完成；值仍被 `Handle` 共享时，条目同样被移除并计入返回值：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("r1", 1u8).unwrap();
+    /// Registry::register("r2", 2u8).unwrap();
+    /// let handle = Registry::<u8>::handle("r1").unwrap();
+    ///
+    /// assert_eq!(Registry::<u8>::retain(|k, _| k != "r1"), 1);
+    /// assert!(!Registry::<u8>::exists("r1"));
+    /// assert!(handle.is_detached());
+    /// assert_eq!(handle.with(|v| *v), Some(1));
+    /// ```
+    ///
+    /// 在同类型的 `apply` 闭包中调用会导致线程死锁：
+    ///
+    /// ```rust,should_panic
+    /// use gom::Registry;
+    ///
+    /// Registry::register("key", 1u16).unwrap();
+    /// Registry::<u16>::apply("key", |_| {
+    ///     Registry::<u16>::retain(|_, _| true);
+    /// });
+    /// ```
+    pub fn retain<F: FnMut(&str, &mut T) -> bool>(mut f: F) -> usize {
+        check_deadlock!(mut T:"";Lock::Type);
+        let type_id = TypeId::of::<T>();
+        let mut removed = 0;
+        let mut emptied = false;
+        for name in Self::keys() {
+            let Some(slot) = Self::_writable_record(&name) else {
+                continue;
+            };
+            check_deadlock!(mut T:&name;Lock::Key);
+            let Ok(mut value) = slot.write_as::<T>(Wait::Block) else {
+                continue;
+            };
+            // 查找与获取锁之间已被其他线程移除的键将被跳过
+            if !slot.is_attached() {
+                continue;
+            }
+            let frame = ContextOperator::enter(Context::Apply(
+                intern(&name),
+                type_id,
+                std::any::type_name::<T>(),
+            ));
+            let keep = f(&name, &mut value);
+            drop(frame);
+            if keep {
+                value.commit();
+                continue;
+            }
+            // 在仍持有该值的写锁时移除条目，因而谓词与移除之间其他线程无法修改或替换该值
+            let detached = Self::_detach(&name, &slot);
+            drop(value);
+            // 值仍被 `Handle` 共享时条目同样已被移除，被移除的条目在释放锁之后销毁
+            if let Some((old, now_empty)) = detached {
+                drop(old);
+                removed += 1;
+                emptied |= now_empty;
+            }
+        }
+        if emptied {
+            collect_empty(|id| *id == type_id);
+        }
+        removed
+    }
+
+    // 仅当指定键当前对应的条目仍为 `slot` 时将其移出表，并返回该条目以及该类型对应的表是否因此变为空
+    //
+    // 应在释放所有锁之后销毁返回的条目
+    fn _detach(name: &str, slot: &Arc<RecordData>) -> Option<(Record, bool)> {
+        let type_table = _TABLE.get(TypeId::of::<T>()).ok()??;
+        let mut type_map = type_table.write_shard(name).ok()?;
+        if !Arc::ptr_eq(&type_map.get(name)?.data, slot) {
+            return None;
+        }
+        let old = type_map.remove(name)?;
+        let emptied = type_map.is_empty();
+        drop(type_map);
+        Some((old, emptied && type_table.is_empty()))
+    }
+
+    /// 向注册表中该类型下的所有值依次应用一个函数，该函数可以修改注册表中的值
+    ///
+    /// 返回被访问的条目数量；先获取所有键的副本，再依次锁定每个键对应的值，因而其他线程仍可访问未被锁定的键。
+    /// 遍历期间被其他线程移除的键将被跳过
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Position(i32);
+    /// struct Expired;
+    ///
+    /// for i in 0..5 {
+    ///     Registry::register(&format!("entity.{}", i), Position(i)).unwrap();
+    /// }
+    /// Registry::register("entity.3", Expired).unwrap();
+    ///
+    /// let visited = Registry::<Position>::apply_all(|name, p| {
+    ///     p.0 += 10;
+    ///     Registry::<Expired>::remove(name);
+    /// });
+    /// assert_eq!(visited, 5);
+    /// assert!(Registry::<Expired>::is_empty());
+    /// assert_eq!(Registry::<Position>::with("entity.3", |p| p.0), Some(13));
+    /// ```
+    ///
+    /// 与其他线程的 `remove` 操作并发执行：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// for i in 0..1000 {
+    ///     Registry::register(&format!("n.{}", i), 0u32).unwrap();
+    /// }
+    /// let remover = thread::spawn(|| {
+    ///     (0..1000)
+    ///         .filter(|i| Registry::<u32>::remove(&format!("n.{}", i)).is_some())
+    ///         .count()
+    /// });
+    /// let visited = Registry::<u32>::apply_all(|_, v| *v += 1);
+    /// let removed = remover.join().unwrap();
+    /// assert_eq!(removed, 1000);
+    /// assert!(visited <= 1000);
+    /// ```
+    ///
+    /// 在同类型的 `apply_all` 闭包中嵌套调用会导致线程死锁：
+    ///
+    /// ```rust,should_panic
+    /// use gom::Registry;
+    ///
+    /// Registry::register("key", 1u16).unwrap();
+    /// Registry::<u16>::apply_all(|_, _| {
+    ///     Registry::<u16>::apply_all(|_, _| {});
+    /// });
+    /// ```
+    pub fn apply_all<F: FnMut(&str, &mut T)>(mut f: F) -> usize {
+        check_deadlock!(mut T:"";Lock::Type);
+        Self::keys()
+            .into_iter()
+            .filter(|name| Self::apply(name, |v| f(name, v)).is_some())
+            .count()
+    }
+
+    /// 以只读方式遍历该类型下的所有值，并将累加值依次传递给每次调用
+    ///
+    /// 遍历期间被其他线程新注册的键可能会被访问，也可能不会；被其他线程移除的键将被跳过
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// for i in 1..=1000u64 {
+    ///     Registry::register(&format!("n.{}", i), i).unwrap();
+    /// }
+    /// let writer = thread::spawn(|| {
+    ///     for i in 1001..=2000u64 {
+    ///         Registry::register(&format!("n.{}", i), i).unwrap();
+    ///     }
+    /// });
+    /// let (count, sum) = Registry::<u64>::fold((0, 0), |(count, sum), _, v| (count + 1, sum + v));
+    /// writer.join().unwrap();
+    ///
+    /// assert!(count >= 1000 && count <= 2000);
+    /// assert!(sum >= 500500);
+    /// assert_eq!(Registry::<u64>::fold(0, |sum, _, v| sum + v), 2001000);
+    /// ```
+    pub fn fold<A, F: FnMut(A, &str, &T) -> A>(init: A, mut f: F) -> A {
+        check_deadlock!(ref T);
+        let mut acc = Some(init);
+        for name in Self::keys() {
+            Self::with(&name, |v| {
+                if let Some(a) = acc.take() {
+                    acc = Some(f(a, &name, v));
+                }
+            });
+        }
+        acc.expect("accumulator is always restored after each call")
+    }
+
+    /// 以只读方式遍历该类型下的所有值，返回第一个使谓词返回 `true` 的键
+    ///
+    /// 遍历顺序不确定（启用 `ordered` 特性时按键的字典序遍历）；每次仅锁定一个键对应的值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Player {
+    ///     team: &'static str,
+    /// }
+    ///
+    /// Registry::register("p.1", Player { team: "red" }).unwrap();
+    /// Registry::register("p.2", Player { team: "blue" }).unwrap();
+    /// Registry::register("p.3", Player { team: "blue" }).unwrap();
+    ///
+    /// assert_eq!(Registry::<Player>::find(|_, p| p.team == "red"), Some("p.1".to_string()));
+    /// assert_eq!(Registry::<Player>::find(|_, p| p.team == "green"), None);
+    ///
+    /// let blue = Registry::<Player>::find(|_, p| p.team == "blue").unwrap();
+    /// assert!(blue == "p.2" || blue == "p.3");
+    ///
+    /// // 谓词中可以访问其他类型的值
+    /// Registry::register("p.3", 100u32).unwrap();
+    /// let rich = Registry::<Player>::find(|name, _| {
+    ///     Registry::<u32>::with(name, |score| *score > 50).unwrap_or(false)
+    /// });
+    /// assert_eq!(rich, Some("p.3".to_string()));
+    /// ```
+    pub fn find<F: FnMut(&str, &T) -> bool>(mut f: F) -> Option<String> {
+        Self::find_map(|name, v| f(name, v).then(|| String::from(name)))
+    }
+
+    /// 以只读方式遍历该类型下的所有值，返回第一个使闭包函数返回 `Some` 的结果
+    ///
+    /// 遍历顺序不确定（启用 `ordered` 特性时按键的字典序遍历）；每次仅锁定一个键对应的值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("a", vec![1, 2]).unwrap();
+    /// Registry::register("b", vec![3, 4, 5]).unwrap();
+    ///
+    /// let found = Registry::<Vec<i32>>::find_map(|name, v| (v.len() == 3).then(|| (name.to_string(), v[0])));
+    /// assert_eq!(found, Some(("b".to_string(), 3)));
+    /// assert_eq!(Registry::<Vec<i32>>::find_map(|_, v| v.first().filter(|x| **x > 10).copied()), None);
+    /// ```
+    pub fn find_map<R, F: FnMut(&str, &T) -> Option<R>>(mut f: F) -> Option<R> {
+        check_deadlock!(ref T);
+        Self::keys()
+            .into_iter()
+            .find_map(|name| Self::with(&name, |v| f(&name, v)).flatten())
+    }
+
+    /// 向注册表中的指定键应用一个函数，如果键不存在，则先使用 `init` 的返回值注册该键
+    ///
+    /// 检查与注册是原子的：多个线程同时访问同一个不存在的键时，`init` 只会被调用一次。
+    /// `init` 在该键所在分片的写锁下执行，与键不存在时 `entry` 的闭包函数相同，在其中写入同一类型的值会导致线程死锁（调试模式下会发生 panic）
+    ///
+    /// 如果锁已中毒，或者键不存在且位于已被封存的前缀之下（此时不会调用 `init`），则返回 `None`；否则，返回闭包函数的返回值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// fn note(text: &str) -> String {
+    ///     Registry::<String>::get_or_register_with("note", String::new, |t| {
+    ///         std::mem::replace(t, text.to_string())
+    ///     })
+    ///     .unwrap()
+    /// }
+    ///
+    /// assert_eq!(note("Hello"), "");
+    /// assert_eq!(note("World"), "Hello");
+    /// ```
+    ///
+    /// 多个线程竞争同一个不存在的键：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::thread;
+    ///
+    /// static INIT_COUNT: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// let workers: Vec<_> = (0..8)
+    ///     .map(|_| {
+    ///         thread::spawn(|| {
+    ///             for _ in 0..100 {
+    ///                 Registry::<usize>::get_or_register_with(
+    ///                     "counter",
+    ///                     || {
+    ///                         INIT_COUNT.fetch_add(1, Ordering::SeqCst);
+    ///                         0
+    ///                     },
+    ///                     |v| *v += 1,
+    ///                 );
+    ///             }
+    ///         })
+    ///     })
+    ///     .collect();
+    /// for worker in workers {
+    ///     worker.join().unwrap();
+    /// }
+    /// assert_eq!(INIT_COUNT.load(Ordering::SeqCst), 1);
+    /// assert_eq!(Registry::<usize>::with("counter", |v| *v), Some(800));
+    /// ```
+    pub fn get_or_register_with<R, I, F>(name: &str, init: I, func: F) -> Option<R>
+    where
+        I: FnOnce() -> T,
+        F: FnOnce(&mut T) -> R,
+    {
+        let type_id = TypeId::of::<T>();
+        let slot = match Self::_record(name) {
+            Some((_, true)) => return None,
+            Some((slot, false)) => slot,
+            None => {
+                let sealed = is_sealed(name);
+                let type_table = Self::_ensure_type(name)?;
+                check_deadlock!(mut T:name;Lock::TypeKey);
+                let Some(type_map) = type_table.writable()?.write_shard_attached(name) else {
+                    return Self::get_or_register_with(name, init, func);
+                };
+                let mut type_map = type_map.ok()?;
+                let slot = match type_map.entry(intern(name)) {
+                    hash_map::Entry::Occupied(entry) => entry.into_mut(),
+                    hash_map::Entry::Vacant(_) if sealed => return None,
+                    hash_map::Entry::Vacant(entry) => {
+                        // 与 `entry` 相同地记录所持有的分片的写锁，从而检查 `init` 中的嵌套访问
+                        let frame = ContextOperator::enter(Context::Type(
+                            type_id,
+                            std::any::type_name::<T>(),
+                        ));
+                        let value = init();
+                        drop(frame);
+                        let slot = entry.insert(Record::new(Box::new(value)));
+                        notify_registered();
+                        slot
+                    }
+                };
+                Arc::clone(&slot.data)
+            }
+        };
+        check_deadlock!(mut T:name;Lock::Key);
+        let mut value = slot.write_as::<T>(Wait::Block).ok()?;
+        let frame = ContextOperator::enter(Context::Apply(
+            intern(name),
+            type_id,
+            std::any::type_name::<T>(),
+        ));
+        let ret = func(&mut value);
+        drop(frame);
+        value.commit();
+        Some(ret)
+    }
+
+    fn _remove_prefix(prefix: &str) -> Option<Vec<(String, T)>> {
+        let type_id = TypeId::of::<T>();
+        let values: Vec<_> = {
+            let type_table = _TABLE.get(type_id).ok()??;
+            let type_map = type_table.writable()?;
+            check_deadlock!(mut T:prefix;Lock::Type);
+            let mut type_map = type_map.write().ok()?;
+            let names: Vec<_> = type_map
+                .keys()
+                .filter(|name| has_prefix(name, prefix))
+                .cloned()
+                .collect();
+            names
+                .into_iter()
+                .filter_map(|name| {
+                    let value = type_map.remove(&name)?;
+                    Some((name, value))
+                })
+                .collect()
+        };
+        let mut ret: Vec<_> = values
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let value = value.into_inner()?.ok()?;
+                let type_value = value.downcast::<T>().ok()?;
+                Some((name.to_string(), *type_value))
+            })
+            .collect();
+        order_by_key(&mut ret, |(name, _)| name);
+        Some(ret)
+    }
+
+    /// 从注册表中移除该类型下所有位于指定前缀之下的值，并返回这些值的所有权
+    ///
+    /// 前缀的匹配规则与 `keys_with_prefix` 相同；所有匹配的键在同一个写锁下被移除，锁已中毒的条目将被移除但不会返回
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register(".app.a", 1).unwrap();
+    /// Registry::register(".app.b", 2).unwrap();
+    /// Registry::register(".application", 3).unwrap();
+    ///
+    /// let mut removed = Registry::<i32>::remove_prefix(".app");
+    /// removed.sort();
+    /// assert_eq!(removed, vec![(".app.a".to_string(), 1), (".app.b".to_string(), 2)]);
+    /// assert_eq!(Registry::<i32>::keys(), vec![".application"]);
+    /// ```
+    ///
+    /// 与其他线程对同一子树的修改并发执行：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// for i in 0..100 {
+    ///     Registry::register(&format!(".sub.old.{}", i), i).unwrap();
+    /// }
+    /// let writer = thread::spawn(|| {
+    ///     for i in 0..100 {
+    ///         Registry::<i32>::apply(&format!(".sub.old.{}", i), |v| *v += 1000);
+    ///         Registry::register(&format!(".sub.new.{}", i), i).unwrap();
+    ///     }
+    /// });
+    /// let removed = Registry::<i32>::remove_prefix(".sub");
+    /// writer.join().unwrap();
+    ///
+    /// assert!(removed.len() >= 100);
+    /// for i in 0..100 {
+    ///     assert!(!Registry::<i32>::exists(&format!(".sub.old.{}", i)));
+    /// }
+    /// ```
+    pub fn remove_prefix(prefix: &str) -> Vec<(String, T)> {
+        Self::_remove_prefix(prefix).unwrap_or_default()
+    }
+
+    /// 从注册表中移除该类型下的所有值，并以 `HashMap` 的形式返回这些值
+    ///
+    /// 之后 `exists` 将返回 `false`；无法取回的条目将被跳过，需要知道被跳过的键时使用 `take_map_with_skipped`。
+    /// 该类型对应的表也会被移除（参见 `gc`）
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("a", 1u8).unwrap();
+    /// Registry::register("b", 2u8).unwrap();
+    ///
+    /// let map = Registry::<u8>::take_map();
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map["a"], 1);
+    /// assert!(!Registry::<u8>::exists("a"));
+    ///
+    /// Registry::register("c", 3u8).unwrap();
+    /// assert_eq!(Registry::<u8>::keys(), vec!["c"]);
+    /// ```
+    ///
+    /// 与其他线程的注册操作并发执行时，每个新注册的值要么出现在返回的 `HashMap` 中，要么仍然保留在注册表中：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// let writer = thread::spawn(|| {
+    ///     for i in 0..1000 {
+    ///         Registry::register(&format!("n.{}", i), i).unwrap();
+    ///     }
+    /// });
+    /// let mut taken = 0;
+    /// while !writer.is_finished() {
+    ///     taken += Registry::<i32>::take_map().len();
+    /// }
+    /// writer.join().unwrap();
+    /// taken += Registry::<i32>::take_map().len();
+    /// assert_eq!(taken, 1000);
+    /// ```
+    pub fn take_map() -> HashMap<String, T> {
+        Self::take_map_with_skipped().0
+    }
+
+    /// 与 `take_map` 相同，但同时返回被跳过的条目
+    ///
+    /// 锁已中毒、仍被 `Handle` 共享或值的类型不符的条目无法取回，它们同样被移除，其键按原因记录在 `SkippedEntries` 中
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("ok", 1u8).unwrap();
+    /// Registry::register("bad", 2u8).unwrap();
+    /// Registry::register("held", 3u8).unwrap();
+    /// let handle = Registry::<u8>::handle("held").unwrap();
+    /// let _ = thread::spawn(|| {
+    ///     Registry::<u8>::apply("bad", |_| panic!());
+    /// })
+    /// .join();
+    ///
+    /// let (map, skipped) = Registry::<u8>::take_map_with_skipped();
+    /// assert_eq!(skipped.shared, vec!["held"]);
+    /// assert_eq!(handle.with(|v| *v), Some(3));
+    /// // 启用 `parking_lot` 特性时锁不会中毒
+    /// if cfg!(feature = "parking_lot") {
+    ///     assert_eq!(map.len(), 2);
+    ///     assert!(skipped.poisoned.is_empty());
+    /// } else {
+    ///     assert_eq!(map.into_iter().collect::<Vec<_>>(), vec![("ok".to_string(), 1)]);
+    ///     assert_eq!(skipped.poisoned, vec!["bad"]);
+    /// }
+    /// ```
+    pub fn take_map_with_skipped() -> (HashMap<String, T>, SkippedEntries) {
+        let type_id = TypeId::of::<T>();
+        let type_map = {
+            let Ok(Some(type_table)) = _TABLE.get(type_id) else {
+                return Default::default();
+            };
+            let Some(type_table) = type_table.writable() else {
+                return Default::default();
+            };
+            // 取出的条目需要等待对该类型下的值的访问全部结束
+            check_deadlock!(mut T:"";Lock::Type);
+            let mut type_map = type_table.write().unwrap_or_else(|e| e.into_inner());
+            type_map.take()
+        };
+        collect_empty(|id| *id == type_id);
+        let mut values = HashMap::with_capacity(type_map.len());
+        let mut skipped = SkippedEntries::default();
+        for (name, value) in type_map {
+            if let Some(value) = skipped.take(&name, value) {
+                values.insert(name.to_string(), value);
+            }
+        }
+        (values, skipped)
+    }
+
+    /// 获取指定键的条目，并将其传递给闭包函数
+    ///
+    /// 与 `HashMap::entry` 类似；键已存在时，闭包函数执行期间持有该值的写锁，而不持有该类型对应的表的锁；
+    /// 键不存在时，闭包函数持有该键所在分片的写锁直至插入新值（不持有注册表的锁，因而其他线程仍可注册新的类型），之后同样仅持有新值的写锁。因而条目上的所有操作都是原子的。
+    /// 如果锁已中毒，或者键不存在且位于已被封存的前缀之下，则不会调用闭包函数并返回 `None`；否则，返回闭包函数的返回值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// for _ in 0..3 {
+    ///     Registry::<u32>::entry("hits", |e| *e.and_modify(|v| *v += 1).or_insert(1));
+    /// }
+    /// assert_eq!(Registry::<u32>::with("hits", |v| *v), Some(3));
+    ///
+    /// let v = Registry::<Vec<i32>>::entry("list", |e| {
+    ///     let mut list = e.or_insert_with(Vec::new);
+    ///     list.push(1);
+    ///     list.clone()
+    /// });
+    /// assert_eq!(v, Some(vec![1]));
+    /// assert_eq!(Registry::<Vec<i32>>::entry("list", |e| e.or_default().len()), Some(1));
+    /// ```
+    ///
+    /// 仅当插入了新值、`and_modify` 修改了该值或通过可变引用访问了该值时才记录修改；该值的锁已中毒时返回 `None`：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("count", 1u32).unwrap();
+    /// let version = Registry::<u32>::version("count");
+    /// assert_eq!(Registry::<u32>::entry("count", |e| *e.or_insert(0)), Some(1));
+    /// assert_eq!(Registry::<u32>::version("count"), version);
+    ///
+    /// Registry::<u32>::entry("count", |e| e.and_modify(|v| *v += 1).key().len());
+    /// assert_ne!(Registry::<u32>::version("count"), version);
+    ///
+    /// // 使该值的锁中毒；启用 `parking_lot` 特性时锁不会中毒
+    /// let _ = thread::spawn(|| Registry::<u32>::apply("count", |_| panic!())).join();
+    /// #[cfg(not(feature = "parking_lot"))]
+    /// assert_eq!(Registry::<u32>::entry("count", |e| *e.or_insert(0)), None);
+    /// ```
+    ///
+    /// 条目及其提供的引用无法离开闭包：
+    ///
+    /// ```rust,compile_fail
+    /// use gom::Registry;
+    ///
+    /// let v = Registry::<u32>::entry("hits", |e| e.or_insert(1));
+    /// ```
+    ///
+    /// 在同一个键的 `apply` 闭包中调用会导致线程死锁：
+    ///
+    /// ```rust,should_panic
+    /// use gom::Registry;
+    ///
+    /// Registry::register("key", 1u16).unwrap();
+    /// Registry::<u16>::apply("key", |_| {
+    ///     Registry::<u16>::entry("key", |e| *e.or_insert(0) += 1);
+    /// });
+    /// ```
+    pub fn entry<R, F: FnOnce(Entry<'_, T>) -> R>(name: &str, func: F) -> Option<R> {
+        let type_id = TypeId::of::<T>();
+        let type_name = std::any::type_name::<T>();
+        let slot = OnceCell::new();
+        // 键已存在时，在释放该类型对应的表的锁之后再获取其值的锁，与 `with`、`apply` 的加锁顺序一致
+        if let Some((data, frozen)) = Self::_record(name) {
+            if frozen {
+                return None;
+            }
+            check_deadlock!(mut T:name;Lock::Key);
+            let data = slot.get_or_init(|| data);
+            let value = data.write_as::<T>(Wait::Block).ok()?;
+            // 查找与获取锁之间该键已被移除或替换时重新查找
+            if !data.is_attached() {
+                drop(value);
+                return Self::entry(name, func);
+            }
+            let key = intern(name);
+            let frame = ContextOperator::enter(Context::Apply(key.clone(), type_id, type_name));
+            let ret = func(Entry::occupied(key, value));
+            drop(frame);
+            return Some(ret);
+        }
+        let sealed = is_sealed(name);
+        let type_table = Self::_ensure_type(name)?;
+        check_deadlock!(mut T:name;Lock::TypeKey);
+        let Some(type_map) = type_table.writable()?.write_shard_attached(name) else {
+            return Self::entry(name, func);
+        };
+        let type_map = type_map.ok()?;
+        if type_map.contains_key(name) {
+            drop(type_map);
+            return Self::entry(name, func);
+        }
+        // 条目的 `or_insert` 等函数无法失败，因而在键无法被注册时不提供条目
+        if sealed {
+            return None;
+        }
+        let key = intern(name);
+        let frame = ContextOperator::enter(Context::Apply(key.clone(), type_id, type_name));
+        let context = Context::Type(type_id, type_name);
+        ContextOperator::push(context.clone());
+        let ret = func(Entry::vacant(key, type_map, &slot, context));
+        drop(frame);
+        Some(ret)
+    }
+
+    /// 向注册表中的指定键应用一个函数，如果键不存在，则先注册 `T::default()`
+    ///
+    /// 与 `get_or_register_with(name, T::default, func)` 相同
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// assert_eq!(Registry::<Vec<i32>>::apply_or_default("buffer", |v| { v.push(1); v.len() }), Some(1));
+    /// assert_eq!(Registry::<Vec<i32>>::apply_or_default("buffer", |v| { v.push(2); v.len() }), Some(2));
+    /// ```
+    pub fn apply_or_default<R, F: FnOnce(&mut T) -> R>(name: &str, func: F) -> Option<R>
+    where
+        T: Default,
+    {
+        Self::get_or_register_with(name, T::default, func)
+    }
+
+    fn _modified(name: &str) -> Option<(Instant, u64)> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                let type_map = type_table.read_shard(name).ok()?;
+                Some(type_map.get(name)?.modified())
+            })
+            .ok()??
+    }
+
+    /// 获取指定键对应的值最后一次被修改的时间
+    ///
+    /// `register`、`replace`、`set` 以及每次 `apply` 等可修改值的操作结束时都会更新该时间，`with` 等只读操作不会更新该时间；
+    /// 获取该时间时不会锁定键对应的值。如果键不存在，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("my_key", 42).unwrap();
+    /// let registered = Registry::<i32>::last_modified("my_key").unwrap();
+    /// let tick = Registry::<i32>::modified_tick("my_key").unwrap();
+    ///
+    /// Registry::<i32>::with("my_key", |v| *v);
+    /// assert_eq!(Registry::<i32>::last_modified("my_key"), Some(registered));
+    /// assert_eq!(Registry::<i32>::modified_tick("my_key"), Some(tick));
+    ///
+    /// Registry::<i32>::apply("my_key", |v| *v += 1);
+    /// let applied = Registry::<i32>::last_modified("my_key").unwrap();
+    /// assert!(applied >= registered);
+    /// assert!(Registry::<i32>::modified_tick("my_key").unwrap() > tick);
+    ///
+    /// // 即使新值与旧值相等，`replace` 也会更新该时间
+    /// let tick = Registry::<i32>::modified_tick("my_key").unwrap();
+    /// Registry::<i32>::replace("my_key", 43);
+    /// assert!(Registry::<i32>::last_modified("my_key").unwrap() >= applied);
+    /// assert!(Registry::<i32>::modified_tick("my_key").unwrap() > tick);
+    ///
+    /// assert_eq!(Registry::<i32>::last_modified("other_key"), None);
+    /// ```
+    pub fn last_modified(name: &str) -> Option<Instant> {
+        Self::_modified(name).map(|(instant, _)| instant)
+    }
+
+    /// 获取指定键对应的值最后一次被修改时的全局修改计数
+    ///
+    /// 修改计数在整个注册表范围内单调递增，因而可以用于比较不同键的修改先后顺序。如果键不存在，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("a", 1).unwrap();
+    /// Registry::register("b", 2).unwrap();
+    /// assert!(Registry::<i32>::modified_tick("a") < Registry::<i32>::modified_tick("b"));
+    ///
+    /// Registry::<i32>::apply("a", |v| *v += 1);
+    /// assert!(Registry::<i32>::modified_tick("a") > Registry::<i32>::modified_tick("b"));
+    /// ```
+    pub fn modified_tick(name: &str) -> Option<u64> {
+        Self::_modified(name).map(|(_, tick)| tick)
+    }
+
+    /// 判断指定键对应的值在给定时间之后是否被修改过
+    ///
+    /// 如果键不存在，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::time::Instant;
+    ///
+    /// Registry::register("my_key", 42).unwrap();
+    /// let since = Instant::now();
+    /// assert_eq!(Registry::<i32>::modified_since("my_key", since), Some(false));
+    ///
+    /// Registry::<i32>::apply("my_key", |v| *v += 1);
+    /// assert_eq!(Registry::<i32>::modified_since("my_key", since), Some(true));
+    /// ```
+    pub fn modified_since(name: &str, since: Instant) -> Option<bool> {
+        Self::last_modified(name).map(|instant| instant > since)
+    }
+
+    /// 获取指定键对应的值的版本号
+    ///
+    /// 版本号在键首次注册时为 0，此后每次成功的修改（`apply`、`replace`、`set`、对已存在的键再次注册等）都会使其加 1；
+    /// 版本号达到 `u64::MAX` 后将回绕为 0。如果键不存在，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("my_key", 42).unwrap();
+    /// assert_eq!(Registry::<i32>::version("my_key"), Some(0));
+    ///
+    /// Registry::<i32>::apply("my_key", |v| *v += 1);
+    /// assert_eq!(Registry::<i32>::version("my_key"), Some(1));
+    ///
+    /// Registry::<i32>::replace("my_key", 64);
+    /// Registry::<i32>::set("my_key", 65);
+    /// Registry::register("my_key", 66).unwrap();
+    /// assert_eq!(Registry::<i32>::version("my_key"), Some(4));
+    ///
+    /// // 只读操作不会改变版本号
+    /// Registry::<i32>::with("my_key", |v| *v);
+    /// assert_eq!(Registry::<i32>::version("my_key"), Some(4));
+    ///
+    /// assert_eq!(Registry::<i32>::version("other_key"), None);
+    /// ```
+    pub fn version(name: &str) -> Option<u64> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                let type_map = type_table.read_shard(name).ok()?;
+                Some(type_map.get(name)?.version())
+            })
+            .ok()??
+    }
+
+    /// 向注册表中的指定键应用一个只读函数，并同时传入该值的版本号
+    ///
+    /// 版本号在持有读锁时读取，因而与传入的值一致。版本号的含义参见 [`Registry::version`]。
+    /// 如果键不存在，则返回 `None`；否则，返回闭包函数的返回值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("counter", 0u64).unwrap();
+    ///
+    /// let writer = thread::spawn(|| {
+    ///     for _ in 0..100 {
+    ///         Registry::<u64>::apply("counter", |v| *v += 1);
+    ///     }
+    /// });
+    /// let reader = thread::spawn(|| {
+    ///     let mut last = None;
+    ///     for _ in 0..100 {
+    ///         let (value, version) = Registry::<u64>::with_versioned("counter", |v, ver| (*v, ver)).unwrap();
+    ///         // 每次 apply 都使值与版本号各加 1
+    ///         assert_eq!(value, version);
+    ///         if let Some(last) = last {
+    ///             assert!(version >= last);
+    ///         }
+    ///         last = Some(version);
+    ///     }
+    /// });
+    /// writer.join().unwrap();
+    /// reader.join().unwrap();
+    ///
+    /// // 写线程的每次修改都严格递增版本号
+    /// let mut versions = Vec::new();
+    /// for _ in 0..10 {
+    ///     Registry::<u64>::apply("counter", |v| *v += 1);
+    ///     versions.push(Registry::<u64>::with_versioned("counter", |_, ver| ver).unwrap());
+    /// }
+    /// assert!(versions.windows(2).all(|w| w[0] < w[1]));
+    /// assert_eq!(Registry::<u64>::with_versioned("other_key", |_, ver| ver), None);
+    /// ```
+    pub fn with_versioned<R, F: FnOnce(&T, u64) -> R>(name: &str, func: F) -> Option<R> {
+        let type_id = TypeId::of::<T>();
+        let (slot, _) = Self::_record(name)?;
+        check_deadlock!(ref T:name);
+        let value = slot.read_as::<T>(Wait::Block).ok()?;
+        let version = slot.version();
+        let frame = ContextOperator::enter(Context::With(
+            intern(name),
+            type_id,
+            std::any::type_name::<T>(),
+        ));
+        let ret = Some(func(&value, version));
+        drop(frame);
+        ret
+    }
+
+    fn _handle(name: &str) -> Option<Handle<T>> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                let type_map = type_table.read_shard(name).ok()?;
+                let slot = type_map.get(name)?;
+                Some(Handle::new(
+                    name,
+                    Arc::clone(&slot.data),
+                    Arc::clone(&type_table.frozen),
+                ))
+            })
+            .ok()??
+    }
+
+    /// 获取指定键对应的值的读锁守卫
+    ///
+    /// 守卫通过解引用访问该值，并且仅持有该值自身的读锁，不持有注册表及该类型对应的表的锁，因而同一类型的其他键在守卫存续期间仍可正常访问。
+    /// 守卫存续期间，当前线程视同处于对该键的 `with` 闭包中，在调试模式下会导致死锁的嵌套写操作同样会引发 panic
+    ///
+    /// 如果键不存在或锁已中毒，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("a", vec![1, 2, 3]).unwrap();
+    /// Registry::register("b", vec![4, 5, 6]).unwrap();
+    ///
+    /// let a = Registry::<Vec<i32>>::read_guard("a").unwrap();
+    /// assert_eq!(*a, vec![1, 2, 3]);
+    ///
+    /// // 同一类型的其他键仍可正常读写
+    /// Registry::<Vec<i32>>::apply("b", |v| v.push(7)).unwrap();
+    /// assert_eq!(Registry::<Vec<i32>>::with("b", |v| v.len()), Some(4));
+    /// let b = Registry::<Vec<i32>>::read_guard("b").unwrap();
+    /// assert_eq!(a.len() + b.len(), 7);
+    ///
+    /// // 同一键可以同时被多次读取
+    /// assert_eq!(Registry::<Vec<i32>>::with("a", |v| v[0]), Some(1));
+    /// drop(a);
+    /// drop(b);
+    ///
+    /// Registry::<Vec<i32>>::apply("a", |v| v.clear()).unwrap();
+    /// assert!(Registry::<Vec<i32>>::read_guard("a").unwrap().is_empty());
+    /// assert!(Registry::<Vec<i32>>::read_guard("c").is_none());
+    /// ```
+    ///
+    /// 在调试模式下，守卫存续期间修改同一个键会引发 panic：
+    ///
+    /// ```rust,should_panic
+    /// use gom::Registry;
+    ///
+    /// Registry::register("a", 42).unwrap();
+    /// let guard = Registry::<i32>::read_guard("a").unwrap();
+    /// # if !cfg!(any(debug_assertions, feature = "deadlock-detection")) { panic!() }
+    /// Registry::<i32>::apply("a", |v| *v += 1);
+    /// # drop(guard);
+    /// ```
+    pub fn read_guard(name: &str) -> Option<ReadGuard<T>> {
+        let (slot, _) = Self::_record(name)?;
+        check_deadlock!(ref T:name);
+        ReadGuard::new(name, slot)
+    }
+
+    /// 获取指定键对应的值的写锁守卫
+    ///
+    /// 守卫通过解引用修改该值，并且仅持有该值自身的写锁，不持有注册表及该类型对应的表的锁，因而同一类型的其他键在守卫存续期间仍可正常访问。
+    /// 守卫存续期间，当前线程视同处于对该键的 `apply` 闭包中；在调试模式下，如果当前线程已持有该键的守卫或正处于访问该键的闭包中，则会引发 panic。
+    /// 守卫被销毁时（包括 panic 导致的栈展开）会记录一次修改
+    ///
+    /// 如果键不存在、该类型已被冻结或锁已中毒，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("a", vec![1, 2, 3]).unwrap();
+    /// Registry::register("b", vec![4, 5, 6]).unwrap();
+    ///
+    /// let mut a = Registry::<Vec<i32>>::write_guard("a").unwrap();
+    /// a.push(4);
+    ///
+    /// // 同一类型的其他键仍可正常访问
+    /// assert_eq!(Registry::<Vec<i32>>::with("b", |v| v.len()), Some(3));
+    /// let mut b = Registry::<Vec<i32>>::write_guard("b").unwrap();
+    /// b.push(a.len() as i32);
+    /// drop(a);
+    /// drop(b);
+    ///
+    /// assert_eq!(Registry::<Vec<i32>>::with("a", |v| v.clone()), Some(vec![1, 2, 3, 4]));
+    /// assert_eq!(Registry::<Vec<i32>>::with("b", |v| v.clone()), Some(vec![4, 5, 6, 4]));
+    /// assert!(Registry::<Vec<i32>>::write_guard("c").is_none());
+    ///
+    /// // 守卫在栈展开时同样会被销毁并移除对应的上下文，该键的锁因此中毒，但之后仍可替换该键
+    /// let result = std::panic::catch_unwind(|| {
+    ///     let mut a = Registry::<Vec<i32>>::write_guard("a").unwrap();
+    ///     a.push(5);
+    ///     panic!("test failed");
+    /// });
+    /// assert!(result.is_err());
+    /// // 启用 `parking_lot` 特性时锁不会中毒
+    /// #[cfg(not(feature = "parking_lot"))]
+    /// {
+    ///     assert!(Registry::<Vec<i32>>::write_guard("a").is_none());
+    ///     Registry::register("a", vec![0]).unwrap();
+    ///     assert_eq!(Registry::<Vec<i32>>::write_guard("a").map(|a| a.len()), Some(1));
+    /// }
+    /// ```
+    ///
+    /// 在调试模式下，已持有同一个键的守卫时再次获取写锁守卫会引发 panic：
+    ///
+    /// ```rust,should_panic
+    /// use gom::Registry;
+    ///
+    /// Registry::register("a", 42).unwrap();
+    /// let guard = Registry::<i32>::read_guard("a").unwrap();
+    /// # if !cfg!(any(debug_assertions, feature = "deadlock-detection")) { panic!() }
+    /// let _ = Registry::<i32>::write_guard("a");
+    /// # drop(guard);
+    /// ```
+    pub fn write_guard(name: &str) -> Option<WriteGuard<T>> {
+        let slot = Self::_writable_record(name)?;
+        check_deadlock!(mut T:name;Lock::Key);
+        WriteGuard::new(name, slot)
+    }
+
+    /// 获取指定键对应的条目的编号
+    ///
+    /// 编号在该类型下以数组下标的形式直接定位条目，通过 `with_slot` 与 `apply_slot` 访问时无需对键求哈希值，也无需获取该类型对应的表的锁。
+    /// 编号指向条目而非键：条目被移除或被替换（包括再次注册同一个键）后编号失效，此后通过它访问将返回 `None`，
+    /// 即使其下标已被分配给其他条目；条目被移动到其他键下时编号仍然有效
+    ///
+    /// 首次为某个条目分配编号时需要获取该类型对应的表的写锁；如果键不存在或锁已中毒，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Position(f32, f32);
+    ///
+    /// Registry::register("player", Position(0.0, 0.0)).unwrap();
+    /// let player = Registry::<Position>::slot_of("player").unwrap();
+    /// assert_eq!(Registry::<Position>::slot_of("player"), Some(player));
+    ///
+    /// for _ in 0..60 {
+    ///     Registry::apply_slot(player, |p: &mut Position| p.0 += 0.5);
+    /// }
+    /// assert_eq!(Registry::with_slot(player, |p: &Position| p.0), Some(30.0));
+    ///
+    /// // 移除并重新注册后，旧的编号失效
+    /// Registry::<Position>::remove("player");
+    /// assert_eq!(Registry::with_slot(player, |p: &Position| p.0), None);
+    /// Registry::register("player", Position(1.0, 1.0)).unwrap();
+    /// assert_eq!(Registry::with_slot(player, |p: &Position| p.0), None);
+    ///
+    /// let player = Registry::<Position>::slot_of("player").unwrap();
+    /// assert_eq!(Registry::with_slot(player, |p: &Position| p.1), Some(1.0));
+    /// Registry::<Position>::register("player", Position(2.0, 2.0)).unwrap();
+    /// assert_eq!(Registry::with_slot(player, |p: &Position| p.1), None);
+    /// ```
+    pub fn slot_of(name: &str) -> Option<Slot<T>> {
+        let type_id = TypeId::of::<T>();
+        let type_table = _TABLE.get(type_id).ok()??;
+        {
+            let type_map = type_table.read_shard(name).ok()?;
+            let record = type_map.get(name)?;
+            if let Some((_, index)) = record.arena {
+                return Some(Slot::new(index, record.registered));
+            }
+        }
+        check_deadlock!(mut T:name;Lock::TypeKey);
+        let mut type_map = type_table.write_shard(name).ok()?;
+        let record = type_map.get_mut(name)?;
+        let index = match record.arena {
+            Some((_, index)) => index,
+            None => {
+                let mut arena = type_table.arena.write().ok()?;
+                let index = arena.alloc(intern(name), Arc::clone(&record.data));
+                record.arena = Some((Arc::downgrade(&type_table.arena), index));
+                index
+            }
+        };
+        Some(Slot::new(index, record.registered))
+    }
+
+    // 查找编号对应的键与条目，并判断该类型是否已被冻结
+    fn _resolve(slot: Slot<T>) -> Option<(Arc<str>, Arc<RecordData>, bool)> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                let arena = type_table.arena.read().ok()?;
+                let (name, data) = arena.entries.get(slot.index())?.as_ref()?;
+                if data.registered != slot.generation() {
+                    return None;
+                }
+                Some((name.clone(), Arc::clone(data), type_table.is_frozen()))
+            })
+            .ok()??
+    }
+
+    /// 通过编号向对应的值应用一个函数，该函数仅能读取该值
+    ///
+    /// 如果编号已失效或锁已中毒，则返回 `None`；否则，返回闭包函数的返回值
+    pub fn with_slot<R, F: FnOnce(&T) -> R>(slot: Slot<T>, func: F) -> Option<R> {
+        let type_id = TypeId::of::<T>();
+        let (name, data, _) = Self::_resolve(slot)?;
+        check_deadlock!(ref T:&name);
+        let value = data.read_as::<T>(Wait::Block).ok()?;
+        let frame =
+            ContextOperator::enter(Context::With(name, type_id, std::any::type_name::<T>()));
+        let ret = Some(func(&value));
+        drop(frame);
+        ret
+    }
+
+    /// 通过编号向对应的值应用一个函数，该函数可以修改该值
+    ///
+    /// 如果编号已失效、该类型已被冻结或锁已中毒，则返回 `None`；否则，返回闭包函数的返回值
+    pub fn apply_slot<R, F: FnOnce(&mut T) -> R>(slot: Slot<T>, func: F) -> Option<R> {
+        let type_id = TypeId::of::<T>();
+        let (name, data, frozen) = Self::_resolve(slot)?;
+        if frozen {
+            return None;
+        }
+        check_deadlock!(mut T:&name;Lock::Key);
+        let mut value = data.write_as::<T>(Wait::Block).ok()?;
+        let frame =
+            ContextOperator::enter(Context::Apply(name, type_id, std::any::type_name::<T>()));
+        let ret = Some(func(&mut value));
+        drop(frame);
+        value.commit();
+        ret
+    }
+
+    /// 获取指定键的热键，用于频繁访问同一个键
+    ///
+    /// 热键缓存该键当前对应的条目，缓存仍然有效时访问该值无需获取注册表及该类型对应的表的锁，也无需查找类型与键；
+    /// 该类型对应的表发生任何修改（插入、移除、替换等）后缓存失效，下一次访问时重新查找该键。
+    /// 缓存不会使被移除的值继续存活，因而不影响 `remove` 等函数取回值的所有权。热键可以被移动到其他线程，但不能在线程之间共享
+    ///
+    /// 即使键尚不存在也可以创建热键，此时访问该值返回 `None`，直到该键被注册
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// let counter = Registry::<u64>::hot_key("counter");
+    /// assert_eq!(counter.with(|v| *v), None);
+    ///
+    /// Registry::register("counter", 0u64).unwrap();
+    /// for _ in 0..100 {
+    ///     counter.apply(|v| *v += 1);
+    /// }
+    /// assert_eq!(counter.with(|v| *v), Some(100));
+    ///
+    /// // 移除后缓存不会使值继续存活
+    /// assert_eq!(Registry::<u64>::remove("counter"), Some(100));
+    /// assert_eq!(counter.with(|v| *v), None);
+    ///
+    /// // 重新注册后访问新的值
+    /// Registry::register("counter", 7u64).unwrap();
+    /// assert_eq!(counter.with(|v| *v), Some(7));
+    /// Registry::<u64>::replace("counter", 8);
+    /// assert_eq!(counter.with(|v| *v), Some(8));
+    /// ```
+    pub fn hot_key(name: &str) -> HotKey<T> {
+        HotKey::new(name)
+    }
+
+    /// 获取指定键对应的值的句柄
+    ///
+    /// 句柄与注册表共享该值，通过句柄访问值时无需再查找注册表，因而适用于频繁访问同一个值的场景。
+    /// 从注册表中移除该键后，句柄仍可继续访问该值，此时 `Handle::is_detached` 返回 `true`；
+    /// `replace` 与 `set` 在原有的条目中替换值，句柄随后会观察到新值；对已存在的键再次注册则会以新的条目替换原有的条目，原有的句柄仍指向旧值而不会观察到新值。
+    /// 仍存在句柄时，被移除的值的所有权无法取回，`remove`、`register_replacing` 等函数将返回 `None`，值将在最后一个句柄被销毁时销毁
+    ///
+    /// 如果键不存在，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Position(f32, f32);
+    ///
+    /// Registry::register("player", Position(0.0, 0.0)).unwrap();
+    /// let player = Registry::<Position>::handle("player").unwrap();
+    /// for _ in 0..10 {
+    ///     player.apply(|p| p.0 += 1.0);
+    /// }
+    /// assert_eq!(Registry::<Position>::with("player", |p| p.0), Some(10.0));
+    /// assert_eq!(Registry::<Position>::version("player"), Some(10));
+    ///
+    /// // `replace` 在原有的条目中替换值，句柄会观察到新值
+    /// assert_eq!(Registry::<Position>::replace("player", Position(-1.0, -1.0)).map(|p| p.0), Some(10.0));
+    /// assert!(!player.is_detached());
+    /// assert_eq!(player.with(|p| p.0), Some(-1.0));
+    ///
+    /// // 再次注册会以新的条目替换原有的条目，新值对原有的句柄不可见
+    /// Registry::register("player", Position(-2.0, -2.0)).unwrap();
+    /// assert!(player.is_detached());
+    /// assert_eq!(player.with(|p| p.0), Some(-1.0));
+    /// assert_eq!(Registry::<Position>::with("player", |p| p.0), Some(-2.0));
+    ///
+    /// // 移除后句柄仍可访问该值
+    /// let player = Registry::<Position>::handle("player").unwrap();
+    /// assert!(!player.is_detached());
+    /// assert!(Registry::<Position>::remove("player").is_none());
+    /// assert!(!Registry::<Position>::exists("player"));
+    /// assert!(player.is_detached());
+    /// assert_eq!(player.apply(|p| { p.1 += 2.0; p.1 }), Some(0.0));
+    ///
+    /// assert!(Registry::<Position>::handle("player").is_none());
+    /// ```
+    pub fn handle(name: &str) -> Option<Handle<T>> {
+        Self::_handle(name)
+    }
+
+    /// 获取指定键对应的值的弱句柄
+    ///
+    /// 弱句柄不会使该值在被移除后继续存活：该值从注册表中被移除（或被替换）且不存在任何 `Handle` 时，通过弱句柄的访问将返回 `None`。
+    /// 如果键不存在，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("session", String::from("token")).unwrap();
+    /// let weak = Registry::<String>::weak_handle("session").unwrap();
+    /// assert_eq!(weak.with(|s| s.len()), Some(5));
+    ///
+    /// // 仍存在句柄时，弱句柄可以继续访问已被移除的值
+    /// let strong = Registry::<String>::handle("session").unwrap();
+    /// Registry::<String>::remove("session");
+    /// assert_eq!(weak.with(|s| s.clone()), Some(String::from("token")));
+    ///
+    /// drop(strong);
+    /// assert_eq!(weak.with(|s| s.clone()), None);
+    ///
+    /// // 不存在句柄时，移除后弱句柄立即失效
+    /// Registry::register("session", String::from("other")).unwrap();
+    /// let weak = Registry::<String>::handle("session").unwrap().downgrade();
+    /// assert_eq!(Registry::<String>::remove("session"), Some(String::from("other")));
+    /// assert!(weak.upgrade().is_none());
+    /// assert_eq!(weak.apply(|s| s.push('!')), None);
+    /// ```
+    pub fn weak_handle(name: &str) -> Option<WeakHandle<T>> {
+        Self::_handle(name).map(|handle| handle.downgrade())
+    }
+
+    fn _keys() -> Option<Vec<String>> {
+        let mut ret = Self::_keys_where(|_| true)?;
+        order_by_key(&mut ret, String::as_str);
+        Some(ret)
+    }
+
+    /// 获取注册表中该类型下所有已注册的键
+    ///
+    /// 返回的是键的副本，调用结束后不持有任何锁；如果该类型从未注册过，则返回空的 `Vec`。
+    /// 键的顺序不确定；启用 `ordered` 特性时，该函数以及其他枚举键的函数（`keys_with_prefix`、`snapshot`、`drain`、`apply_all`
+    /// 等）均按键的字典序返回或遍历
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Plugin;
+    ///
+    /// assert!(Registry::<Plugin>::keys().is_empty());
+    ///
+    /// Registry::register("plugin.a", Plugin).unwrap();
+    /// Registry::register("plugin.b", Plugin).unwrap();
+    ///
+    /// let mut keys = Registry::<Plugin>::keys();
+    /// keys.sort();
+    /// assert_eq!(keys, vec!["plugin.a", "plugin.b"]);
+    ///
+    /// // 仅涉及读锁，因此可以在同类型的 `with` 闭包中调用
+    /// Registry::<Plugin>::with("plugin.a", |_| {
+    ///     assert_eq!(Registry::<Plugin>::keys().len(), 2);
+    /// });
+    /// ```
+    ///
+    /// 与其他线程的注册操作并发执行：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// struct Item;
+    ///
+    /// let writer = thread::spawn(|| {
+    ///     for i in 0..100 {
+    ///         Registry::register(&format!("item.{}", i), Item).unwrap();
+    ///     }
+    /// });
+    /// while !writer.is_finished() {
+    ///     assert!(Registry::<Item>::keys().len() <= 100);
+    /// }
+    /// writer.join().unwrap();
+    /// assert_eq!(Registry::<Item>::keys().len(), 100);
+    /// ```
+    ///
+    /// 启用 `ordered` 特性时：
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "ordered")]
+    /// # {
+    /// use gom::Registry;
+    ///
+    /// for name in ["b.2", "a", "b.10", "c", "b.1"] {
+    ///     Registry::register(name, name.len()).unwrap();
+    /// }
+    /// assert_eq!(Registry::<usize>::keys(), vec!["a", "b.1", "b.10", "b.2", "c"]);
+    /// assert_eq!(Registry::<usize>::keys_with_prefix("b"), vec!["b.1", "b.10", "b.2"]);
+    ///
+    /// let mut visited = Vec::new();
+    /// Registry::<usize>::apply_all(|name, _| visited.push(name.to_string()));
+    /// assert_eq!(visited, Registry::<usize>::keys());
+    ///
+    /// let names: Vec<_> = Registry::<usize>::snapshot().into_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(names, Registry::<usize>::keys());
+    /// # }
+    /// ```
+    pub fn keys() -> Vec<String> {
+        Self::_keys().unwrap_or_default()
+    }
+
+    fn _len() -> Option<usize> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                Some(type_table.read().ok()?.len())
+            })
+            .ok()??
+    }
+
+    /// 获取注册表中该类型下已注册值的数量
+    ///
+    /// 不会获取任何键对应值的锁；如果该类型从未注册过，则返回 `0`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Counter;
+    ///
+    /// assert_eq!(Registry::<Counter>::len(), 0);
+    ///
+    /// for i in 0..10 {
+    ///     Registry::register(&format!("counter.{}", i), Counter).unwrap();
+    /// }
+    /// assert_eq!(Registry::<Counter>::len(), 10);
+    ///
+    /// for i in 0..5 {
+    ///     Registry::<Counter>::remove(&format!("counter.{}", i));
+    /// }
+    /// assert_eq!(Registry::<Counter>::len(), 5);
+    /// ```
+    pub fn len() -> usize {
+        Self::_len().unwrap_or(0)
+    }
+
+    /// 判断注册表中该类型下是否没有任何已注册的值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Flag;
+    ///
+    /// assert!(Registry::<Flag>::is_empty());
+    /// Registry::register("flag", Flag).unwrap();
+    /// assert!(!Registry::<Flag>::is_empty());
+    /// Registry::<Flag>::remove("flag");
+    /// assert!(Registry::<Flag>::is_empty());
+    /// ```
+    pub fn is_empty() -> bool {
+        Self::len() == 0
+    }
+
+    fn _reserve(additional: usize) -> Option<()> {
+        let type_table = Self::_ensure_type("")?;
+        check_deadlock!(mut T:"";Lock::Type);
+        let mut type_map = type_table.write().ok()?;
+        type_map.reserve(additional);
+        Some(())
+    }
+
+    /// 为该类型预留至少能再容纳 `additional` 个键的容量
+    ///
+    /// 如果该类型从未注册过，则会先为其创建对应的表。该操作需要获取该类型的写锁
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Item;
+    ///
+    /// Registry::<Item>::reserve(100);
+    /// assert!(Registry::<Item>::stats().capacity >= 100);
+    /// assert_eq!(Registry::<Item>::stats().len, 0);
+    /// ```
+    pub fn reserve(additional: usize) {
+        Self::_reserve(additional);
+    }
+
+    fn _shrink_to_fit() -> Option<()> {
+        let type_id = TypeId::of::<T>();
+        let type_table = _TABLE.get(type_id).ok()??;
+        check_deadlock!(mut T:"";Lock::Type);
+        let mut type_map = type_table.write().ok()?;
+        type_map.shrink_to_fit();
+        Some(())
+    }
+
+    /// 尽可能地释放该类型对应的表中多余的容量
+    ///
+    /// 大量移除键之后，表的容量并不会自动减小；长期运行的程序可以调用该函数归还内存。该操作需要获取该类型的写锁
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Item;
+    ///
+    /// for i in 0..10000 {
+    ///     Registry::register(&format!("item.{}", i), Item).unwrap();
+    /// }
+    /// // 保留一个键，否则该类型对应的表将随最后一个键一同被移除
+    /// for i in 1..10000 {
+    ///     Registry::<Item>::remove(&format!("item.{}", i));
+    /// }
+    /// let before = Registry::<Item>::stats().capacity;
+    /// assert!(before >= 10000);
+    ///
+    /// Registry::<Item>::shrink_to_fit();
+    /// assert!(Registry::<Item>::stats().capacity < before);
+    /// ```
+    ///
+    /// 在同类型的 `with` 或 `apply` 闭包中调用会导致线程死锁：
+    ///
+    /// ```rust,should_panic
+    /// use gom::Registry;
+    ///
+    /// Registry::register("key", 1u16).unwrap();
+    /// Registry::<u16>::with("key", |_| {
+    ///     Registry::<u16>::shrink_to_fit();
+    /// });
+    /// ```
+    pub fn shrink_to_fit() {
+        Self::_shrink_to_fit();
+    }
+
+    fn _stats() -> Option<BucketStats> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                let type_map = type_table.read().ok()?;
+                Some(BucketStats {
+                    len: type_map.len(),
+                    capacity: type_map.capacity(),
+                    poisoned_entries: type_map
+                        .values()
+                        .filter(|slot| slot.value.is_poisoned())
+                        .count(),
+                })
+            })
+            .ok()??
+    }
+
+    /// 获取该类型对应的表的统计信息
+    ///
+    /// 不会获取任何键对应值的锁；如果该类型从未注册过，则返回全零的统计信息
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("a", 1u32).unwrap();
+    /// Registry::register("b", 2u32).unwrap();
+    /// let _ = std::panic::catch_unwind(|| {
+    ///     Registry::<u32>::apply("b", |_| panic!("poison"));
+    /// });
+    ///
+    /// let stats = Registry::<u32>::stats();
+    /// assert_eq!(stats.len, 2);
+    /// assert!(stats.capacity >= 2);
+    /// // 启用 `parking_lot` 特性时锁不会中毒
+    /// #[cfg(not(feature = "parking_lot"))]
+    /// assert_eq!(stats.poisoned_entries, 1);
+    ///
+    /// assert_eq!(Registry::<u64>::stats(), Default::default());
+    /// ```
+    pub fn stats() -> BucketStats {
+        Self::_stats().unwrap_or_default()
+    }
+
+    /// 判断指定键对应的值的锁是否已中毒
+    ///
+    /// 在 `apply` 等闭包中发生 panic 会使该值的锁中毒（在 `entry` 的闭包中发生 panic 则会使该键所在分片的锁中毒），
+    /// 此后 `with`、`apply` 等访问该键时都将返回 `None`，而 `try_with` 等函数返回 `TryAccessError::Poisoned`；
+    /// 可以通过 `clear_poison` 恢复访问，或通过 `remove_poisoned` 移除该类型下所有锁已中毒的值。
+    /// 如果键不存在，则返回 `None`；启用 `parking_lot` 特性时锁不会中毒，总是返回 `Some(false)`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("config", 1u8).unwrap();
+    /// assert_eq!(Registry::<u8>::is_poisoned("config"), Some(false));
+    /// assert_eq!(Registry::<u8>::is_poisoned("missing"), None);
+    /// ```
+    pub fn is_poisoned(name: &str) -> Option<bool> {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |type_table| {
+                let shard = type_table.shard(name);
+                let type_map = shard.read().unwrap_or_else(|e| e.into_inner());
+                let slot = type_map.get(name)?;
+                Some(shard.is_poisoned() || slot.value.is_poisoned())
+            })
+            .ok()??
+    }
+
+    /// 清除指定键对应的值及其所在分片的锁的中毒状态，从而恢复对该键的访问
+    ///
+    /// 值保持发生 panic 时的状态，调用方应确认其仍然有效，否则应使用 `replace` 替换或 `remove` 移除该值。
+    /// 锁未中毒时不做任何操作；如果键不存在，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, TryAccessError};
+    /// use std::thread;
+    ///
+    /// Registry::register("counter", 0u32).unwrap();
+    /// let result = thread::spawn(|| {
+    ///     Registry::<u32>::apply("counter", |v| {
+    ///         *v += 1;
+    ///         panic!("failed halfway");
+    ///     })
+    /// })
+    /// .join();
+    /// assert!(result.is_err());
+    ///
+    /// // 启用 `parking_lot` 特性时锁不会中毒
+    /// #[cfg(not(feature = "parking_lot"))]
+    /// {
+    ///     assert_eq!(Registry::<u32>::is_poisoned("counter"), Some(true));
+    ///     assert_eq!(Registry::<u32>::with("counter", |v| *v), None);
+    ///     assert_eq!(Registry::<u32>::try_with("counter", |v| *v), Err(TryAccessError::Poisoned));
+    ///     assert_eq!(Registry::<u32>::try_with("missing", |v| *v), Err(TryAccessError::KeyMissing));
+    /// }
+    ///
+    /// assert_eq!(Registry::<u32>::clear_poison("counter"), Some(()));
+    /// assert_eq!(Registry::<u32>::is_poisoned("counter"), Some(false));
+    /// // 值保持发生 panic 时的状态
+    /// assert_eq!(Registry::<u32>::with("counter", |v| *v), Some(1));
+    /// assert_eq!(Registry::<u32>::apply("counter", |v| { *v += 1; *v }), Some(2));
+    /// assert_eq!(Registry::<u32>::clear_poison("missing"), None);
+    /// ```
+    ///
+    /// 在 `entry` 的闭包中发生 panic 时，该键的锁同样可以被恢复：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("item", String::from("a")).unwrap();
+    /// let _ = thread::spawn(|| {
+    ///     Registry::<String>::entry("item", |_| panic!("failed"));
+    /// })
+    /// .join();
+    /// #[cfg(not(feature = "parking_lot"))]
+    /// assert_eq!(Registry::<String>::with("item", |v| v.clone()), None);
+    ///
+    /// Registry::<String>::clear_poison("item").unwrap();
+    /// assert_eq!(Registry::<String>::with("item", |v| v.clone()), Some(String::from("a")));
+    /// ```
+    pub fn clear_poison(name: &str) -> Option<()> {
+        let type_table = _TABLE.get(TypeId::of::<T>()).ok()??;
+        let shard = type_table.shard(name);
+        {
+            let type_map = shard.read().unwrap_or_else(|e| e.into_inner());
+            type_map.get(name)?.value.clear_poison();
+        }
+        shard.clear_poison();
+        Some(())
+    }
+
+    fn _remove_poisoned() -> Option<Vec<String>> {
+        let type_id = TypeId::of::<T>();
+        let removed: Vec<_> = {
+            let type_table = _TABLE.get(type_id).ok()??;
+            let type_table = type_table.writable()?;
+            check_deadlock!(mut T:"";Lock::Type);
+            let mut type_map = type_table.write().unwrap_or_else(|e| e.into_inner());
+            let poisoned: Vec<_> = type_map
+                .iter()
+                .filter(|(name, slot)| {
+                    type_table.shard(name).is_poisoned() || slot.value.is_poisoned()
+                })
+                .map(|(name, _)| Arc::clone(name))
+                .collect();
+            let removed = poisoned
+                .into_iter()
+                .filter_map(|name| Some((name.to_string(), type_map.remove(&name)?)))
+                .collect();
+            drop(type_map);
+            for shard in &type_table.shards {
+                shard.clear_poison();
+            }
+            removed
+        };
+        collect_empty(|id| *id == type_id);
+        let mut names: Vec<_> = removed.iter().map(|(name, _)| name.clone()).collect();
+        order_by_key(&mut names, String::as_str);
+        // 被移除的条目在释放所有锁之后销毁
+        drop(removed);
+        Some(names)
+    }
+
+    /// 从注册表中移除该类型下所有锁已中毒的值（包括位于锁已中毒的分片中的值），并返回被移除的键
+    ///
+    /// 被移除的值在释放锁之后被销毁；返回的 `Vec` 中的元素顺序不确定（启用 `ordered` 特性时按键的字典序排列）。
+    /// 如果该类型不存在或已被冻结，则返回空的 `Vec`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// for name in ["a", "b", "c"] {
+    ///     Registry::register(name, 0i64).unwrap();
+    /// }
+    /// for name in ["a", "c"] {
+    ///     let _ = thread::spawn(move || {
+    ///         Registry::<i64>::apply(name, |_| panic!("failed"));
+    ///     })
+    ///     .join();
+    /// }
+    ///
+    /// let mut removed = Registry::<i64>::remove_poisoned();
+    /// removed.sort();
+    /// // 启用 `parking_lot` 特性时锁不会中毒
+    /// if cfg!(feature = "parking_lot") {
+    ///     assert!(removed.is_empty());
+    /// } else {
+    ///     assert_eq!(removed, vec!["a", "c"]);
+    ///     assert_eq!(Registry::<i64>::keys(), vec!["b"]);
+    /// }
+    /// assert_eq!(Registry::<i64>::apply("b", |v| { *v += 1; *v }), Some(1));
+    /// ```
+    pub fn remove_poisoned() -> Vec<String> {
+        Self::_remove_poisoned().unwrap_or_default()
+    }
+
+    /// 设置指定键对应的值是否优先写入
+    ///
+    /// 持续被多个线程读取的值在部分平台上可能使 `apply` 等写入长时间无法获取写锁。启用优先写入后，
+    /// 有线程正在等待该值的写锁时，新的读取者先让出执行，直至写入者获取写锁，每次至多等待 10 毫秒；未启用时读取不受任何影响。
+    /// 以 `register` 等函数替换该键的值后保留该设置；如果键不存在，则返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("config", 0u64).unwrap();
+    /// assert_eq!(Registry::<u64>::set_write_priority("config", true), Some(()));
+    /// assert_eq!(Registry::<u64>::apply("config", |v| { *v += 1; *v }), Some(1));
+    /// // 嵌套的读取不会与写入者相互等待
+    /// assert_eq!(Registry::<u64>::with("config", |a| Registry::<u64>::with("config", |b| a + b)), Some(Some(2)));
+    /// assert_eq!(Registry::<u64>::set_write_priority("missing", true), None);
+    /// ```
+    pub fn set_write_priority(name: &str, enabled: bool) -> Option<()> {
+        let type_table = _TABLE.get(TypeId::of::<T>()).ok()??;
+        let type_map = type_table.read_shard(name).ok()?;
+        type_map
+            .get(name)?
+            .write_priority
+            .store(enabled, Ordering::Relaxed);
+        Some(())
+    }
+
+    /// 确保该类型在注册表中已有对应的表
+    ///
+    /// 如果该类型对应的表不存在，则创建一个空表，否则不执行任何操作；多个线程可以同时调用。
+    /// 预先创建表之后，首次注册该类型的值时无需再获取注册表的写锁
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// assert!(!Registry::<f64>::is_initialized());
+    /// let workers: Vec<_> = (0..4).map(|_| thread::spawn(Registry::<f64>::init)).collect();
+    /// for worker in workers {
+    ///     worker.join().unwrap();
+    /// }
+    /// assert!(Registry::<f64>::is_initialized());
+    /// assert!(Registry::<f64>::is_empty());
+    ///
+    /// // 在其他类型的闭包中注册该类型的值
+    /// Registry::register("id", 1i32).unwrap();
+    /// Registry::<i32>::with("id", |_| {
+    ///     Registry::register("ratio", 2.9f64).unwrap();
+    /// });
+    /// assert_eq!(Registry::<f64>::get("ratio"), Some(2.9));
+    /// ```
+    pub fn init() {
+        Self::_ensure_type("");
+    }
+
+    /// 判断该类型在注册表中是否已有对应的表，即是否已调用过 `init` 或注册过该类型的值
+    pub fn is_initialized() -> bool {
+        _TABLE
+            .with_table(TypeId::of::<T>(), |_| ())
+            .is_ok_and(|found| found.is_some())
+    }
+
+    fn _freeze(frozen: bool) -> Option<()> {
+        let type_table = if frozen {
+            Self::_ensure_type("")?
+        } else {
+            _TABLE.get(TypeId::of::<T>()).ok()??
+        };
+        type_table.frozen.store(frozen, Ordering::Release);
+        Some(())
+    }
+
+    /// 冻结该类型，使其此后只能被读取
+    ///
+    /// 冻结后，`register`、`replace`、`remove`、`apply` 等所有修改该类型的操作都将失败：返回 `Result` 的操作返回对应错误类型的
+    /// `Frozen` 变体，返回 `Option` 的操作返回 `None`；`with`、`get`、`exists` 等只读操作不受影响。
+    /// 冻结标志在获取写锁之前检查，因此修改操作会立即失败而不会等待锁。冻结之前已开始的修改操作不受影响
+    ///
+    /// 如果该类型从未注册过，则会先为其创建对应的表
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{ConflictPolicy, RegisterErrorKind, Registry, RenameError};
+    /// use std::thread;
+    ///
+    /// Registry::register("width", 800u32).unwrap();
+    /// Registry::register("height", 600u32).unwrap();
+    /// Registry::<u32>::freeze();
+    /// assert!(Registry::<u32>::is_frozen());
+    ///
+    /// assert_eq!(Registry::register("depth", 1u32), Err(()));
+    /// assert_eq!(
+    ///     Registry::try_register("depth", 1u32).unwrap_err().kind(),
+    ///     RegisterErrorKind::Frozen
+    /// );
+    /// assert_eq!(Registry::<u32>::replace("width", 1024), None);
+    /// assert_eq!(Registry::<u32>::remove("width"), None);
+    /// assert_eq!(Registry::<u32>::apply("width", |v| *v += 1), None);
+    /// assert_eq!(Registry::<u32>::rename("width", "w"), Err(RenameError::Frozen));
+    ///
+    /// let report = Registry::register_many(vec![("depth".to_string(), 1u32)], ConflictPolicy::Overwrite);
+    /// assert!(report.inserted.is_empty());
+    /// assert_eq!(report.remainder.len(), 1);
+    /// assert!(!Registry::<u32>::exists("depth"));
+    /// assert_eq!(Registry::<u32>::extend([("depth".to_string(), 1u32)]).inserted, 0);
+    ///
+    /// let readers: Vec<_> = (0..4)
+    ///     .map(|_| thread::spawn(|| Registry::<u32>::with("width", |v| *v)))
+    ///     .collect();
+    /// for reader in readers {
+    ///     assert_eq!(reader.join().unwrap(), Some(800));
+    /// }
+    /// assert_eq!(Registry::<u32>::get("height"), Some(600));
+    /// assert!(Registry::<u32>::exists("height"));
+    ///
+    /// Registry::<u32>::unfreeze();
+    /// assert_eq!(Registry::<u32>::replace("width", 1024), Some(800));
+    /// ```
+    pub fn freeze() {
+        Self::_freeze(true);
+    }
+
+    /// 解除该类型的冻结，主要用于测试
+    pub fn unfreeze() {
+        Self::_freeze(false);
+    }
+
+    /// 判断该类型是否已被冻结
+    pub fn is_frozen() -> bool {
+        let type_id = TypeId::of::<T>();
+        _TABLE
+            .read()
+            .ok()
+            .and_then(|map| map.get(&type_id).map(|t| t.is_frozen()))
+            .unwrap_or(false)
+    }
+
+    /// 向注册表中的指定键应用一个函数，该函数可以修改注册表中的值
+    ///
+    /// 如果键不存在，则返回 `None`；否则，返回闭包函数的返回值。
+    /// 闭包函数执行期间仅持有该值自身的写锁，而不持有注册表及该类型对应的表的锁，因而同一类型的其他键仍可被注册、移除与访问
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::<i32>::register("my_key", 42);
+    /// assert_eq!(Registry::<i32>::apply("my_key", |v| { *v += 1; *v }), Some(43));
+    /// assert_eq!(Registry::<i32>::apply("other_key", |v| *v += 1), None);
+    /// ```
+    ///
+    /// 在闭包函数中注册同一类型的其他键：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("k", 1u64).unwrap();
+    /// Registry::<u64>::apply("k", |v| {
+    ///     *v += 1;
+    ///     Registry::register("other", *v * 10).unwrap();
+    /// });
+    /// assert_eq!(Registry::<u64>::get("k"), Some(2));
+    /// assert_eq!(Registry::<u64>::get("other"), Some(20));
+    /// ```
+    ///
+    /// 耗时较长的闭包函数不会阻塞其他线程对同一类型其他键的访问：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// Registry::register("slow", 0u32).unwrap();
+    /// Registry::register("fast", 0u32).unwrap();
+    ///
+    /// let (started, wait_started) = mpsc::channel();
+    /// let (finish, wait_finish) = mpsc::channel::<()>();
+    /// let slow = thread::spawn(move || {
+    ///     Registry::<u32>::apply("slow", |v| {
+    ///         started.send(()).unwrap();
+    ///         // 直到其他键的操作全部完成后才结束
+    ///         wait_finish.recv_timeout(Duration::from_secs(10)).unwrap();
+    ///         *v += 1;
+    ///     })
+    /// });
+    /// wait_started.recv().unwrap();
+    ///
+    /// assert_eq!(Registry::<u32>::apply("fast", |v| { *v += 1; *v }), Some(1));
+    /// assert_eq!(Registry::<u32>::with("fast", |v| *v), Some(1));
+    /// Registry::register("new", 7u32).unwrap();
+    /// assert_eq!(Registry::<u32>::replace("fast", 5), Some(1));
+    /// assert_eq!(Registry::<u32>::remove("new"), Some(7));
+    ///
+    /// finish.send(()).unwrap();
+    /// assert_eq!(slow.join().unwrap(), Some(()));
+    /// assert_eq!(Registry::<u32>::get("slow"), Some(1));
+    /// ```
+    pub fn apply<R, F: FnOnce(&mut T) -> R>(name: &str, func: F) -> Option<R> {
+        unchecked("write", Self::apply_checked(name, func))
+    }
+
+    /// 与 `apply` 相同，但失败时返回 `RegistryError` 说明失败的原因
+    ///
+    /// 该类型没有对应的表时返回 `RegistryError::TypeNotRegistered`，键不存在时返回 `RegistryError::KeyNotFound`，
+    /// 该类型已被冻结时返回 `RegistryError::Frozen`，锁已中毒时返回 `RegistryError::Poisoned`，
+    /// 值无法以 `T` 访问（如延迟初始化的值的初始化函数发生了 panic）时返回 `RegistryError::Downcast`；
+    /// 在调试模式下，当前线程已持有该值的锁时返回 `RegistryError::WouldDeadlock`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, RegistryError};
+    /// use std::thread;
+    ///
+    /// struct Score(u32);
+    ///
+    /// let err = Registry::<Score>::apply_checked("alice", |s| s.0 += 1).unwrap_err();
+    /// assert!(matches!(err, RegistryError::TypeNotRegistered { .. }));
+    /// assert!(err.to_string().contains("alice"));
+    /// assert!(err.to_string().contains(std::any::type_name::<Score>()));
+    ///
+    /// Registry::register("bob", Score(0)).unwrap();
+    /// assert_eq!(Registry::<Score>::apply_checked("bob", |s| { s.0 += 1; s.0 }), Ok(1));
+    /// let err = Registry::<Score>::apply_checked("alice", |s| s.0 += 1).unwrap_err();
+    /// assert_eq!(err, RegistryError::KeyNotFound { key: "alice".into(), type_name: err.type_name() });
+    ///
+    /// // 闭包函数发生 panic 会使该值的锁中毒；启用 `parking_lot` 特性时锁不会中毒
+    /// let _ = thread::spawn(|| Registry::<Score>::apply("bob", |_| panic!("failed"))).join();
+    /// #[cfg(not(feature = "parking_lot"))]
+    /// assert!(matches!(
+    ///     Registry::<Score>::apply_checked("bob", |s| s.0),
+    ///     Err(RegistryError::Poisoned { .. })
+    /// ));
+    ///
+    /// // 初始化函数发生 panic 的延迟初始化的值无法以 `Score` 访问
+    /// Registry::register_lazy("carol", || -> Score { panic!("init failed") }).unwrap();
+    /// let _ = thread::spawn(|| Registry::<Score>::with("carol", |s| s.0)).join();
+    /// Registry::<Score>::clear_poison("carol").unwrap();
+    /// assert!(matches!(
+    ///     Registry::<Score>::apply_checked("carol", |s| s.0),
+    ///     Err(RegistryError::Downcast { .. })
+    /// ));
+    ///
+    /// Registry::<Score>::freeze();
+    /// assert!(matches!(
+    ///     Registry::<Score>::apply_checked("bob", |s| s.0),
+    ///     Err(RegistryError::Frozen { .. })
+    /// ));
+    /// ```
+    ///
+    /// 在调试模式下或启用 `deadlock-detection` 特性时，嵌套访问同一个键时返回 `RegistryError::WouldDeadlock`，而 `apply` 会引发 panic：
+    ///
+    /// ```rust
+    /// use gom::{Registry, RegistryError};
+    ///
+    /// Registry::register("key", 1i8).unwrap();
+    /// # if cfg!(any(debug_assertions, feature = "deadlock-detection")) {
+    /// let nested = Registry::<i8>::with("key", |_| Registry::<i8>::apply_checked("key", |v| *v += 1));
+    /// assert!(matches!(nested, Some(Err(RegistryError::WouldDeadlock { .. }))));
+    /// # }
+    /// ```
+    pub fn apply_checked<R, F: FnOnce(&mut T) -> R>(
+        name: &str,
+        func: F,
+    ) -> Result<R, RegistryError> {
+        let type_id = TypeId::of::<T>();
+        let (key, slot, frozen) = Self::_checked_record(name)?;
+        if frozen {
+            return Err(registry_error!(Frozen, name, T));
+        }
+        if would_deadlock!(mut T:name;Lock::Key) {
+            return Err(registry_error!(WouldDeadlock, name, T));
+        }
+        mark_waiting!(mut T:name;Lock::Key);
+        let mut watch = Stopwatch::start();
+        let mut value = slot.write_as::<T>(Wait::Block).map_err(|e| match e {
+            AccessError::Downcast => registry_error!(Downcast, name, T),
+            _ => registry_error!(Poisoned, name, T),
+        })?;
+        watch.locked();
+        let frame =
+            ContextOperator::enter(Context::Apply(key, type_id, std::any::type_name::<T>()));
+        let ret = func(&mut value);
+        drop(frame);
+        // 释放锁之后再调用慢速访问的回调函数
+        value.commit();
+        watch.finish(true, name, std::any::type_name::<T>());
+        Ok(ret)
+    }
+
+    /// 判断当前线程此时以 `apply` 等函数修改指定键的值是否会导致死锁
+    ///
+    /// 当前线程正处于访问该键的 `with` 或 `apply` 闭包中，或持有该键的守卫时返回 `true`；
+    /// 该判断不依赖于调试模式或 `deadlock-detection` 特性，可用于在执行可能嵌套访问注册表的回调前拒绝该操作
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("counter", 0u32).unwrap();
+    /// assert!(!Registry::<u32>::would_deadlock_write("counter"));
+    /// Registry::<u32>::with("counter", |_| {
+    ///     assert!(Registry::<u32>::would_deadlock_write("counter"));
+    ///     assert!(!Registry::<u32>::would_deadlock_read("counter"));
+    ///     assert!(!Registry::<u32>::would_deadlock_write("other"));
+    ///     assert!(!Registry::<u64>::would_deadlock_write("counter"));
+    /// });
+    /// Registry::<u32>::apply("counter", |_| {
+    ///     assert!(Registry::<u32>::would_deadlock_read("counter"));
+    /// });
+    /// ```
+    pub fn would_deadlock_write(name: &str) -> bool {
+        ContextOperator::cannot_lock_write_lock(TypeId::of::<T>(), name, Lock::Key)
+    }
+
+    /// 判断当前线程此时以 `with` 等函数读取指定键的值是否会导致死锁
+    ///
+    /// 当前线程正处于修改该键的 `apply` 闭包中，或持有该键的写锁守卫时返回 `true`；与 `would_deadlock_write` 相同，该判断不依赖于调试模式
+    pub fn would_deadlock_read(name: &str) -> bool {
+        ContextOperator::cannot_lock_read_lock(TypeId::of::<T>(), name)
+    }
+
+    /// 与 `apply` 相同，但不会等待任何锁
+    ///
+    /// 注册表、该类型对应的表或该值自身的锁无法立即获取时，返回 `TryAccessError::WouldBlock` 并且不会执行闭包函数。
+    /// 该函数不进行调试模式下的死锁检查：当前线程自身持有该值的锁（例如在同一个键的 `with` 或 `apply` 闭包中调用）时同样返回
+    /// `TryAccessError::WouldBlock`，因而可以用于探测重入。
+    /// 键不存在时返回 `TryAccessError::KeyMissing`，该类型已被冻结时返回 `TryAccessError::Frozen`，锁已中毒时返回 `TryAccessError::Poisoned`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, TryAccessError};
+    ///
+    /// Registry::register("counter", 0u8).unwrap();
+    /// assert_eq!(Registry::<u8>::try_apply("counter", |v| { *v += 1; *v }), Ok(1));
+    /// assert_eq!(Registry::<u8>::try_apply("missing", |v| *v), Err(TryAccessError::KeyMissing));
+    ///
+    /// // 在同一个键的 `apply` 中重入时返回错误，而不是 panic 或死锁
+    /// let inner = Registry::<u8>::apply("counter", |_| Registry::<u8>::try_apply("counter", |v| *v));
+    /// assert_eq!(inner, Some(Err(TryAccessError::WouldBlock)));
+    /// let inner = Registry::<u8>::with("counter", |_| Registry::<u8>::try_apply("counter", |v| *v));
+    /// assert_eq!(inner, Some(Err(TryAccessError::WouldBlock)));
+    /// assert_eq!(Registry::<u8>::get("counter"), Some(1));
+    /// ```
+    pub fn try_apply<R, F: FnOnce(&mut T) -> R>(name: &str, func: F) -> Result<R, TryAccessError> {
+        let type_id = TypeId::of::<T>();
+        let slot = Self::_try_record(name, true)?;
+        let mut value = slot.write_as::<T>(Wait::Never)?;
+        let frame = ContextOperator::enter(Context::Apply(
+            intern(name),
+            type_id,
+            std::any::type_name::<T>(),
+        ));
+        let ret = func(&mut value);
+        drop(frame);
+        value.commit();
+        Ok(ret)
+    }
+
+    /// 与 `apply` 相同，但最多等待 `timeout` 指定的时长
+    ///
+    /// 等待的方式与精度同 `with_timeout`。超时时返回 `TimeoutError::TimedOut` 并且不会执行闭包函数；
+    /// 键不存在时返回 `TimeoutError::KeyMissing`，该类型已被冻结时返回 `TimeoutError::Frozen`，锁已中毒时返回 `TimeoutError::Poisoned`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, TimeoutError};
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// Registry::register("shared", 0u16).unwrap();
+    ///
+    /// let (locked, wait_locked) = mpsc::channel();
+    /// let reader = thread::spawn(move || {
+    ///     Registry::<u16>::with("shared", |_| {
+    ///         locked.send(()).unwrap();
+    ///         thread::sleep(Duration::from_millis(50));
+    ///     })
+    /// });
+    /// wait_locked.recv().unwrap();
+    ///
+    /// assert_eq!(
+    ///     Registry::<u16>::apply_timeout("shared", Duration::from_millis(10), |v| *v += 1),
+    ///     Err(TimeoutError::TimedOut)
+    /// );
+    /// assert_eq!(
+    ///     Registry::<u16>::apply_timeout("shared", Duration::from_millis(500), |v| { *v += 1; *v }),
+    ///     Ok(1)
+    /// );
+    /// reader.join().unwrap();
+    /// ```
+    pub fn apply_timeout<R, F: FnOnce(&mut T) -> R>(
+        name: &str,
+        timeout: Duration,
+        func: F,
+    ) -> Result<R, TimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let slot = Self::_record_until(name, true, deadline)?;
+        let mut value = slot
+            .write_as::<T>(Wait::Until(deadline))
+            .map_err(TryAccessError::from)?;
+        let frame = ContextOperator::enter(Context::Apply(
+            intern(name),
+            TypeId::of::<T>(),
+            std::any::type_name::<T>(),
+        ));
+        let ret = func(&mut value);
+        drop(frame);
+        value.commit();
+        Ok(ret)
+    }
+
+    /// 先以只读方式访问指定键对应的值，再根据需要以可写方式访问该值
+    ///
+    /// 只读闭包函数返回 `Upgrade::Done` 时直接返回其中的值；返回 `Upgrade::Write` 时，释放读锁后重新获取写锁，并对该值执行其中的闭包函数。
+    ///
+    /// # 注意
+    ///
+    /// 升级不是原子的：释放读锁与获取写锁之间，其他线程可能已经修改、替换或移除了该值。
+    /// 可写闭包函数会再次收到该值，应当重新检查只读阶段所依据的条件，而不是假定该值未被修改
+    ///
+    /// 如果键不存在、锁已中毒，或者需要修改时该类型已被冻结或键已被移除，则返回 `None`；否则，返回相应闭包函数的返回值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, Upgrade};
+    ///
+    /// Registry::register("counter", 1).unwrap();
+    ///
+    /// let ret = Registry::<i32>::with_upgrade("counter", |v| {
+    ///     if *v % 2 == 0 {
+    ///         Upgrade::Done(false)
+    ///     } else {
+    ///         Upgrade::Write(|v: &mut i32| {
+    ///             // 重新检查条件
+    ///             if *v % 2 == 0 {
+    ///                 return false;
+    ///             }
+    ///             *v += 1;
+    ///             true
+    ///         })
+    ///     }
+    /// });
+    /// assert_eq!(ret, Some(true));
+    /// assert_eq!(Registry::<i32>::with("counter", |v| *v), Some(2));
+    /// ```
+    ///
+    /// 多个线程同时执行“小于上限时递增”，可写阶段的重新检查保证其他线程在升级窗口中的修改不会导致超出上限：
+    ///
+    /// ```rust
+    /// use gom::{Registry, Upgrade};
+    /// use std::thread;
+    ///
+    /// Registry::register("bounded", 0u32).unwrap();
+    ///
+    /// let workers: Vec<_> = (0..8)
+    ///     .map(|_| {
+    ///         thread::spawn(|| {
+    ///             let mut succeeded = 0;
+    ///             for _ in 0..50 {
+    ///                 let ret = Registry::<u32>::with_upgrade("bounded", |v| {
+    ///                     if *v >= 100 {
+    ///                         return Upgrade::Done(false);
+    ///                     }
+    ///                     Upgrade::Write(|v: &mut u32| {
+    ///                         if *v >= 100 {
+    ///                             return false;
+    ///                         }
+    ///                         *v += 1;
+    ///                         true
+    ///                     })
+    ///                 });
+    ///                 if ret == Some(true) {
+    ///                     succeeded += 1;
+    ///                 }
+    ///             }
+    ///             succeeded
+    ///         })
+    ///     })
+    ///     .collect();
+    /// let total: u32 = workers.into_iter().map(|w| w.join().unwrap()).sum();
+    /// assert_eq!(total, 100);
+    /// assert_eq!(Registry::<u32>::with("bounded", |v| *v), Some(100));
+    /// ```
+    pub fn with_upgrade<R, W, F>(name: &str, func: F) -> Option<R>
+    where
+        W: FnOnce(&mut T) -> R,
+        F: FnOnce(&T) -> Upgrade<R, W>,
+    {
+        match Self::with(name, func)? {
+            Upgrade::Done(ret) => Some(ret),
+            Upgrade::Write(func) => Self::apply(name, func),
+        }
+    }
+
+    /// 向注册表中的两个不同键同时应用一个函数，该函数可以修改这两个键对应的值
+    ///
+    /// 两个键对应的锁按键的顺序获取，因而不同线程以不同顺序调用时不会死锁。
+    /// 如果任一键不存在或两个键相同，则返回 `None`；否则，返回闭包函数的返回值
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("inventory.a", vec!["sword", "shield"]).unwrap();
+    /// Registry::register("inventory.b", Vec::<&str>::new()).unwrap();
+    ///
+    /// let moved = Registry::<Vec<&str>>::apply_pair("inventory.a", "inventory.b", |a, b| {
+    ///     let item = a.pop()?;
+    ///     b.push(item);
+    ///     Some(item)
+    /// });
+    /// assert_eq!(moved, Some(Some("shield")));
+    /// assert_eq!(Registry::<Vec<&str>>::with("inventory.b", |v| v.clone()), Some(vec!["shield"]));
+    ///
+    /// assert_eq!(Registry::<Vec<&str>>::apply_pair("inventory.a", "inventory.a", |_, _| ()), None);
+    /// assert_eq!(Registry::<Vec<&str>>::apply_pair("inventory.a", "other", |_, _| ()), None);
+    /// ```
+    ///
+    /// 两个线程以相反的顺序同时调用：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("a", 1000i64).unwrap();
+    /// Registry::register("b", 1000i64).unwrap();
+    ///
+    /// let forward = thread::spawn(|| {
+    ///     for _ in 0..1000 {
+    ///         Registry::<i64>::apply_pair("a", "b", |a, b| { *a -= 1; *b += 1; }).unwrap();
+    ///     }
+    /// });
+    /// let backward = thread::spawn(|| {
+    ///     for _ in 0..1000 {
+    ///         Registry::<i64>::apply_pair("b", "a", |b, a| { *b -= 2; *a += 2; }).unwrap();
+    ///     }
+    /// });
+    /// forward.join().unwrap();
+    /// backward.join().unwrap();
+    ///
+    /// assert_eq!(Registry::<i64>::with("a", |v| *v), Some(2000));
+    /// assert_eq!(Registry::<i64>::with("b", |v| *v), Some(0));
+    /// ```
+    pub fn apply_pair<R, F: FnOnce(&mut T, &mut T) -> R>(a: &str, b: &str, func: F) -> Option<R> {
+        if a == b {
+            return None;
+        }
+        let type_id = TypeId::of::<T>();
+        let (lock_a, lock_b) = {
+            let type_table = _TABLE.get(type_id).ok()??;
+            let type_map = type_table.writable()?.read().ok()?;
+            let (lock_a, lock_b) = (type_map.get(a)?, type_map.get(b)?);
+            (Arc::clone(&lock_a.data), Arc::clone(&lock_b.data))
+        };
+        check_deadlock!(mut T:a;Lock::Key);
+        check_deadlock!(mut T:b;Lock::Key);
+        let (mut value_a, mut value_b) = if a < b {
+            let value_a = lock_a.write_as::<T>(Wait::Block).ok()?;
+            (value_a, lock_b.write_as::<T>(Wait::Block).ok()?)
+        } else {
+            let value_b = lock_b.write_as::<T>(Wait::Block).ok()?;
+            (lock_a.write_as::<T>(Wait::Block).ok()?, value_b)
+        };
+        let frames = [
+            ContextOperator::enter(Context::Apply(
+                intern(a),
+                type_id,
+                std::any::type_name::<T>(),
+            )),
+            ContextOperator::enter(Context::Apply(
+                intern(b),
+                type_id,
+                std::any::type_name::<T>(),
+            )),
+        ];
+        let ret = Some(func(&mut value_a, &mut value_b));
+        drop(frames);
+        value_a.commit();
+        value_b.commit();
+        ret
+    }
+
+    /// 向注册表中的指定键应用一个函数，该函数仅能读取注册表中的值
+    ///
+    /// 如果键不存在，则返回 `None`；否则，返回闭包函数的返回值。
+    /// 闭包函数执行期间仅持有该值自身的读锁，而不持有注册表及该类型对应的表的锁
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::<i32>::register("my_key", 42);
+    /// assert_eq!(Registry::<i32>::with("my_key", |v| *v), Some(42));
+    /// assert_eq!(Registry::<i32>::with("other_key", |v| *v), None);
+    /// ```
+    pub fn with<R, F: FnOnce(&T) -> R>(name: &str, func: F) -> Option<R> {
+        unchecked("read", Self::with_checked(name, func))
+    }
+
+    /// 与 `with` 相同，但失败时返回 `RegistryError` 说明失败的原因
+    ///
+    /// 该类型没有对应的表时返回 `RegistryError::TypeNotRegistered`，键不存在时返回 `RegistryError::KeyNotFound`，
+    /// 锁已中毒时返回 `RegistryError::Poisoned`，值无法以 `T` 访问时返回 `RegistryError::Downcast`；
+    /// 在调试模式下，当前线程已持有该值的写锁时返回 `RegistryError::WouldDeadlock`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, RegistryError};
+    ///
+    /// struct Volume(u8);
+    ///
+    /// assert!(matches!(
+    ///     Registry::<Volume>::with_checked("master", |v| v.0),
+    ///     Err(RegistryError::TypeNotRegistered { .. })
+    /// ));
+    /// Registry::register("master", Volume(7)).unwrap();
+    /// assert_eq!(Registry::<Volume>::with_checked("master", |v| v.0), Ok(7));
+    ///
+    /// let err = Registry::<Volume>::with_checked("music", |v| v.0).unwrap_err();
+    /// assert_eq!(err.key(), "music");
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     format!("key `music` of type `{}`: key does not exist", std::any::type_name::<Volume>())
+    /// );
+    /// ```
+    pub fn with_checked<R, F: FnOnce(&T) -> R>(name: &str, func: F) -> Result<R, RegistryError> {
+        let type_id = TypeId::of::<T>();
+        let (key, slot, _) = Self::_checked_record(name)?;
+        // 以 `register_read_mostly` 注册的值无需获取任何锁
+        #[cfg(feature = "arc-swap")]
+        if let Some(read_mostly) = slot.read_mostly::<T>() {
+            let mut watch = Stopwatch::start();
+            let var = read_mostly.load();
+            watch.locked();
+            let frame =
+                ContextOperator::enter(Context::With(key, type_id, std::any::type_name::<T>()));
+            let ret = func(&var);
+            drop(frame);
+            drop(var);
+            watch.finish(false, name, std::any::type_name::<T>());
+            return Ok(ret);
+        }
+        if would_deadlock!(ref T:name) {
+            return Err(registry_error!(WouldDeadlock, name, T));
+        }
+        mark_waiting!(ref T:name);
+        let mut watch = Stopwatch::start();
+        let value = slot
+            .read()
+            .map_err(|_| registry_error!(Poisoned, name, T))?;
+        watch.locked();
+        let var = value
+            .downcast_ref::<T>()
+            .ok_or_else(|| registry_error!(Downcast, name, T))?;
+        let frame = ContextOperator::enter(Context::With(key, type_id, std::any::type_name::<T>()));
+        let ret = func(var);
+        drop(frame);
+        // 释放锁之后再调用慢速访问的回调函数
+        drop(value);
+        watch.finish(false, name, std::any::type_name::<T>());
+        Ok(ret)
+    }
+
+    /// 与 `with` 相同，但不会等待任何锁
+    ///
+    /// 注册表、该类型对应的表或该值自身的锁无法立即获取时，返回 `TryAccessError::WouldBlock` 并且不会执行闭包函数；
+    /// 键不存在时返回 `TryAccessError::KeyMissing`，锁已中毒时返回 `TryAccessError::Poisoned`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, TryAccessError};
+    /// use std::sync::{Arc, Barrier};
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// Registry::register("position", (0i32, 0i32)).unwrap();
+    /// assert_eq!(Registry::<(i32, i32)>::try_with("position", |p| p.0), Ok(0));
+    /// assert_eq!(Registry::<(i32, i32)>::try_with("missing", |p| p.0), Err(TryAccessError::KeyMissing));
+    ///
+    /// let locked = Arc::new(Barrier::new(2));
+    /// let checked = Arc::new(Barrier::new(2));
+    /// let writer = {
+    ///     let (locked, checked) = (Arc::clone(&locked), Arc::clone(&checked));
+    ///     thread::spawn(move || {
+    ///         Registry::<(i32, i32)>::apply("position", |p| {
+    ///             locked.wait();
+    ///             checked.wait();
+    ///             p.0 += 1;
+    ///         })
+    ///     })
+    /// };
+    /// locked.wait();
+    /// let start = Instant::now();
+    /// assert_eq!(
+    ///     Registry::<(i32, i32)>::try_with("position", |p| p.0),
+    ///     Err(TryAccessError::WouldBlock)
+    /// );
+    /// assert!(start.elapsed() < Duration::from_secs(1));
+    /// checked.wait();
+    /// writer.join().unwrap();
+    ///
+    /// assert_eq!(Registry::<(i32, i32)>::try_with("position", |p| p.0), Ok(1));
+    /// ```
+    pub fn try_with<R, F: FnOnce(&T) -> R>(name: &str, func: F) -> Result<R, TryAccessError> {
+        let type_id = TypeId::of::<T>();
+        let slot = Self::_try_record(name, false)?;
+        let value = slot.read_as::<T>(Wait::Never)?;
+        let frame = ContextOperator::enter(Context::With(
+            intern(name),
+            type_id,
+            std::any::type_name::<T>(),
+        ));
+        let ret = func(&value);
+        drop(frame);
+        Ok(ret)
+    }
+
+    /// 与 `with` 相同，但最多等待 `timeout` 指定的时长
+    ///
+    /// 标准库的 `RwLock` 不支持限时获取，因而该函数以 `try_with` 相同的方式反复尝试获取所需的锁，两次尝试之间休眠一段逐次加倍、最长为 1 毫秒的时间。
+    /// 实际等待的时长可能比 `timeout` 多出约 1 毫秒加上线程调度的延迟，锁被释放后也可能需要同样长的时间才能被获取。
+    /// 启用 `parking_lot` 特性时改用其原生的限时获取，锁被释放时等待的线程会立即被唤醒。
+    /// 该函数不进行调试模式下的死锁检查，当前线程自身持有所需的锁时，将在超时后返回错误
+    ///
+    /// 超时时返回 `TimeoutError::TimedOut` 并且不会执行闭包函数；键不存在时返回 `TimeoutError::KeyMissing`，锁已中毒时返回 `TimeoutError::Poisoned`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, TimeoutError};
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// Registry::register("busy", 0u16).unwrap();
+    ///
+    /// let (locked, wait_locked) = mpsc::channel();
+    /// let writer = thread::spawn(move || {
+    ///     Registry::<u16>::apply("busy", |v| {
+    ///         locked.send(()).unwrap();
+    ///         thread::sleep(Duration::from_millis(50));
+    ///         *v += 1;
+    ///     })
+    /// });
+    /// wait_locked.recv().unwrap();
+    ///
+    /// assert_eq!(
+    ///     Registry::<u16>::with_timeout("busy", Duration::from_millis(10), |v| *v),
+    ///     Err(TimeoutError::TimedOut)
+    /// );
+    /// assert_eq!(
+    ///     Registry::<u16>::with_timeout("busy", Duration::from_millis(500), |v| *v),
+    ///     Ok(1)
+    /// );
+    /// writer.join().unwrap();
+    ///
+    /// assert_eq!(
+    ///     Registry::<u16>::with_timeout("missing", Duration::from_millis(10), |v| *v),
+    ///     Err(TimeoutError::KeyMissing)
+    /// );
+    /// ```
+    pub fn with_timeout<R, F: FnOnce(&T) -> R>(
+        name: &str,
+        timeout: Duration,
+        func: F,
+    ) -> Result<R, TimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let slot = Self::_record_until(name, false, deadline)?;
+        let value = slot
+            .read_as::<T>(Wait::Until(deadline))
+            .map_err(TryAccessError::from)?;
+        let frame = ContextOperator::enter(Context::With(
+            intern(name),
+            TypeId::of::<T>(),
+            std::any::type_name::<T>(),
+        ));
+        let ret = func(&value);
+        drop(frame);
+        Ok(ret)
+    }
+
+    // 如果键存在，则执行闭包函数并返回其返回值；否则，将闭包函数原样返回
+    fn _with_or_return<R, F: FnOnce(&T) -> R>(name: &str, func: F) -> Result<R, F> {
+        let type_id = TypeId::of::<T>();
+        let Some((slot, _)) = Self::_record(name) else {
+            return Err(func);
+        };
+        check_deadlock!(ref T:name);
+        let Ok(value) = slot.read_as::<T>(Wait::Block) else {
+            return Err(func);
+        };
+        let frame = ContextOperator::enter(Context::With(
+            intern(name),
+            type_id,
+            std::any::type_name::<T>(),
+        ));
+        let ret = func(&value);
+        drop(frame);
+        Ok(ret)
+    }
+
+    /// 向注册表中的指定键应用一个只读函数；如果键不存在，则改为对给定的后备值应用该函数
+    ///
+    /// 不会注册任何值；对后备值应用闭包函数时不持有任何锁
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// struct Config {
+    ///     volume: u8,
+    /// }
+    ///
+    /// let fallback = Config { volume: 50 };
+    /// assert_eq!(Registry::<Config>::with_or("config", &fallback, |c| c.volume), 50);
+    /// assert!(!Registry::<Config>::exists("config"));
+    ///
+    /// Registry::register("config", Config { volume: 80 }).unwrap();
+    /// assert_eq!(Registry::<Config>::with_or("config", &fallback, |c| c.volume), 80);
+    /// ```
+    ///
+    /// 在同一键的 `apply` 闭包中调用会导致线程死锁：
+    ///
+    /// ```rust,should_panic
+    /// use gom::Registry;
+    ///
+    /// Registry::register("key", 1u16).unwrap();
+    /// Registry::<u16>::apply("key", |_| {
+    ///     Registry::<u16>::with_or("key", &0, |v| *v);
+    /// });
+    /// ```
+    pub fn with_or<R, F: FnOnce(&T) -> R>(name: &str, fallback: &T, func: F) -> R {
+        Self::_with_or_return(name, func).unwrap_or_else(|func| func(fallback))
+    }
+
+    /// 与 `with_or` 相同，但后备值仅在键不存在时才由 `fallback` 构造
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// let mut constructed = 0;
+    /// let len = Registry::<Vec<u8>>::with_or_else("buffer", || { constructed += 1; vec![0; 4] }, |v| v.len());
+    /// assert_eq!((len, constructed), (4, 1));
+    ///
+    /// Registry::register("buffer", vec![1u8, 2]).unwrap();
+    /// let len = Registry::<Vec<u8>>::with_or_else("buffer", || { constructed += 1; vec![0; 4] }, |v| v.len());
+    /// assert_eq!((len, constructed), (2, 1));
+    /// ```
+    pub fn with_or_else<R, D, F>(name: &str, fallback: D, func: F) -> R
+    where
+        D: FnOnce() -> T,
+        F: FnOnce(&T) -> R,
+    {
+        Self::_with_or_return(name, func).unwrap_or_else(|func| func(&fallback()))
+    }
+
+    /// 使用新值替换注册表中的指定键对应的值
+    ///
+    /// 如果键不存在，则返回 `None` 并且不会注册新值；否则，返回旧值。该类型已被冻结或锁已中毒时同样返回 `None` 并丢弃新值，
+    /// 需要区分失败的原因并取回新值时，使用 `try_replace` 或 `try_set`。
+    /// 替换在持有该键自身的写锁时于原有的条目中完成，条目的版本号、修改时间、句柄与 `Slot` 在替换前后保持有效
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::<i32>::register("my_key", 42);
+    /// assert_eq!(Registry::<i32>::replace("my_key", 64), Some(42));
+    /// assert_eq!(Registry::<i32>::replace("other_key", 32), None);
+    /// ```
+    ///
+    /// 替换前后条目保持不变：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("counter", 1u16).unwrap();
+    /// let handle = Registry::<u16>::handle("counter").unwrap();
+    /// let slot = Registry::<u16>::slot_of("counter").unwrap();
+    /// let version = Registry::<u16>::version("counter").unwrap();
+    ///
+    /// assert_eq!(Registry::<u16>::replace("counter", 2), Some(1));
+    /// assert_eq!(Registry::<u16>::version("counter"), Some(version + 1));
+    /// assert!(!handle.is_detached());
+    /// assert_eq!(handle.with(|v| *v), Some(2));
+    /// assert_eq!(Registry::<u16>::with_slot(slot, |v| *v), Some(2));
+    /// ```
+    ///
+    /// 替换时仅为新值分配内存：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::alloc::{GlobalAlloc, Layout, System};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// struct Counting;
+    ///
+    /// static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// unsafe impl GlobalAlloc for Counting {
+    ///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ///         ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+    ///         System.alloc(layout)
+    ///     }
+    ///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    ///         System.dealloc(ptr, layout)
+    ///     }
+    /// }
+    ///
+    /// #[global_allocator]
+    /// static GLOBAL: Counting = Counting;
+    ///
+    /// let name = "a.rather.long.key.that.would.need.a.heap.allocation";
+    /// Registry::register(name, 0u64).unwrap();
+    /// Registry::<u64>::replace(name, 1);
+    ///
+    /// let before = ALLOCATIONS.load(Ordering::SeqCst);
+    /// Registry::<u64>::replace(name, 2);
+    /// let after = ALLOCATIONS.load(Ordering::SeqCst);
+    /// // 仅新值的 `Box` 一次；启用 `deadlock-detection` 特性时还会在等待图中记录等待的键
+    /// #[cfg(not(feature = "deadlock-detection"))]
+    /// assert_eq!(after - before, 1);
+    /// ```
+    pub fn replace(name: &str, value: T) -> Option<T> {
+        Self::_replace(name, value).ok().flatten()
+    }
+
+    /// 与 `replace` 相同，但不会等待任何锁
+    ///
+    /// 成功时返回旧值；尚未初始化的值会先被初始化，再被替换。
+    /// 失败时返回的错误中包含未被写入的新值，其原因与 `try_apply` 相同；该函数同样不进行调试模式下的死锁检查
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, TryAccessError};
+    ///
+    /// Registry::register("level", 1u8).unwrap();
+    /// assert_eq!(Registry::<u8>::try_replace("level", 2), Ok(1));
+    ///
+    /// let err = Registry::<u8>::try_replace("missing", 3).unwrap_err();
+    /// assert_eq!(err.kind(), TryAccessError::KeyMissing);
+    /// assert_eq!(err.into_value(), 3);
+    ///
+    /// let inner = Registry::<u8>::with("level", |_| Registry::<u8>::try_replace("level", 4));
+    /// let err = inner.unwrap().unwrap_err();
+    /// assert_eq!(err.kind(), TryAccessError::WouldBlock);
+    /// assert_eq!(err.into_value(), 4);
+    /// assert_eq!(Registry::<u8>::get("level"), Some(2));
+    /// ```
+    pub fn try_replace(name: &str, value: T) -> Result<T, TryReplaceError<T>> {
+        let (mut old, on_remove) = loop {
+            let record = match Self::_try_record(name, true) {
+                Ok(record) => record,
+                Err(kind) => return Err(TryReplaceError::new(kind, value)),
+            };
+            #[cfg(feature = "arc-swap")]
+            if let Some(read_mostly) = record.read_mostly::<T>() {
+                let Some(_writer) = read_mostly.try_lock() else {
+                    return Err(TryReplaceError::new(TryAccessError::WouldBlock, value));
+                };
+                if !record.is_attached() {
+                    continue;
+                }
+                let old: Value = Box::new(read_mostly.swap(value));
+                record.touch();
+                break (old, record.take_on_remove());
+            }
+            let mut slot = match record.try_write() {
+                Ok(slot) => slot,
+                Err(e) => return Err(TryReplaceError::new(e.into(), value)),
+            };
+            // 查找与获取锁之间该条目已被移出注册表时，重新查找该键
+            if !record.is_attached() {
+                continue;
+            }
+            let old = std::mem::replace(&mut *slot, Box::new(value));
+            record.touch();
+            drop(slot);
+            break (old, record.take_on_remove());
+        };
+        if let Some((key, func)) = on_remove {
+            func(&key, &mut old);
+        }
+        Ok(*old
+            .downcast::<T>()
+            .expect("value type checked on registration"))
+    }
+
+    // 在原有的条目中替换指定键对应的值；如果该类型或键不存在、该类型已被冻结或锁已中毒，则连同原因原样返回新值
+    fn _replace(name: &str, value: T) -> Result<Option<T>, (RegistryError, T)> {
+        let type_id = TypeId::of::<T>();
+        let (mut old, poisoned, on_remove) = loop {
+            let record = {
+                let Ok(type_table) = _TABLE.get(type_id) else {
+                    return Err((registry_error!(Poisoned, name, T), value));
+                };
+                let Some(type_table) = type_table else {
+                    return Err((registry_error!(TypeNotRegistered, name, T), value));
+                };
+                let Some(type_table) = type_table.writable() else {
+                    return Err((registry_error!(Frozen, name, T), value));
+                };
+                let Ok(type_map) = type_table.read_shard(name) else {
+                    return Err((registry_error!(Poisoned, name, T), value));
+                };
+                let Some(record) = type_map.get(name) else {
+                    return Err((registry_error!(KeyNotFound, name, T), value));
+                };
+                Arc::clone(&record.data)
+            };
+            check_deadlock!(mut T:name;Lock::Key);
+            #[cfg(feature = "arc-swap")]
+            if let Some(read_mostly) = record.read_mostly::<T>() {
+                let _writer = read_mostly.lock();
+                if !record.is_attached() {
+                    continue;
+                }
+                let old: Value = Box::new(read_mostly.swap(value));
+                record.touch();
+                break (old, false, record.take_on_remove());
+            }
+            // 直接获取值自身的锁，尚未初始化的值不会被初始化，而是连同其初始化函数一起被替换
+            let (mut slot, poisoned) = match record.value.write() {
+                Ok(slot) => (slot, false),
+                Err(e) => (e.into_inner(), true),
+            };
+            // 查找与获取锁之间该条目已被移出注册表时，重新查找该键
+            if !record.is_attached() {
+                continue;
+            }
+            let old = std::mem::replace(&mut *slot, Box::new(value));
+            record.pending.store(false, Ordering::Release);
+            record.touch();
+            drop(slot);
+            if poisoned {
+                record.value.clear_poison();
+            }
+            break (old, poisoned, record.take_on_remove());
+        };
+        if let Some((key, func)) = on_remove {
+            func(&key, &mut old);
+        }
+        if poisoned {
+            return Ok(None);
+        }
+        Ok(old.downcast::<T>().ok().map(|old| *old))
+    }
+
+    /// 将注册表中的指定键设置为新值
+    ///
+    /// 无论键是否存在，操作结束后该键都对应新值；如果键已存在，则返回旧值，否则返回 `None`。
+    /// 键已存在时与 `replace` 相同，在原有的条目中替换值
+    ///
+    /// 如果键位于已被封存的前缀之下、该类型已被冻结或锁已中毒，则新值被丢弃并同样返回 `None`；
+    /// 需要区分失败与新建键的情况或取回新值时，使用 `try_set`
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// assert_eq!(Registry::<i32>::set("my_key", 42), None);
+    /// assert_eq!(Registry::<i32>::set("my_key", 64), Some(42));
+    /// assert_eq!(Registry::<i32>::with("my_key", |v| *v), Some(64));
+    /// ```
+    ///
+    /// 与其他线程的 `remove` 操作交替执行，每个值恰好被取回一次：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// let remover = thread::spawn(|| {
+    ///     let mut removed = Vec::new();
+    ///     while removed.last() != Some(&999) {
+    ///         if let Some(v) = Registry::<u32>::remove("slot") {
+    ///             removed.push(v);
+    ///         }
+    ///     }
+    ///     removed
+    /// });
+    /// let mut replaced = Vec::new();
+    /// for i in 0..1000 {
+    ///     if let Some(v) = Registry::<u32>::set("slot", i) {
+    ///         replaced.push(v);
+    ///     }
+    /// }
+    /// let mut all = remover.join().unwrap();
+    /// all.extend(replaced);
+    /// all.sort();
+    /// assert_eq!(all, (0..1000).collect::<Vec<_>>());
+    /// ```
+    pub fn set(name: &str, value: T) -> Option<T> {
+        Self::try_set(name, value).unwrap_or(None)
+    }
+
+    /// 与 `set` 相同，但失败时返回包含新值的 `RegisterError`
+    ///
+    /// 键位于已被封存的前缀之下时返回 `RegisterErrorKind::SealedNamespace`，该类型已被冻结时返回 `RegisterErrorKind::Frozen`，
+    /// 锁已中毒时返回 `RegisterErrorKind::Poisoned`；成功时与 `set` 相同，返回旧值或 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{seal_prefix, RegisterErrorKind, Registry};
+    ///
+    /// assert_eq!(Registry::<u32>::try_set("settings.volume", 3).ok(), Some(None));
+    /// assert_eq!(Registry::<u32>::try_set("settings.volume", 4).ok(), Some(Some(3)));
+    ///
+    /// seal_prefix("firmware");
+    /// let err = Registry::<u32>::try_set("firmware.version", 2).unwrap_err();
+    /// assert_eq!(err.kind(), RegisterErrorKind::SealedNamespace);
+    /// assert_eq!(err.into_value(), 2);
+    /// assert!(!Registry::<u32>::exists("firmware.version"));
+    /// ```
+    pub fn try_set(name: &str, value: T) -> Result<Option<T>, RegisterError<T>> {
+        let error = |kind, value| Err(RegisterError::new(name, kind, value));
+        if is_sealed(name) {
+            return error(RegisterErrorKind::SealedNamespace, value);
+        }
+        let value = match Self::_replace(name, value) {
+            Ok(old) => return Ok(old),
+            Err((RegistryError::Poisoned { .. }, value)) => {
+                return error(RegisterErrorKind::Poisoned, value)
+            }
+            Err((_, value)) => value,
+        };
+        let old = Self::_with_shard(name, value, |type_map, value| {
+            put_slot(type_map, name, Record::new(Box::new(value)))
+        })
+        .map_err(|(e, value)| match e {
+            RegistryError::WouldDeadlock { key, type_name } => {
+                thread_deadlock!("cannot write key `{key}` of type `{type_name}`")
+            }
+            RegistryError::Frozen { .. } => (RegisterErrorKind::Frozen, value),
+            _ => (RegisterErrorKind::Poisoned, value),
+        });
+        let old = match old {
+            Ok(old) => old,
+            Err((kind, value)) => return error(kind, value),
+        };
+        // 旧值仍被 `Handle` 共享或尚未初始化时，无法取回其所有权
+        let Some(old) = old.and_then(Record::into_inner) else {
+            return Ok(None);
+        };
+        let old = old.unwrap_or_else(|e| e.into_inner());
+        Ok(old.downcast::<T>().ok().map(|old| *old))
+    }
+
+    /// 临时覆盖指定键对应的值，并在闭包函数返回后恢复
+    ///
+    /// 如果键已存在，则在原有的锁中替换其值，持有该键 `Handle` 的代码同样会观察到临时值；否则，临时插入该键。
+    /// 闭包函数结束后（包括 panic 导致的栈展开），该键会被恢复为覆盖之前的状态：原值被放回，或者临时插入的键被移除。
+    /// 对同一键的嵌套覆盖按照后进先出的顺序恢复
+    ///
+    /// 如果该类型已被冻结、键不存在且位于已被封存的前缀之下或锁已中毒，则返回相应的错误，其中包含临时值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// #[derive(Debug)]
+    /// struct Config(&'static str);
+    ///
+    /// Registry::register("config", Config("real")).unwrap();
+    ///
+    /// let ret = Registry::with_override("config", Config("fake"), || {
+    ///     Registry::<Config>::with("config", |c| c.0)
+    /// });
+    /// assert_eq!(ret.unwrap(), Some("fake"));
+    /// assert_eq!(Registry::<Config>::with("config", |c| c.0), Some("real"));
+    ///
+    /// // 覆盖之前不存在的键，结束后该键被移除
+    /// let ret = Registry::with_override("other", Config("fake"), || {
+    ///     Registry::<Config>::exists("other")
+    /// });
+    /// assert_eq!(ret.unwrap(), true);
+    /// assert!(!Registry::<Config>::exists("other"));
+    ///
+    /// // 发生 panic 时同样会恢复
+    /// let result = std::panic::catch_unwind(|| {
+    ///     Registry::with_override("config", Config("fake"), || panic!("test failed"))
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(Registry::<Config>::with("config", |c| c.0), Some("real"));
+    ///
+    /// // 嵌套覆盖按照后进先出的顺序恢复
+    /// Registry::with_override("config", Config("outer"), || {
+    ///     Registry::with_override("config", Config("inner"), || {
+    ///         assert_eq!(Registry::<Config>::with("config", |c| c.0), Some("inner"));
+    ///     })
+    ///     .unwrap();
+    ///     assert_eq!(Registry::<Config>::with("config", |c| c.0), Some("outer"));
+    /// })
+    /// .unwrap();
+    /// assert_eq!(Registry::<Config>::with("config", |c| c.0), Some("real"));
+    /// ```
+    pub fn with_override<R, F: FnOnce() -> R>(
+        name: &str,
+        temp: T,
+        func: F,
+    ) -> Result<R, OverrideError<T>> {
+        let sealed = is_sealed(name);
+        let slot = {
+            let Some(type_table) = Self::_ensure_type(name) else {
+                return Err(OverrideError::Poisoned { temp });
+            };
+            if type_table.is_frozen() {
+                return Err(OverrideError::Frozen { temp });
+            }
+            check_deadlock!(mut T:name;Lock::TypeKey);
+            let Some(type_map) = type_table.write_shard_attached(name) else {
+                return Self::with_override(name, temp, func);
+            };
+            let Ok(mut type_map) = type_map else {
+                return Err(OverrideError::Poisoned { temp });
+            };
+            match type_map.get(name) {
+                Some(slot) => Arc::clone(&slot.data),
+                None if sealed => return Err(OverrideError::SealedNamespace { temp }),
+                None => {
+                    insert_slot(&mut type_map, intern(name), Box::new(temp));
+                    drop(type_map);
+                    let _guard = OverrideGuard::<T>::new(name, None);
+                    return Ok(func());
+                }
+            }
+        };
+        // 键已存在时，释放该类型对应的表的锁之后再获取该值自身的锁并交换值
+        check_deadlock!(mut T:name;Lock::Key);
+        #[cfg(feature = "arc-swap")]
+        if let Some(read_mostly) = slot.read_mostly::<T>() {
+            let writer = read_mostly.lock();
+            let previous: Value = Box::new(read_mostly.swap(temp));
+            slot.touch();
+            drop(writer);
+            let _guard = OverrideGuard::<T>::new(name, Some(previous));
+            return Ok(func());
+        }
+        let Ok(mut value) = slot.write() else {
+            return Err(OverrideError::Poisoned { temp });
+        };
+        let previous = std::mem::replace(&mut *value, Box::new(temp));
+        drop(value);
+        slot.touch();
+        let _guard = OverrideGuard::<T>::new(name, Some(previous));
+        Ok(func())
+    }
+
+    /// 将注册表中指定键对应的值移动到新键下
+    ///
+    /// 值连同其锁一起被移动；如果原键不存在或新键已存在，则不会修改注册表并返回相应的错误。
+    /// 原键与新键相同时，只要键存在即视为成功
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::{Registry, RenameError};
+    ///
+    /// Registry::<i32>::register("a", 42).unwrap();
+    /// Registry::<i32>::register("b", 64).unwrap();
+    ///
+    /// assert_eq!(Registry::<i32>::rename("a", "b"), Err(RenameError::DestinationExists));
+    /// assert_eq!(Registry::<i32>::rename("c", "d"), Err(RenameError::SourceMissing));
+    /// assert_eq!(Registry::<i32>::rename("a", "a"), Ok(()));
+    /// assert_eq!(Registry::<i32>::rename("a", "c"), Ok(()));
+    ///
+    /// assert!(!Registry::<i32>::exists("a"));
+    /// assert_eq!(Registry::<i32>::with("c", |v| *v), Some(42));
+    /// ```
+    ///
+    /// 与其他线程的 `with` 操作并发执行时，读取方要么在移动前读到完整的值，要么在移动后发现键不存在：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("old", vec![1, 2, 3]).unwrap();
+    ///
+    /// let reader = thread::spawn(|| {
+    ///     while let Some(v) = Registry::<Vec<i32>>::with("old", |v| v.clone()) {
+    ///         assert_eq!(v, vec![1, 2, 3]);
+    ///     }
+    /// });
+    /// Registry::<Vec<i32>>::rename("old", "new").unwrap();
+    /// reader.join().unwrap();
+    ///
+    /// assert_eq!(Registry::<Vec<i32>>::with("new", |v| v.clone()), Some(vec![1, 2, 3]));
+    /// ```
+    pub fn rename(old: &str, new: &str) -> Result<(), RenameError> {
+        let type_id = TypeId::of::<T>();
+        if old != new && is_sealed(new) {
+            return Err(RenameError::SealedNamespace);
+        }
+        let type_table = _TABLE
+            .get(type_id)
+            .map_err(|_| RenameError::Poisoned)?
+            .ok_or(RenameError::SourceMissing)?;
+        let type_map = type_table.writable().ok_or(RenameError::Frozen)?;
+        check_deadlock!(mut T:old;Lock::Type);
+        let mut type_map = type_map.write().map_err(|_| RenameError::Poisoned)?;
+        if !type_map.contains_key(old) {
+            return Err(RenameError::SourceMissing);
+        }
+        if old == new {
+            return Ok(());
+        }
+        if type_map.contains_key(new) {
+            return Err(RenameError::DestinationExists);
+        }
+        let value = type_map.remove(old).ok_or(RenameError::SourceMissing)?;
+        type_map.insert(intern(new), value.renamed(new));
+        notify_registered();
+        Ok(())
+    }
+
+    /// 交换注册表中两个键对应的值
+    ///
+    /// 交换在同一个写锁下完成，因而其他线程不会观察到中间状态；如果任一键不存在，则不会修改注册表并返回 `SwapError::Missing`
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::{Registry, SwapError};
+    ///
+    /// Registry::<&str>::register("front", "frame 1").unwrap();
+    /// Registry::<&str>::register("back", "frame 2").unwrap();
+    ///
+    /// Registry::<&str>::swap("front", "back").unwrap();
+    /// assert_eq!(Registry::<&str>::with("front", |v| *v), Some("frame 2"));
+    /// assert_eq!(Registry::<&str>::with("back", |v| *v), Some("frame 1"));
+    ///
+    /// assert_eq!(
+    ///     Registry::<&str>::swap("front", "other"),
+    ///     Err(SwapError::Missing("other".to_string()))
+    /// );
+    /// ```
+    ///
+    /// 读取方在反复交换期间始终只能观察到两个有效值之一：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("a", 1u8).unwrap();
+    /// Registry::register("b", 2u8).unwrap();
+    ///
+    /// let readers: Vec<_> = (0..4)
+    ///     .map(|_| {
+    ///         thread::spawn(|| {
+    ///             for _ in 0..1000 {
+    ///                 let a = Registry::<u8>::with("a", |v| *v).unwrap();
+    ///                 let b = Registry::<u8>::with("b", |v| *v).unwrap();
+    ///                 assert!(a == 1 || a == 2);
+    ///                 assert!(b == 1 || b == 2);
+    ///             }
+    ///         })
+    ///     })
+    ///     .collect();
+    /// for _ in 0..1000 {
+    ///     Registry::<u8>::swap("a", "b").unwrap();
+    /// }
+    /// for reader in readers {
+    ///     reader.join().unwrap();
+    /// }
+    /// assert_eq!(Registry::<u8>::with("a", |v| *v), Some(1));
+    /// ```
+    pub fn swap(a: &str, b: &str) -> Result<(), SwapError> {
+        let type_id = TypeId::of::<T>();
+        let type_table = _TABLE
+            .get(type_id)
+            .map_err(|_| SwapError::Poisoned)?
+            .ok_or_else(|| SwapError::Missing(String::from(a)))?;
+        let type_map = type_table.writable().ok_or(SwapError::Frozen)?;
+        check_deadlock!(mut T:a;Lock::Type);
+        let mut type_map = type_map.write().map_err(|_| SwapError::Poisoned)?;
+        for key in [a, b] {
+            if !type_map.contains_key(key) {
+                return Err(SwapError::Missing(String::from(key)));
+            }
+        }
+        if a == b {
+            return Ok(());
+        }
+        let value_a = type_map
+            .remove(a)
+            .ok_or_else(|| SwapError::Missing(String::from(a)))?;
+        let value_b = type_map
+            .remove(b)
+            .ok_or_else(|| SwapError::Missing(String::from(b)))?;
+        type_map.insert(intern(a), value_b.renamed(a));
+        type_map.insert(intern(b), value_a.renamed(b));
+        Ok(())
+    }
+
+    /// 将该类型下所有位于 `old` 前缀之下的键移动到 `new` 前缀之下
+    ///
+    /// 前缀的匹配规则与 `keys_with_prefix` 相同，新键由 `new` 替换原键中的 `old` 前缀得到；值连同其锁一起被移动。
+    /// 所有键在同一个写锁下移动：如果任一新键与不被移动的已有键冲突，则不会修改注册表并返回 `RenamePrefixError::DestinationExists`。
+    /// 返回被移动的键的数量
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{Registry, RenamePrefixError};
+    ///
+    /// Registry::register(".plugin.a", 1).unwrap();
+    /// Registry::register(".plugin.b.c", 2).unwrap();
+    /// Registry::register(".plugins", 3).unwrap();
+    ///
+    /// assert_eq!(Registry::<i32>::rename_prefix(".plugin", ".mnt.plugin"), Ok(2));
+    /// assert_eq!(Registry::<i32>::with(".mnt.plugin.a", |v| *v), Some(1));
+    /// assert_eq!(Registry::<i32>::with(".mnt.plugin.b.c", |v| *v), Some(2));
+    /// // 只匹配完整的段
+    /// assert_eq!(Registry::<i32>::with(".plugins", |v| *v), Some(3));
+    ///
+    /// // 新键冲突时不会移动任何键
+    /// Registry::register(".other.b.c", 4).unwrap();
+    /// assert_eq!(
+    ///     Registry::<i32>::rename_prefix(".mnt.plugin", ".other"),
+    ///     Err(RenamePrefixError::DestinationExists(".other.b.c".to_string()))
+    /// );
+    /// assert!(Registry::<i32>::exists(".mnt.plugin.a"));
+    /// assert!(!Registry::<i32>::exists(".other.a"));
+    /// assert_eq!(Registry::<i32>::with(".other.b.c", |v| *v), Some(4));
+    /// ```
+    pub fn rename_prefix(old: &str, new: &str) -> Result<usize, RenamePrefixError> {
+        let type_id = TypeId::of::<T>();
+        let Some(type_table) = _TABLE
+            .get(type_id)
+            .map_err(|_| RenamePrefixError::Poisoned)?
+        else {
+            return Ok(0);
+        };
+        let type_map = type_table.writable().ok_or(RenamePrefixError::Frozen)?;
+        check_deadlock!(mut T:old;Lock::Type);
+        let mut type_map = type_map.write().map_err(|_| RenamePrefixError::Poisoned)?;
+        let plan = plan_rename_prefix(&type_map, old, new)?;
+        Ok(apply_rename_prefix(&mut type_map, plan))
+    }
+
+    /// 与 `replace` 相同，但已弃用，请使用 `replace` 替代
+    #[deprecated(since = "0.1.6", note = "use `replace` instead")]
+    pub fn take(name: &str, value: T) -> Option<T> {
+        Self::replace(name, value)
+    }
+}
+
+impl<T: 'static + Send + Sync + Any + Clone> Registry<T> {
+    /// 以读多写少的方式向注册表中注册一个新值；需要启用 `arc-swap` 特性
+    ///
+    /// 值保存在 `ArcSwap` 中：`with` 与 `get` 仅加载当前值的 `Arc` 而不获取任何锁，因而不会被修改操作阻塞；
+    /// `apply` 复制当前值、在副本上执行闭包函数后整体替换，`replace` 与 `set` 直接替换为新值。
+    /// 同一个键上的修改彼此串行执行，不会丢失其他线程的修改；读取方在替换之前开始的访问仍看到旧值。
+    /// 同一类型下可以同时存在以两种方式注册的键
+    ///
+    /// 其余访问该值的函数（例如 `read_guard`、`write_guard`、`handle`、`try_with`、`entry` 与 `snapshot`）以相同的方式访问该键：
+    /// 只读访问加载当前值，修改在持有该键的修改互斥锁时于当前值的副本上进行，完成后整体替换。如果相同的键已存在，那么旧值将会被新值替换
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// #[derive(Clone)]
+    /// struct Config {
+    ///     retries: u32,
+    /// }
+    ///
+    /// Registry::register_read_mostly("config", Config { retries: 3 }).unwrap();
+    /// Registry::register("local", Config { retries: 0 }).unwrap();
+    ///
+    /// assert_eq!(Registry::<Config>::with("config", |c| c.retries), Some(3));
+    /// Registry::<Config>::apply("config", |c| c.retries += 1);
+    /// assert_eq!(Registry::<Config>::get("config").map(|c| c.retries), Some(4));
+    /// assert_eq!(Registry::<Config>::replace("config", Config { retries: 5 }).map(|c| c.retries), Some(4));
+    ///
+    /// // 两种方式注册的键互不影响
+    /// Registry::<Config>::apply("local", |c| c.retries += 1);
+    /// assert_eq!(Registry::<Config>::with("local", |c| c.retries), Some(1));
+    /// assert_eq!(Registry::<Config>::keys().len(), 2);
+    ///
+    /// assert_eq!(Registry::<Config>::remove("config").map(|c| c.retries), Some(5));
+    /// assert!(!Registry::<Config>::exists("config"));
+    /// ```
+    ///
+    /// 修改进行期间，其他线程的读取不会被阻塞：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// Registry::register_read_mostly("limit", 10u32).unwrap();
+    ///
+    /// let (started, wait_started) = mpsc::channel();
+    /// let (finish, wait_finish) = mpsc::channel::<()>();
+    /// let writer = thread::spawn(move || {
+    ///     Registry::<u32>::apply("limit", |v| {
+    ///         started.send(()).unwrap();
+    ///         wait_finish.recv_timeout(Duration::from_secs(10)).unwrap();
+    ///         *v = 20;
+    ///     })
+    /// });
+    /// wait_started.recv().unwrap();
+    /// // 修改尚未完成，读取立即返回旧值
+    /// assert_eq!(Registry::<u32>::with("limit", |v| *v), Some(10));
+    /// assert_eq!(Registry::<u32>::get("limit"), Some(10));
+    /// finish.send(()).unwrap();
+    /// writer.join().unwrap();
+    /// assert_eq!(Registry::<u32>::get("limit"), Some(20));
+    /// ```
+    ///
+    /// 大量替换期间，读取方总能看到完整的值；多个线程同时 `apply` 不会丢失修改：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::thread;
+    ///
+    /// Registry::register_read_mostly("pair", (0u64, 0u64)).unwrap();
+    /// Registry::register_read_mostly("counter", (0u64, 0u64)).unwrap();
+    ///
+    /// let done = AtomicBool::new(false);
+    /// thread::scope(|s| {
+    ///     s.spawn(|| {
+    ///         for i in 1..=10_000 {
+    ///             Registry::<(u64, u64)>::replace("pair", (i, i * 2));
+    ///         }
+    ///         done.store(true, Ordering::SeqCst);
+    ///     });
+    ///     for _ in 0..4 {
+    ///         s.spawn(|| {
+    ///             while !done.load(Ordering::SeqCst) {
+    ///                 let (a, b) = Registry::<(u64, u64)>::get("pair").unwrap();
+    ///                 assert_eq!(b, a * 2);
+    ///             }
+    ///         });
+    ///     }
+    ///     for _ in 0..8 {
+    ///         s.spawn(|| {
+    ///             for _ in 0..1000 {
+    ///                 Registry::<(u64, u64)>::apply("counter", |c| {
+    ///                     c.0 += 1;
+    ///                     c.1 += 2;
+    ///                 });
+    ///             }
+    ///         });
+    ///     }
+    /// });
+    /// assert_eq!(Registry::<(u64, u64)>::get("pair"), Some((10_000, 20_000)));
+    /// assert_eq!(Registry::<(u64, u64)>::get("counter"), Some((8000, 16_000)));
+    /// ```
+    #[cfg(feature = "arc-swap")]
+    #[allow(clippy::result_unit_err)]
+    pub fn register_read_mostly(name: &str, value: T) -> Result<(), ()> {
+        unchecked(
+            "write",
+            Self::_register_slot(name, Record::read_mostly(value)),
+        )
+        .map(|_| ())
+        .ok_or(())
+    }
+
+    /// 获取注册表中指定键对应的值的副本
+    ///
+    /// 如果键不存在或其对应的锁已中毒，则返回 `None`；其行为与 `with(name, |v| v.clone())` 相同
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("my_key", String::from("value")).unwrap();
+    /// assert_eq!(Registry::<String>::get("my_key"), Some(String::from("value")));
+    /// assert_eq!(Registry::<String>::get("other_key"), None);
+    ///
+    /// // 使指定键对应的锁中毒；启用 `parking_lot` 特性时锁不会中毒
+    /// let _ = thread::spawn(|| {
+    ///     Registry::<String>::apply("my_key", |_| panic!());
+    /// })
+    /// .join();
+    /// #[cfg(not(feature = "parking_lot"))]
+    /// assert_eq!(Registry::<String>::get("my_key"), None);
+    /// ```
+    pub fn get(name: &str) -> Option<T> {
+        let (slot, _) = Self::_record(name)?;
+        Self::_clone_record(name, &slot)
+    }
+
+    /// 与 `get` 相同，但在当前线程中缓存该值的副本
+    ///
+    /// 该值的版本号未改变时直接返回缓存的副本，仅需读取一次版本号，无需获取任何锁或查找类型与键；
+    /// 该值被修改、替换、移除或重命名后，下一次调用时重新获取该值。适用于被频繁读取而很少修改的值（如功能开关）。
+    /// 缓存持有所对应的条目，因而被移除的值在当前线程下一次以该键调用此函数之前不会被销毁
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("flags.dark_mode", false).unwrap();
+    /// assert_eq!(Registry::<bool>::cached_get("flags.dark_mode"), Some(false));
+    ///
+    /// thread::spawn(|| Registry::<bool>::set("flags.dark_mode", true))
+    ///     .join()
+    ///     .unwrap();
+    /// assert_eq!(Registry::<bool>::cached_get("flags.dark_mode"), Some(true));
+    ///
+    /// Registry::<bool>::remove("flags.dark_mode");
+    /// assert_eq!(Registry::<bool>::cached_get("flags.dark_mode"), None);
+    /// ```
+    pub fn cached_get(name: &str) -> Option<T> {
+        if let Some(value) = read_cache::lookup::<T>(name) {
+            return Some(value);
+        }
+        let Some((slot, _)) = Self::_record(name) else {
+            read_cache::evict::<T>(name);
+            return None;
+        };
+        let version = slot.version();
+        let value = Self::_clone_record(name, &slot)?;
+        read_cache::store(name, slot, version, value.clone());
+        Some(value)
+    }
+
+    fn _clone_record(name: &str, slot: &RecordData) -> Option<T> {
+        let type_id = TypeId::of::<T>();
+        #[cfg(feature = "arc-swap")]
+        if let Some(read_mostly) = slot.read_mostly::<T>() {
+            return Some(read_mostly.cloned());
+        }
+        check_deadlock!(ref T:name);
+        let value = slot.read().ok()?;
+        let var = value.downcast_ref::<T>()?;
+        let frame = ContextOperator::enter(Context::With(
+            intern(name),
+            type_id,
+            std::any::type_name::<T>(),
+        ));
+        let ret = Some(var.clone());
+        drop(frame);
+        ret
+    }
+
+    /// 复制注册表中指定键对应的值到新键下
+    ///
+    /// 克隆原值时仅持有原值自身的读锁，克隆完成后才在该类型对应的表的写锁下写入新键，因而其他线程不会观察到未完成的新值。如果新键已存在，则仅当 `overwrite` 为 `true`
+    /// 时替换其旧值，否则返回 `CopyError::DestinationExists`；原键与新键相同时返回 `CopyError::SameKey`。
+    /// 新键同样受到 `seal_prefix` 的限制
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{CopyError, Registry};
+    ///
+    /// #[derive(Clone)]
+    /// struct Enemy {
+    ///     health: u32,
+    /// }
+    ///
+    /// Registry::register("prototype.enemy", Enemy { health: 100 }).unwrap();
+    /// Registry::<Enemy>::copy("prototype.enemy", "enemy.1", false).unwrap();
+    ///
+    /// // 复制得到的值与原值相互独立
+    /// Registry::<Enemy>::apply("enemy.1", |e| e.health -= 30);
+    /// assert_eq!(Registry::<Enemy>::with("enemy.1", |e| e.health), Some(70));
+    /// assert_eq!(Registry::<Enemy>::with("prototype.enemy", |e| e.health), Some(100));
+    ///
+    /// assert_eq!(
+    ///     Registry::<Enemy>::copy("prototype.enemy", "enemy.1", false),
+    ///     Err(CopyError::DestinationExists)
+    /// );
+    /// assert_eq!(Registry::<Enemy>::with("enemy.1", |e| e.health), Some(70));
+    ///
+    /// Registry::<Enemy>::copy("prototype.enemy", "enemy.1", true).unwrap();
+    /// assert_eq!(Registry::<Enemy>::with("enemy.1", |e| e.health), Some(100));
+    ///
+    /// assert_eq!(Registry::<Enemy>::copy("enemy.1", "enemy.1", true), Err(CopyError::SameKey));
+    /// assert_eq!(Registry::<Enemy>::copy("enemy.2", "enemy.3", true), Err(CopyError::SourceMissing));
+    /// ```
+    pub fn copy(src: &str, dst: &str, overwrite: bool) -> Result<(), CopyError> {
+        if src == dst {
+            return Err(CopyError::SameKey);
+        }
+        if is_sealed(dst) {
+            return Err(CopyError::SealedNamespace);
+        }
+        let type_id = TypeId::of::<T>();
+        let slot = {
+            let type_table = _TABLE
+                .get(type_id)
+                .map_err(|_| CopyError::Poisoned)?
+                .ok_or(CopyError::SourceMissing)?;
+            let type_map = type_table.writable().ok_or(CopyError::Frozen)?;
+            let type_map = type_map.read_shard(src).map_err(|_| CopyError::Poisoned)?;
+            let slot = type_map.get(src).ok_or(CopyError::SourceMissing)?;
+            if !overwrite && type_map.contains_key(dst) {
+                return Err(CopyError::DestinationExists);
+            }
+            Arc::clone(&slot.data)
+        };
+        // 在释放该类型对应的表的锁之后复制原值，再获取写锁写入新键
+        check_deadlock!(ref T:src);
+        let clone = {
+            let value = slot.read_as::<T>(Wait::Block).map_err(|e| match e {
+                AccessError::Downcast => CopyError::SourceMissing,
+                _ => CopyError::Poisoned,
+            })?;
+            let frame = ContextOperator::enter(Context::With(
+                intern(src),
+                type_id,
+                std::any::type_name::<T>(),
+            ));
+            let clone = T::clone(&value);
+            drop(frame);
+            clone
+        };
+        let old = loop {
+            let type_table = _TABLE
+                .get(type_id)
+                .map_err(|_| CopyError::Poisoned)?
+                .ok_or(CopyError::SourceMissing)?;
+            let type_map = type_table.writable().ok_or(CopyError::Frozen)?;
+            check_deadlock!(mut T:dst;Lock::TypeKey);
+            let Some(type_map) = type_map.write_shard_attached(dst) else {
+                continue;
+            };
+            let mut type_map = type_map.map_err(|_| CopyError::Poisoned)?;
+            if !overwrite && type_map.contains_key(dst) {
+                return Err(CopyError::DestinationExists);
+            }
+            break insert_slot(&mut type_map, intern(dst), Box::new(clone));
+        };
+        // 被替换的旧值在释放锁之后销毁
+        drop(old);
+        Ok(())
+    }
+
+    /// 获取该类型下所有键与指定正则表达式匹配的键值对的副本
+    ///
+    /// 仅涉及读锁；锁已中毒的条目将被跳过。需要启用 `regex` 特性
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use regex::Regex;
+    ///
+    /// Registry::register(".user.1001", String::from("alice")).unwrap();
+    /// Registry::register(".user.admin", String::from("root")).unwrap();
+    ///
+    /// let values = Registry::<String>::values_regex(&Regex::new(r"\.\d+$").unwrap());
+    /// assert_eq!(values, vec![(".user.1001".to_string(), "alice".to_string())]);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn values_regex(re: &regex::Regex) -> Vec<(String, T)> {
+        let Some(records) = Self::_records(|name| re.is_match(name)) else {
+            return Vec::new();
+        };
+        check_deadlock!(ref T);
+        let mut ret: Vec<_> = records
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let value = value.read_as::<T>(Wait::Block).ok()?;
+                let var = T::clone(&value);
+                Some((name.to_string(), var))
+            })
+            .collect();
+        order_by_key(&mut ret, |(name, _)| name);
+        ret
+    }
+
+    /// 仅当指定键对应的值等于期望值时，使用新值替换该值
+    ///
+    /// 比较与替换在该键的写锁下完成；如果当前值与期望值不相等，则返回当前值的副本与被拒绝的新值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::{CasError, Registry};
+    ///
+    /// Registry::register("my_key", 42).unwrap();
+    /// assert_eq!(Registry::<i32>::compare_and_swap("my_key", &42, 64), Ok(()));
+    /// assert_eq!(
+    ///     Registry::<i32>::compare_and_swap("my_key", &42, 100),
+    ///     Err(CasError::Mismatch { current: 64, new: 100 })
+    /// );
+    /// assert_eq!(
+    ///     Registry::<i32>::compare_and_swap("other_key", &42, 100),
+    ///     Err(CasError::Missing { new: 100 })
+    /// );
+    /// ```
+    ///
+    /// 多个线程通过 CAS 循环递增同一个计数器：
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("counter", 0u64).unwrap();
+    ///
+    /// let workers: Vec<_> = (0..4)
+    ///     .map(|_| {
+    ///         thread::spawn(|| {
+    ///             for _ in 0..250 {
+    ///                 let mut current = Registry::<u64>::get("counter").unwrap();
+    ///                 while let Err(e) = Registry::<u64>::compare_and_swap("counter", &current, current + 1) {
+    ///                     current = match e {
+    ///                         gom::CasError::Mismatch { current, .. } => current,
+    ///                         e => panic!("{}", e),
+    ///                     };
+    ///                 }
+    ///             }
+    ///         })
+    ///     })
+    ///     .collect();
+    /// for worker in workers {
+    ///     worker.join().unwrap();
+    /// }
+    /// assert_eq!(Registry::<u64>::get("counter"), Some(1000));
+    /// ```
+    pub fn compare_and_swap(name: &str, expected: &T, new: T) -> Result<(), CasError<T>>
+    where
+        T: PartialEq,
+    {
+        let type_id = TypeId::of::<T>();
+        let slot = {
+            let Ok(type_table) = _TABLE.get(type_id) else {
+                return Err(CasError::Poisoned { new });
+            };
+            let Some(type_table) = type_table else {
+                return Err(CasError::Missing { new });
+            };
+            if type_table.is_frozen() {
+                return Err(CasError::Frozen { new });
+            }
+            let Ok(type_map) = type_table.read_shard(name) else {
+                return Err(CasError::Poisoned { new });
+            };
+            let Some(slot) = type_map.get(name) else {
+                return Err(CasError::Missing { new });
+            };
+            Arc::clone(&slot.data)
+        };
+        check_deadlock!(mut T:name;Lock::Key);
+        let mut value = match slot.write_as::<T>(Wait::Block) {
+            Ok(value) => value,
+            Err(AccessError::Downcast) => return Err(CasError::Missing { new }),
+            Err(_) => return Err(CasError::Poisoned { new }),
+        };
+        if *value != *expected {
+            return Err(CasError::Mismatch {
+                current: value.clone(),
+                new,
+            });
+        }
+        *value = new;
+        value.commit();
+        Ok(())
+    }
+
+    /// 读取指定键对应的值，并根据闭包函数的返回值决定是否写入新值
+    ///
+    /// 与 `AtomicUsize::fetch_update` 类似：闭包函数返回 `Some(新值)` 时写入新值并返回 `Ok(旧值)`，返回 `None` 时放弃写入并返回 `Err(当前值)`。
+    /// 整个过程在该键的写锁下完成；如果键不存在，则返回 `None`。
+    /// 闭包函数发生 panic 时，原值保持不变且锁不会中毒
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("counter", 7u32).unwrap();
+    ///
+    /// assert_eq!(Registry::<u32>::fetch_update("counter", |v| Some(v + 1)), Some(Ok(7)));
+    /// assert_eq!(Registry::<u32>::fetch_update("counter", |v| v.checked_sub(10)), Some(Err(8)));
+    /// assert_eq!(Registry::<u32>::fetch_update("other", |v| Some(v + 1)), None);
+    ///
+    /// let result = std::panic::catch_unwind(|| {
+    ///     Registry::<u32>::fetch_update("counter", |_| panic!("update failed"))
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(Registry::<u32>::get("counter"), Some(8));
+    /// ```
+    pub fn fetch_update<F: FnMut(&T) -> Option<T>>(name: &str, mut f: F) -> Option<Result<T, T>> {
+        let type_id = TypeId::of::<T>();
+        let slot = Self::_writable_record(name)?;
+        check_deadlock!(mut T:name;Lock::Key);
+        let mut value = slot.write_as::<T>(Wait::Block).ok()?;
+        let frame = ContextOperator::enter(Context::Apply(
+            intern(name),
+            type_id,
+            std::any::type_name::<T>(),
+        ));
+        let new = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(&value)));
+        drop(frame);
+        let new = match new {
+            Ok(new) => new,
+            Err(e) => {
+                // 闭包函数只能读取值，因此释放锁时无需使其中毒
+                drop(value);
+                std::panic::resume_unwind(e);
+            }
+        };
+        match new {
+            Some(new) => {
+                let old = std::mem::replace(&mut *value, new);
+                value.commit();
+                Some(Ok(old))
+            }
+            None => Some(Err(T::clone(&value))),
+        }
+    }
+
+    /// 获取注册表中指定键对应的值的副本，如果键不存在，则先注册 `T::default()`
+    ///
+    /// 检查与注册是原子的：多个线程同时访问同一个不存在的键时，`T::default()` 只会被调用一次
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::thread;
+    ///
+    /// static CONSTRUCTED: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// #[derive(Clone)]
+    /// struct Counter(u64);
+    ///
+    /// impl Default for Counter {
+    ///     fn default() -> Self {
+    ///         CONSTRUCTED.fetch_add(1, Ordering::SeqCst);
+    ///         Counter(0)
+    ///     }
+    /// }
+    ///
+    /// let workers: Vec<_> = (0..8)
+    ///     .map(|_| {
+    ///         thread::spawn(|| {
+    ///             for _ in 0..100 {
+    ///                 Registry::<Counter>::apply_or_default("counter", |c| c.0 += 1);
+    ///                 Registry::<Counter>::get_or_default("counter");
+    ///             }
+    ///         })
+    ///     })
+    ///     .collect();
+    /// for worker in workers {
+    ///     worker.join().unwrap();
+    /// }
+    /// assert_eq!(CONSTRUCTED.load(Ordering::SeqCst), 1);
+    /// assert_eq!(Registry::<Counter>::get_or_default("counter").map(|c| c.0), Some(800));
+    /// ```
+    pub fn get_or_default(name: &str) -> Option<T>
+    where
+        T: Default,
+    {
+        Self::get_or_register_with(name, T::default, |v| v.clone())
+    }
+
+    fn _snapshot() -> Option<Vec<(String, T)>> {
+        let records = Self::_records(|_| true)?;
+        check_deadlock!(ref T);
+        let mut ret: Vec<_> = records
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let value = value.read_as::<T>(Wait::Block).ok()?;
+                let var = T::clone(&value);
+                Some((key.to_string(), var))
+            })
+            .collect();
+        order_by_key(&mut ret, |(key, _)| key);
+        Some(ret)
+    }
+
+    /// 获取注册表中该类型下所有键值对的副本
+    ///
+    /// 返回的 `Vec` 中的元素顺序不确定（启用 `ordered` 特性时按键的字典序排列），调用结束后不持有任何锁；锁已中毒的条目将被跳过
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("a", vec![1]).unwrap();
+    /// Registry::register("b", vec![2, 3]).unwrap();
+    /// Registry::register("c", Vec::<i32>::new()).unwrap();
+    ///
+    /// let mut snapshot = Registry::<Vec<i32>>::snapshot();
+    /// snapshot.sort();
+    /// assert_eq!(
+    ///     snapshot,
+    ///     vec![
+    ///         ("a".to_string(), vec![1]),
+    ///         ("b".to_string(), vec![2, 3]),
+    ///         ("c".to_string(), vec![]),
+    ///     ]
+    /// );
+    /// ```
+    pub fn snapshot() -> Vec<(String, T)> {
+        Self::_snapshot().unwrap_or_default()
+    }
+}
+
+impl<T: 'static + Send + Sync + Any + Numeric> Registry<T> {
+    fn _fetch_with<F: FnOnce(T) -> T>(name: &str, func: F) -> Option<T> {
+        Self::apply(name, |v| {
+            let old = *v;
+            *v = func(old);
+            old
+        })
+    }
+
+    /// 将指定键对应的值加上 `delta`，并返回旧值
+    ///
+    /// 如果键不存在，则返回 `None` 并且不会注册新值；整数在溢出时回绕
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    /// use std::thread;
+    ///
+    /// Registry::register("counter", 0i64).unwrap();
+    ///
+    /// let workers: Vec<_> = (0..8)
+    ///     .map(|_| {
+    ///         thread::spawn(|| {
+    ///             for _ in 0..1000 {
+    ///                 Registry::<i64>::fetch_add("counter", 1).unwrap();
+    ///             }
+    ///         })
+    ///     })
+    ///     .collect();
+    /// for worker in workers {
+    ///     worker.join().unwrap();
+    /// }
+    /// assert_eq!(Registry::<i64>::fetch_add("counter", 0), Some(8000));
+    /// assert_eq!(Registry::<i64>::fetch_add("other", 1), None);
+    /// assert!(!Registry::<i64>::exists("other"));
+    /// ```
+    pub fn fetch_add(name: &str, delta: T) -> Option<T> {
+        Self::_fetch_with(name, |v| v.add(delta))
+    }
+
+    /// 将指定键对应的值减去 `delta`，并返回旧值
+    ///
+    /// 如果键不存在，则返回 `None` 并且不会注册新值；整数在溢出时回绕
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("stock", 10u32).unwrap();
+    /// assert_eq!(Registry::<u32>::fetch_sub("stock", 3), Some(10));
+    /// assert_eq!(Registry::<u32>::fetch_sub("stock", 8), Some(7));
+    /// assert_eq!(Registry::<u32>::with("stock", |v| *v), Some(u32::MAX));
+    /// ```
+    pub fn fetch_sub(name: &str, delta: T) -> Option<T> {
+        Self::_fetch_with(name, |v| v.sub(delta))
+    }
+
+    /// 将指定键对应的值设为其与 `value` 中的较大者，并返回旧值
+    ///
+    /// 如果键不存在，则返回 `None` 并且不会注册新值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("peak", 1.5f64).unwrap();
+    /// assert_eq!(Registry::<f64>::fetch_max("peak", 2.5), Some(1.5));
+    /// assert_eq!(Registry::<f64>::fetch_max("peak", 0.5), Some(2.5));
+    /// assert_eq!(Registry::<f64>::with("peak", |v| *v), Some(2.5));
+    /// ```
+    pub fn fetch_max(name: &str, value: T) -> Option<T> {
+        Self::_fetch_with(name, |v| if value > v { value } else { v })
+    }
+
+    /// 将指定键对应的值设为其与 `value` 中的较小者，并返回旧值
+    ///
+    /// 如果键不存在，则返回 `None` 并且不会注册新值
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use gom::Registry;
+    ///
+    /// Registry::register("low", 5i8).unwrap();
+    /// assert_eq!(Registry::<i8>::fetch_min("low", -3), Some(5));
+    /// assert_eq!(Registry::<i8>::fetch_min("low", 0), Some(-3));
+    /// ```
+    pub fn fetch_min(name: &str, value: T) -> Option<T> {
+        Self::_fetch_with(name, |v| if value < v { value } else { v })
+    }
+}
+
+/// `Registry::extend` 的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExtendReport {
+    /// 新建的键的数量
+    pub inserted: usize,
+    /// 旧值被替换的键的数量
+    pub replaced: usize,
+    /// 位于已被封存的前缀之下而未被注册的键的数量
+    pub sealed: usize,
+}
+
+/// `Registry::register_many` 遇到已存在的键时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// 使用新值替换旧值
+    Overwrite,
+    /// 保留旧值并丢弃新值
+    Skip,
+    /// 放弃整个操作，不注册任何值
+    Fail,
+}
+
+/// `Registry::register_many` 的结果
+#[derive(Debug)]
+pub struct RegisterManyReport<T> {
+    /// 新建的键
+    pub inserted: Vec<String>,
+    /// 旧值被替换的键
+    pub replaced: Vec<String>,
+    /// 因冲突而未注册的键
+    pub skipped: Vec<String>,
+    /// 操作被放弃时未被注册的值
+    pub remainder: Vec<(String, T)>,
+}
+
+impl<T> Default for RegisterManyReport<T> {
+    fn default() -> Self {
+        Self {
+            inserted: Vec::new(),
+            replaced: Vec::new(),
+            skipped: Vec::new(),
+            remainder: Vec::new(),
+        }
+    }
+}
+
+/// `Registry::drain_with_skipped` 与 `Registry::take_map_with_skipped` 移除了但无法取回其值的条目，按原因记录其键
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SkippedEntries {
+    /// 锁已中毒的键
+    pub poisoned: Vec<String>,
+    /// 值仍被 `Handle` 或守卫共享的键
+    pub shared: Vec<String>,
+    /// 值的类型不符的键
+    pub mismatched: Vec<String>,
+}
+
+impl SkippedEntries {
+    /// 被跳过的条目总数
+    pub fn len(&self) -> usize {
+        self.poisoned.len() + self.shared.len() + self.mismatched.len()
+    }
+
+    /// 是否没有条目被跳过
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 取回已被移除的条目的值；无法取回时记录其键并返回 `None`
+    fn take<T: 'static>(&mut self, name: &str, slot: Record) -> Option<T> {
+        let skipped = match slot.into_inner() {
+            Some(Ok(value)) => match value.downcast::<T>() {
+                Ok(value) => return Some(*value),
+                Err(_) => &mut self.mismatched,
+            },
+            Some(Err(_)) => &mut self.poisoned,
+            None => &mut self.shared,
+        };
+        skipped.push(name.to_string());
+        None
+    }
+}
+
+/// `Registry::stats` 的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BucketStats {
+    /// 该类型下已注册的键的数量
+    pub len: usize,
+    /// 该类型对应的表在不重新分配内存的情况下可容纳的键的数量
+    pub capacity: usize,
+    /// 锁已中毒的值的数量
+    pub poisoned_entries: usize,
+}
+
+/// `Registry::with_upgrade` 中只读闭包函数的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upgrade<R, W> {
+    /// 无需修改，直接返回给定的值
+    Done(R),
+    /// 需要修改，获取写锁后执行给定的闭包函数
+    Write(W),
+}
+
+/// 针对于线程局部变量的注册表
+pub struct LocalRegistry<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T: 'static> LocalRegistry<T> {
+    /// 向注册表中注册一个新值
+    ///
+    /// 如果相同的键已存在，那么旧值将会被新值替换
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::LocalRegistry;
+    ///
+    /// LocalRegistry::<i32>::register("my_key", 42);
+    /// ```
+    pub fn register(name: &str, value: T) {
+        let type_id = TypeId::of::<T>();
+        _LOCAL_TABLE.with_borrow_mut(|table| {
+            let type_map = table.entry(type_id).or_default();
+            type_map.insert(String::from(name), Box::new(value));
+        })
+    }
+
+    /// 从注册表中移除指定键对应的值
+    ///
+    /// 如果键不存在，则返回 `None`
+    ///
+    /// # 示例
+    /// ```rust
     /// use gom::LocalRegistry;
     ///
     /// LocalRegistry::<i32>::register("my_key", 42);
@@ -342,89 +6836,1049 @@ impl<T: 'static> LocalRegistry<T> {
         Some(*value)
     }
 
-    /// 判断指定键是否存在于注册表中
-    ///
-    /// # 示例
-    /// ```rust
-    /// use gom::LocalRegistry;
-    ///
-    /// LocalRegistry::<i32>::register("my_key", 42);
-    /// assert_eq!(LocalRegistry::<i32>::exists("my_key"), true);
-    /// assert_eq!(LocalRegistry::<i32>::exists("other_key"), false);
-    /// ```
-    pub fn exists(name: &str) -> bool {
-        let type_id = TypeId::of::<T>();
-        _LOCAL_TABLE.with_borrow(|table| {
-            let type_map = table.get(&type_id).unwrap();
-            type_map.contains_key(name)
-        })
+    /// 判断指定键是否存在于注册表中
+    ///
+    /// 当前线程从未注册过该类型的值时同样返回 `false`
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::LocalRegistry;
+    ///
+    /// LocalRegistry::<i32>::register("my_key", 42);
+    /// assert_eq!(LocalRegistry::<i32>::exists("my_key"), true);
+    /// assert_eq!(LocalRegistry::<i32>::exists("other_key"), false);
+    /// assert_eq!(LocalRegistry::<u128>::exists("never"), false);
+    /// ```
+    pub fn exists(name: &str) -> bool {
+        let type_id = TypeId::of::<T>();
+        _LOCAL_TABLE.with_borrow(|table| {
+            table
+                .get(&type_id)
+                .is_some_and(|type_map| type_map.contains_key(name))
+        })
+    }
+
+    /// 向注册表中的指定键应用一个函数，该函数可以修改注册表中的值
+    ///
+    /// 如果键不存在，则返回 `None`；否则，返回闭包函数的返回值
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::LocalRegistry;
+    ///
+    /// LocalRegistry::register("my_key", 42);
+    /// assert_eq!(LocalRegistry::<i32>::apply("my_key", |v| { *v += 1; *v }), Some(43));
+    /// assert_eq!(LocalRegistry::<i32>::apply("other_key", |v| *v += 1), None);
+    /// ```
+    pub fn apply<R, F: FnOnce(&mut T) -> R>(name: &str, func: F) -> Option<R> {
+        let type_id = TypeId::of::<T>();
+        _LOCAL_TABLE.with_borrow_mut(|table| {
+            let type_map = table.get_mut(&type_id)?;
+            let value = type_map.get_mut(name)?;
+            let value = value.downcast_mut::<T>()?;
+            Some(func(value))
+        })
+    }
+
+    /// 向注册表中的指定键应用一个函数，该函数仅能读取注册表中的值
+    ///
+    /// 如果键不存在，则返回 `None`；否则，返回闭包函数的返回值
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::LocalRegistry;
+    ///
+    /// LocalRegistry::<i32>::register("my_key", 42);
+    /// assert_eq!(LocalRegistry::<i32>::with("my_key", |v| *v), Some(42));
+    /// assert_eq!(LocalRegistry::<i32>::with("other_key", |v| *v), None);
+    /// ```
+    pub fn with<R, F: FnOnce(&T) -> R>(name: &str, func: F) -> Option<R> {
+        let type_id = TypeId::of::<T>();
+        _LOCAL_TABLE.with_borrow(|table| {
+            let type_map = table.get(&type_id)?;
+            let value = type_map.get(name)?;
+            let value = value.downcast_ref::<T>()?;
+            Some(func(value))
+        })
+    }
+
+    /// 使用新值替换注册表中的指定键对应的值
+    ///
+    /// 如果键不存在，则返回 `None` 并且不会注册新值；否则，返回旧值
+    ///
+    /// # 示例
+    /// ```rust
+    /// use gom::LocalRegistry;
+    ///
+    /// LocalRegistry::<i32>::register("my_key", 42);
+    /// assert_eq!(LocalRegistry::<i32>::replace("my_key", 64), Some(42));
+    /// assert_eq!(LocalRegistry::<i32>::replace("other_key", 32), None);
+    /// assert_eq!(LocalRegistry::<i32>::exists("other_key"), false);
+    /// ```
+    pub fn replace(name: &str, value: T) -> Option<T> {
+        let type_id = TypeId::of::<T>();
+        _LOCAL_TABLE.with_borrow_mut(|table| {
+            let type_map = table.get_mut(&type_id)?;
+            let old = type_map.get_mut(name)?.downcast_mut::<T>()?;
+            Some(std::mem::replace(old, value))
+        })
+    }
+}
+
+/// 判断指定键是否以任意类型存在于注册表中
+///
+/// 仅涉及读锁，因此可以在 `with` 闭包中调用
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{exists_any, Registry};
+///
+/// assert!(!exists_any("my_key"));
+///
+/// Registry::register("my_key", 42i32).unwrap();
+/// Registry::register("my_key", String::from("value")).unwrap();
+/// assert!(exists_any("my_key"));
+///
+/// Registry::<i32>::remove("my_key");
+/// assert!(exists_any("my_key"));
+/// Registry::<String>::with("my_key", |_| {
+///     assert!(exists_any("my_key"));
+/// });
+///
+/// Registry::<String>::remove("my_key");
+/// assert!(!exists_any("my_key"));
+/// ```
+pub fn exists_any(name: &str) -> bool {
+    let Ok(tables) = _TABLE.tables() else {
+        return false;
+    };
+    tables
+        .iter()
+        .any(|(_, type_map)| match type_map.read_shard(name) {
+            Ok(type_map) => type_map.contains_key(name),
+            Err(_) => false,
+        })
+}
+
+/// 获取指定键下已注册的所有类型
+///
+/// 返回每个类型的 `TypeId` 及其类型名称（由 `std::any::type_name` 给出）；返回的 `Vec` 中的元素顺序不确定
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{types_of, Registry};
+/// use std::any::{type_name, TypeId};
+///
+/// struct Position(f32, f32);
+/// struct Velocity(f32, f32);
+///
+/// Registry::register("entity", Position(0.0, 0.0)).unwrap();
+/// Registry::register("entity", Velocity(1.0, 0.0)).unwrap();
+/// Registry::register("entity", String::from("player")).unwrap();
+///
+/// let mut names: Vec<_> = types_of("entity").into_iter().map(|(_, name)| name).collect();
+/// names.sort();
+/// let mut expected = vec![
+///     type_name::<Position>(),
+///     type_name::<Velocity>(),
+///     type_name::<String>(),
+/// ];
+/// expected.sort();
+/// assert_eq!(names, expected);
+///
+/// assert!(types_of("entity").contains(&(TypeId::of::<String>(), type_name::<String>())));
+/// assert!(types_of("other").is_empty());
+/// ```
+pub fn types_of(name: &str) -> Vec<(TypeId, &'static str)> {
+    let Ok(tables) = _TABLE.tables() else {
+        return Vec::new();
+    };
+    tables
+        .iter()
+        .filter(|(_, type_table)| match type_table.read_shard(name) {
+            Ok(type_map) => type_map.contains_key(name),
+            Err(_) => false,
+        })
+        .map(|(type_id, type_table)| (*type_id, type_table.type_name))
+        .collect()
+}
+
+/// 获取所有类型下位于指定前缀之下的键
+///
+/// 返回每个键及其所属类型的名称；前缀的匹配规则与 `Registry::keys_with_prefix` 相同
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{keys_with_prefix_any, Registry};
+///
+/// Registry::register(".app.name", String::from("gom")).unwrap();
+/// Registry::register(".app.version", 7u32).unwrap();
+/// Registry::register(".application.name", String::from("other")).unwrap();
+///
+/// let mut keys = keys_with_prefix_any(".app");
+/// keys.sort();
+/// assert_eq!(
+///     keys,
+///     vec![
+///         ("alloc::string::String", ".app.name".to_string()),
+///         ("u32", ".app.version".to_string()),
+///     ]
+/// );
+/// ```
+pub fn keys_with_prefix_any(prefix: &str) -> Vec<(&'static str, String)> {
+    let Ok(tables) = _TABLE.tables() else {
+        return Vec::new();
+    };
+    let mut ret = Vec::new();
+    for (_, type_table) in &tables {
+        let Ok(type_map) = type_table.read() else {
+            continue;
+        };
+        ret.extend(
+            type_map
+                .keys()
+                .filter(|name| has_prefix(name, prefix))
+                .map(|name| (type_table.type_name, name.to_string())),
+        );
+    }
+    // 同一个键下的不同类型按类型名称排列
+    #[cfg(feature = "ordered")]
+    ret.sort_unstable_by(|a, b| (&a.1, a.0).cmp(&(&b.1, b.0)));
+    ret
+}
+
+// 判断键是否位于已被封存的前缀之下
+fn is_sealed(name: &str) -> bool {
+    let sealed = _SEALED.read().unwrap_or_else(|e| e.into_inner());
+    sealed.iter().any(|prefix| has_prefix(name, prefix))
+}
+
+/// 封存指定前缀，禁止此后在该前缀之下注册新的键
+///
+/// 封存后，对于位于该前缀之下的键（匹配规则与 `Registry::keys_with_prefix` 相同），`register`、`set`、`try_register`、
+/// `register_boxed`、`register_many`、`extend`、`get_or_register_with`、`entry` 等注册操作，以及以其为目标的 `rename`、
+/// `rename_prefix` 与 `rename_prefix_all` 都将失败：返回 `Result` 的操作返回对应错误类型的 `SealedNamespace` 变体，
+/// `register_many` 与 `extend` 在其报告中记录未被注册的键，其余操作返回 `None`；
+/// 封存之前已注册的键不受影响，仍可被读取、通过 `apply` 修改以及移除。重复封存同一前缀不会产生任何效果
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{seal_prefix, RegisterBoxedError, RegisterErrorKind, Registry};
+/// use std::any::{type_name, TypeId};
+///
+/// Registry::register(".core.renderer", 1i32).unwrap();
+/// Registry::register(".core.audio", 2i32).unwrap();
+/// seal_prefix(".core");
+///
+/// assert_eq!(Registry::register(".core.input", 3i32), Err(()));
+/// assert_eq!(Registry::<i32>::set(".core.renderer", 4), None);
+/// assert_eq!(
+///     Registry::try_register(".core.input", 3i32).unwrap_err().kind(),
+///     RegisterErrorKind::SealedNamespace
+/// );
+/// assert_eq!(
+///     gom::register_boxed(TypeId::of::<i32>(), type_name::<i32>(), ".core.input", Box::new(3i32)),
+///     Err(RegisterBoxedError::SealedNamespace)
+/// );
+///
+/// // 前缀只能匹配完整的段
+/// Registry::register(".coreutils", 5i32).unwrap();
+/// Registry::register(".plugin.core", 6i32).unwrap();
+///
+/// // 封存之前已注册的键仍可访问、修改与移除
+/// assert_eq!(Registry::<i32>::apply(".core.renderer", |v| { *v += 10; *v }), Some(11));
+/// assert_eq!(Registry::<i32>::remove(".core.audio"), Some(2));
+/// assert!(!Registry::<i32>::exists(".core.input"));
+/// ```
+pub fn seal_prefix(prefix: &str) {
+    let mut sealed = _SEALED.write().unwrap_or_else(|e| e.into_inner());
+    if !sealed.iter().any(|p| p == prefix) {
+        sealed.push(String::from(prefix));
+    }
+}
+
+/// 解除对指定前缀的封存，仅在启用 `test-util` 特性时可用
+///
+/// 返回该前缀此前是否已被封存
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{seal_prefix, unseal_prefix, Registry};
+///
+/// seal_prefix(".core");
+/// assert_eq!(Registry::register(".core.input", 1i32), Err(()));
+///
+/// assert!(unseal_prefix(".core"));
+/// assert!(!unseal_prefix(".core"));
+/// assert_eq!(Registry::register(".core.input", 1i32), Ok(()));
+/// ```
+#[cfg(feature = "test-util")]
+pub fn unseal_prefix(prefix: &str) -> bool {
+    let mut sealed = _SEALED.write().unwrap_or_else(|e| e.into_inner());
+    let len = sealed.len();
+    sealed.retain(|p| p != prefix);
+    sealed.len() != len
+}
+
+/// 从注册表中移除所有类型下位于指定前缀之下的值
+///
+/// 返回被移除的值的数量；前缀的匹配规则与 `Registry::keys_with_prefix` 相同，被移除的值会在释放锁之后被销毁
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{remove_prefix_all, Registry};
+///
+/// Registry::register(".plugin.a", 1i32).unwrap();
+/// Registry::register(".plugin.a", String::from("a")).unwrap();
+/// Registry::register(".plugin.b.c", 2.0f64).unwrap();
+/// Registry::register(".plugins", 3i32).unwrap();
+///
+/// assert_eq!(remove_prefix_all(".plugin"), 3);
+/// assert!(!Registry::<String>::exists(".plugin.a"));
+/// assert!(Registry::<i32>::exists(".plugins"));
+/// ```
+pub fn remove_prefix_all(prefix: &str) -> usize {
+    check_deadlock!(mut *);
+    let Ok(tables) = _TABLE.tables() else {
+        return 0;
+    };
+    let mut removed = Vec::new();
+    for (_, type_table) in &tables {
+        let Some(Ok(mut type_map)) = type_table.writable().map(TypeTable::write) else {
+            continue;
+        };
+        let names: Vec<_> = type_map
+            .keys()
+            .filter(|name| has_prefix(name, prefix))
+            .cloned()
+            .collect();
+        removed.extend(names.into_iter().filter_map(|name| type_map.remove(&name)));
+    }
+    removed.len()
+}
+
+/// 将所有类型下位于 `old` 前缀之下的键移动到 `new` 前缀之下
+///
+/// 与 `Registry::rename_prefix` 相同，但作用于所有类型：所有类型对应的表按照 `TypeId` 的顺序加写锁，
+/// 如果任一类型下的新键发生冲突或任一包含匹配键的类型已被冻结，则不会修改注册表。返回被移动的键的总数
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{rename_prefix_all, Registry, RenamePrefixError};
+///
+/// Registry::register(".plugin.a", 1i32).unwrap();
+/// Registry::register(".plugin.a", String::from("a")).unwrap();
+/// Registry::register(".plugin.b", 2.0f64).unwrap();
+///
+/// assert_eq!(rename_prefix_all(".plugin", ".mnt"), Ok(3));
+/// assert_eq!(Registry::<String>::get(".mnt.a"), Some(String::from("a")));
+/// assert!(Registry::<f64>::exists(".mnt.b"));
+///
+/// Registry::register(".plugin.a", 3i32).unwrap();
+/// Registry::register(".plugin.b", 4i32).unwrap();
+/// assert_eq!(
+///     rename_prefix_all(".mnt", ".plugin"),
+///     Err(RenamePrefixError::DestinationExists(".plugin.a".to_string()))
+/// );
+/// assert_eq!(Registry::<String>::get(".mnt.a"), Some(String::from("a")));
+/// ```
+pub fn rename_prefix_all(old: &str, new: &str) -> Result<usize, RenamePrefixError> {
+    check_deadlock!(mut *);
+    let mut tables = _TABLE.tables().map_err(|_| RenamePrefixError::Poisoned)?;
+    tables.sort_by_key(|(type_id, _)| *type_id);
+    let mut plans = Vec::with_capacity(tables.len());
+    for (_, type_table) in &tables {
+        if type_table.is_frozen() {
+            // 已冻结的表仅在包含匹配的键时才视为错误，且不获取其写锁
+            let type_map = type_table.read().map_err(|_| RenamePrefixError::Poisoned)?;
+            if type_map.keys().any(|name| has_prefix(name, old)) {
+                return Err(RenamePrefixError::Frozen);
+            }
+            continue;
+        }
+        let type_map = type_table
+            .write()
+            .map_err(|_| RenamePrefixError::Poisoned)?;
+        let plan = plan_rename_prefix(&type_map, old, new)?;
+        plans.push((type_map, plan));
     }
+    Ok(plans
+        .into_iter()
+        .map(|(mut type_map, plan)| apply_rename_prefix(&mut type_map, plan))
+        .sum())
+}
 
-    /// 向注册表中的指定键应用一个函数，该函数可以修改注册表中的值
-    ///
-    /// 如果键不存在，则返回 `None`；否则，返回闭包函数的返回值
-    ///
-    /// # 示例
-    /// ```rust
-    /// use gom::LocalRegistry;
-    ///
-    /// LocalRegistry::register("my_key", 42);
-    /// assert_eq!(LocalRegistry::<i32>::apply("my_key", |v| { *v += 1; *v }), Some(43));
-    /// assert_eq!(LocalRegistry::<i32>::apply("other_key", |v| *v += 1), None);
-    /// ```
-    pub fn apply<R, F: FnOnce(&mut T) -> R>(name: &str, func: F) -> Option<R> {
-        let type_id = TypeId::of::<T>();
-        _LOCAL_TABLE.with_borrow_mut(|table| {
-            let type_map = table.get_mut(&type_id)?;
-            let value = type_map.get_mut(name)?;
-            let value = value.downcast_mut::<T>()?;
-            Some(func(value))
-        })
+/// 以只读方式访问指定键下所有类型的值
+///
+/// 对于每个包含该键的类型，闭包函数会接收到该类型的 `TypeId`、类型名称以及类型擦除后的值；返回被访问的值的数量
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{visit_any, Registry};
+/// use std::any::type_name as type_name_of;
+///
+/// struct Position(i32, i32);
+/// struct Name(&'static str);
+///
+/// Registry::register("entity", Position(1, 2)).unwrap();
+/// Registry::register("entity", Name("player")).unwrap();
+///
+/// let mut found = Vec::new();
+/// let visited = visit_any("entity", |_, type_name, value| {
+///     if let Some(p) = value.downcast_ref::<Position>() {
+///         assert_eq!(type_name, type_name_of::<Position>());
+///         found.push(format!("({}, {})", p.0, p.1));
+///     } else if let Some(n) = value.downcast_ref::<Name>() {
+///         assert_eq!(type_name, type_name_of::<Name>());
+///         found.push(n.0.to_string());
+///     }
+/// });
+/// found.sort();
+/// assert_eq!(visited, 2);
+/// assert_eq!(found, vec!["(1, 2)", "player"]);
+/// assert_eq!(visit_any("other", |_, _, _| {}), 0);
+/// ```
+pub fn visit_any<F: FnMut(TypeId, &'static str, &dyn Any)>(name: &str, mut f: F) -> usize {
+    // 先收集该键在各类型下对应的条目，释放所有表的锁之后再依次访问
+    let records: Vec<_> = {
+        let Ok(tables) = _TABLE.tables() else {
+            return 0;
+        };
+        tables
+            .iter()
+            .filter_map(|(type_id, type_table)| {
+                let type_map = type_table.read_shard(name).ok()?;
+                let slot = type_map.get(name)?;
+                Some((*type_id, type_table.type_name, Arc::clone(&slot.data)))
+            })
+            .collect()
+    };
+    let mut visited = 0;
+    for (type_id, type_name, value) in records {
+        check_deadlock!(ref dyn type_id, type_name, name);
+        let read = value.read_any(&mut |value| {
+            let frame = ContextOperator::enter(Context::With(intern(name), type_id, type_name));
+            f(type_id, type_name, value);
+            drop(frame);
+        });
+        if read.is_ok() {
+            visited += 1;
+        }
     }
+    visited
+}
 
-    /// 向注册表中的指定键应用一个函数，该函数仅能读取注册表中的值
-    ///
-    /// 如果键不存在，则返回 `None`；否则，返回闭包函数的返回值
-    ///
-    /// # 示例
-    /// ```rust
-    /// use gom::LocalRegistry;
-    ///
-    /// LocalRegistry::<i32>::register("my_key", 42);
-    /// assert_eq!(LocalRegistry::<i32>::with("my_key", |v| *v), Some(42));
-    /// assert_eq!(LocalRegistry::<i32>::with("other_key", |v| *v), None);
-    /// ```
-    pub fn with<R, F: FnOnce(&T) -> R>(name: &str, func: F) -> Option<R> {
-        let type_id = TypeId::of::<T>();
-        _LOCAL_TABLE.with_borrow(|table| {
-            let type_map = table.get(&type_id)?;
-            let value = type_map.get(name)?;
-            let value = value.downcast_ref::<T>()?;
-            Some(func(value))
+/// 向注册表中注册一个类型擦除后的值
+///
+/// 适用于编译期无法得知具体类型的场景；`type_id` 必须与值的实际类型一致，否则返回 `RegisterBoxedError::TypeMismatch`。
+/// `type_name` 仅在该类型第一次注册时被记录。如果相同的键已存在，那么旧值将会被新值替换
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{register_boxed, remove_boxed, Registry, RegisterBoxedError};
+/// use std::any::{type_name, Any, TypeId};
+///
+/// let value: Box<dyn Any + Send + Sync> = Box::new(42i32);
+/// register_boxed(TypeId::of::<i32>(), type_name::<i32>(), "my_key", value).unwrap();
+/// assert_eq!(Registry::<i32>::with("my_key", |v| *v), Some(42));
+///
+/// let value: Box<dyn Any + Send + Sync> = Box::new(1.0f64);
+/// assert_eq!(
+///     register_boxed(TypeId::of::<i32>(), type_name::<i32>(), "other", value),
+///     Err(RegisterBoxedError::TypeMismatch)
+/// );
+///
+/// let value = remove_boxed(TypeId::of::<i32>(), "my_key").unwrap();
+/// assert_eq!(value.downcast_ref::<i32>(), Some(&42));
+/// assert!(!Registry::<i32>::exists("my_key"));
+/// ```
+pub fn register_boxed(
+    type_id: TypeId,
+    type_name: &'static str,
+    name: &str,
+    value: Box<dyn Any + Send + Sync>,
+) -> Result<(), RegisterBoxedError> {
+    if (*value).type_id() != type_id {
+        return Err(RegisterBoxedError::TypeMismatch);
+    }
+    if is_sealed(name) {
+        return Err(RegisterBoxedError::SealedNamespace);
+    }
+    let old = loop {
+        let type_table = match _TABLE.get(type_id) {
+            Ok(Some(type_table)) => type_table,
+            Ok(None) => {
+                check_deadlock!(mut dyn type_id, type_name, name; Lock::Global);
+                _TABLE
+                    .get_or_create(type_id, || TypeTable::with_name(type_id, type_name))
+                    .map_err(|_| RegisterBoxedError::Poisoned)?
+            }
+            Err(()) => return Err(RegisterBoxedError::Poisoned),
+        };
+        let type_table = type_table.writable().ok_or(RegisterBoxedError::Frozen)?;
+        check_deadlock!(mut dyn type_id, type_table.type_name, name; Lock::TypeKey);
+        let Some(type_map) = type_table.write_shard_attached(name) else {
+            continue;
+        };
+        let mut type_map = type_map.map_err(|_| RegisterBoxedError::Poisoned)?;
+        break insert_slot(&mut type_map, intern(name), value);
+    };
+    // 被替换的旧值在释放锁之后销毁
+    drop(old);
+    Ok(())
+}
+
+/// 从注册表中移除指定类型与键对应的类型擦除后的值
+///
+/// 如果键不存在，则返回 `None`
+pub fn remove_boxed(type_id: TypeId, name: &str) -> Option<Box<dyn Any + Send + Sync>> {
+    let value = {
+        let type_table = _TABLE.get(type_id).ok()??;
+        let type_map = type_table.writable()?;
+        check_deadlock!(mut dyn type_id, type_map.type_name, name; Lock::TypeKey);
+        let mut type_map = type_map.write_shard(name).ok()?;
+        type_map.remove(name)?
+    };
+    value.into_inner()?.ok()
+}
+
+/// 注册表中某一类型的概况，由 `registered_types` 返回
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeInfo {
+    /// 类型的 `TypeId`
+    pub type_id: TypeId,
+    /// 类型的名称，由 `std::any::type_name` 给出
+    pub type_name: &'static str,
+    /// 该类型下已注册的键的数量
+    pub len: usize,
+    /// 该类型对应的表的锁是否已中毒
+    pub poisoned: bool,
+}
+
+/// 获取注册表中所有类型的概况
+///
+/// 仅涉及读锁；返回的 `Vec` 中的元素顺序不确定
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{registered_types, Registry};
+///
+/// Registry::register("a", 1i32).unwrap();
+/// Registry::register("b", 2i32).unwrap();
+/// Registry::register("a", String::from("value")).unwrap();
+///
+/// let mut types = registered_types();
+/// types.sort_by_key(|info| info.type_name);
+/// assert_eq!(types.len(), 2);
+/// assert_eq!((types[0].type_name, types[0].len), ("alloc::string::String", 1));
+/// assert_eq!((types[1].type_name, types[1].len), ("i32", 2));
+/// assert!(types.iter().all(|info| !info.poisoned));
+/// ```
+pub fn registered_types() -> Vec<TypeInfo> {
+    let Ok(tables) = _TABLE.tables() else {
+        return Vec::new();
+    };
+    tables
+        .iter()
+        .map(|(type_id, type_table)| {
+            let (len, poisoned) = match type_table.read() {
+                Ok(type_map) => (type_map.len(), false),
+                Err(e) => (e.into_inner().len(), true),
+            };
+            TypeInfo {
+                type_id: *type_id,
+                type_name: type_table.type_name,
+                len,
+                poisoned,
+            }
         })
+        .collect()
+}
+
+/// 从注册表中移除所有已为空的类型对应的表，并返回被移除的表的数量
+///
+/// `remove`、`drain`、`clear` 与 `retain` 移除某一类型的最后一个键时已会移除该类型对应的表；
+/// 但如果当前线程仍持有某一类型对应的表的锁（如在键不存在时 `entry` 的闭包中调用），获取注册表的写锁会导致死锁，此时空表会被保留，
+/// 可以稍后调用该函数清理。此时调用该函数同样不会执行任何操作并返回 0。
+/// 已被冻结的类型、锁已中毒的表以及正被其他线程锁定的表不会被移除
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{gc, registered_types, Registry};
+///
+/// Registry::register("other", 1u8).unwrap();
+/// Registry::register("inner", 2u16).unwrap();
+///
+/// // 键不存在时，`entry` 的闭包中无法安全地获取注册表的写锁，因而空表被保留
+/// Registry::<u8>::entry("outer", |_| {
+///     assert_eq!(Registry::<u16>::remove("inner"), Some(2));
+///     assert_eq!(gc(), 0);
+/// });
+/// assert!(registered_types().iter().any(|info| info.type_name == "u16"));
+///
+/// assert_eq!(gc(), 1);
+/// assert!(registered_types().iter().all(|info| info.type_name != "u16"));
+/// ```
+pub fn gc() -> usize {
+    collect_empty(|_| true)
+}
+
+/// 同时读取两个可能不同类型的值
+///
+/// 锁按照 `TypeId` 与键的顺序获取，从而保证确定的加锁顺序；如果任一键不存在，则返回 `None`；否则，返回闭包函数的返回值
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{with_pair, Registry};
+///
+/// struct Name(&'static str);
+/// struct Age(u32);
+///
+/// Registry::register("user", Name("alice")).unwrap();
+/// Registry::register("user", Age(30)).unwrap();
+///
+/// let text = with_pair::<Name, Age, _>("user", "user", |name, age| format!("{} ({})", name.0, age.0));
+/// assert_eq!(text, Some("alice (30)".to_string()));
+/// assert_eq!(with_pair::<Name, Age, _>("user", "other", |_, _| ()), None);
+///
+/// // 可以嵌套在其他读取操作中
+/// Registry::<Name>::with("user", |_| {
+///     assert_eq!(with_pair::<Name, Age, _>("user", "user", |_, age| age.0), Some(30));
+/// });
+/// ```
+pub fn with_pair<A, B, R>(a: &str, b: &str, func: impl FnOnce(&A, &B) -> R) -> Option<R>
+where
+    A: 'static + Send + Sync,
+    B: 'static + Send + Sync,
+{
+    let (type_a, type_b) = (TypeId::of::<A>(), TypeId::of::<B>());
+    let ((lock_a, _), (lock_b, _)) = (Registry::<A>::_record(a)?, Registry::<B>::_record(b)?);
+    check_deadlock!(ref A:a);
+    check_deadlock!(ref B:b);
+    let (value_a, value_b) = if (type_a, a) <= (type_b, b) {
+        let value_a = lock_a.read_as::<A>(Wait::Block).ok()?;
+        (value_a, lock_b.read_as::<B>(Wait::Block).ok()?)
+    } else {
+        let value_b = lock_b.read_as::<B>(Wait::Block).ok()?;
+        (lock_a.read_as::<A>(Wait::Block).ok()?, value_b)
+    };
+    let frames = [
+        ContextOperator::enter(Context::With(intern(a), type_a, std::any::type_name::<A>())),
+        ContextOperator::enter(Context::With(intern(b), type_b, std::any::type_name::<B>())),
+    ];
+    let ret = Some(func(&value_a, &value_b));
+    drop(frames);
+    ret
+}
+
+/// 修改一个值的同时读取另一个可能不同类型的值
+///
+/// 锁按照 `TypeId` 与键的顺序获取，从而保证确定的加锁顺序；如果任一键不存在，或两者为同一类型下的同一个键，则返回 `None`；
+/// 否则，返回闭包函数的返回值
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{apply_with, Registry};
+///
+/// struct Health(u32);
+/// struct Armor(u32);
+///
+/// Registry::register("player", Health(100)).unwrap();
+/// Registry::register("player", Armor(30)).unwrap();
+///
+/// let left = apply_with::<Health, Armor, _>("player", "player", |health, armor| {
+///     health.0 -= 50 - armor.0.min(50);
+///     health.0
+/// });
+/// assert_eq!(left, Some(80));
+/// assert_eq!(apply_with::<Health, Health, _>("player", "player", |_, _| ()), None);
+///
+/// // 可以嵌套在对其他值的读取操作中
+/// Registry::<Armor>::with("player", |_| {
+///     assert_eq!(apply_with::<Health, Armor, _>("player", "player", |h, _| h.0), Some(80));
+/// });
+/// ```
+pub fn apply_with<A, B, R>(a: &str, b: &str, func: impl FnOnce(&mut A, &B) -> R) -> Option<R>
+where
+    A: 'static + Send + Sync,
+    B: 'static + Send + Sync,
+{
+    let (type_a, type_b) = (TypeId::of::<A>(), TypeId::of::<B>());
+    if (type_a, a) == (type_b, b) {
+        return None;
     }
+    let lock_a = Registry::<A>::_writable_record(a)?;
+    let (lock_b, _) = Registry::<B>::_record(b)?;
+    check_deadlock!(mut A:a;Lock::Key);
+    check_deadlock!(ref B:b);
+    let (mut value_a, value_b) = if (type_a, a) < (type_b, b) {
+        let value_a = lock_a.write_as::<A>(Wait::Block).ok()?;
+        (value_a, lock_b.read_as::<B>(Wait::Block).ok()?)
+    } else {
+        let value_b = lock_b.read_as::<B>(Wait::Block).ok()?;
+        (lock_a.write_as::<A>(Wait::Block).ok()?, value_b)
+    };
+    let frames = [
+        ContextOperator::enter(Context::Apply(
+            intern(a),
+            type_a,
+            std::any::type_name::<A>(),
+        )),
+        ContextOperator::enter(Context::With(intern(b), type_b, std::any::type_name::<B>())),
+    ];
+    let ret = Some(func(&mut value_a, &value_b));
+    drop(frames);
+    value_a.commit();
+    ret
+}
 
-    /// 使用新值替换注册表中的指定键对应的值
-    ///
-    /// 如果键不存在，则返回 `None` 并且不会注册新值；否则，返回旧值
-    ///
-    /// # 示例
-    /// ```rust
-    /// use gom::LocalRegistry;
-    ///
-    /// LocalRegistry::<i32>::register("my_key", 42);
-    /// assert_eq!(LocalRegistry::<i32>::replace("my_key", 64), Some(42));
-    /// assert_eq!(LocalRegistry::<i32>::replace("other_key", 32), None);
-    /// ```
-    pub fn replace(name: &str, value: T) -> Option<T> {
-        let type_id = TypeId::of::<T>();
-        let value = _LOCAL_TABLE.with_borrow_mut(|table| {
-            let type_map = table.get_mut(&type_id)?;
-            type_map.insert(name.to_string(), Box::new(value))
-        })?;
-        let value = value.downcast::<T>().ok()?;
-        Some(*value)
+/// 将指定键对应的 `T` 类型的值转换为 `U` 类型的值
+///
+/// 转换函数获得原值的所有权，执行期间持有原值的写锁，以 `Registry::register_read_mostly` 注册的值则持有其修改的互斥锁并转换当前值的副本；
+/// 转换完成后同时获取两个类型对应的表中该键所在分片的写锁并完成替换，
+/// 因而其他线程要么观察到旧的 `T`，要么观察到新的 `U`，不会观察到两者都不存在的状态。
+/// 如果该键已存在 `U` 类型的值，则其将被替换。如果键不存在、锁已中毒或任一类型已被冻结，则不会调用转换函数并返回 `None`。
+///
+/// 如果转换函数发生 panic，锁不会中毒：以 `Registry::register_read_mostly` 注册的原值保持不变；
+/// 其余的原值已交给转换函数并随其栈展开而销毁，该键的 `T` 条目随之被移除，而不是留下一个无法访问的条目
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{transform, Registry};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Config {
+///     name: String,
+/// }
+///
+/// Registry::register(".app.config", String::from("server")).unwrap();
+///
+/// // 原值的所有权被交给转换函数，无需复制
+/// let ret = transform::<String, Config, _>(".app.config", |name| Config { name });
+/// assert_eq!(ret, Some(()));
+/// assert!(!Registry::<String>::exists(".app.config"));
+/// assert_eq!(
+///     Registry::<Config>::with(".app.config", |c| c.name.clone()),
+///     Some(String::from("server"))
+/// );
+///
+/// assert_eq!(transform::<String, Config, _>(".app.config", |name| Config { name }), None);
+/// ```
+///
+/// 转换函数发生 panic 时，以 `Registry::register_read_mostly` 注册的原值仍然保留在注册表中：
+///
+/// ```rust
+/// # #[cfg(feature = "arc-swap")]
+/// # {
+/// use gom::{transform, Registry};
+///
+/// Registry::register_read_mostly("port", String::from("not a number")).unwrap();
+///
+/// let result = std::panic::catch_unwind(|| {
+///     transform::<String, u16, _>("port", |s| s.parse().unwrap())
+/// });
+/// assert!(result.is_err());
+/// assert_eq!(Registry::<String>::get("port"), Some(String::from("not a number")));
+/// assert!(!Registry::<u16>::exists("port"));
+/// # }
+/// ```
+///
+/// 其余的原值随转换函数的栈展开而销毁，该键的 `T` 条目被移除，其他键不受影响：
+///
+/// ```rust
+/// use gom::{transform, Registry};
+///
+/// Registry::register("level", String::from("high")).unwrap();
+/// Registry::register("other", String::from("kept")).unwrap();
+///
+/// let result = std::panic::catch_unwind(|| {
+///     transform::<String, u8, _>("level", |s| s.parse().unwrap())
+/// });
+/// assert!(result.is_err());
+/// assert!(!Registry::<String>::exists("level"));
+/// assert!(!Registry::<u8>::exists("level"));
+/// assert_eq!(Registry::<String>::get("other"), Some(String::from("kept")));
+/// ```
+pub fn transform<T, U, F>(name: &str, func: F) -> Option<()>
+where
+    T: 'static + Send + Sync,
+    U: 'static + Send + Sync,
+    F: FnOnce(T) -> U,
+{
+    let type_t = TypeId::of::<T>();
+    // 在获取原值的锁之前确保 `U` 类型对应的表存在且未被冻结，从而转换完成之后无需再创建该表
+    Registry::<U>::_ensure_type(name)?.writable()?;
+    // 转换期间持有原值的写锁，在释放该类型对应的表的锁之后获取，与 `apply` 的加锁顺序一致
+    let (slot, frozen) = Registry::<T>::_record(name)?;
+    if frozen {
+        return None;
+    }
+    check_deadlock!(mut T:name;Lock::Key);
+    let (value, held) = slot.write_as::<T>(Wait::Block).ok()?.into_owned();
+    let frame = ContextOperator::enter(Context::Apply(
+        intern(name),
+        type_t,
+        std::any::type_name::<T>(),
+    ));
+    let new = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| func(value)));
+    drop(frame);
+    let new = match new {
+        Ok(new) => new,
+        Err(e) => {
+            // 原值已随转换函数的栈展开而销毁时，移除只剩占位值的条目
+            let removed = match held {
+                Held::Locked { .. } => replace_converted::<T, U>(name, &slot, None),
+                #[cfg(feature = "arc-swap")]
+                Held::Published { .. } => Vec::new(),
+            };
+            drop(held);
+            drop(removed);
+            std::panic::resume_unwind(e);
+        }
+    };
+    let replaced = replace_converted::<T, U>(name, &slot, Some(new));
+    // 被替换的条目在释放原值的锁之后销毁，其移除回调函数可能需要获取该锁
+    drop(held);
+    drop(replaced);
+    Some(())
+}
+
+// `transform` 的最后一步：在两个类型对应的表中该键所在分片的写锁下以 `new` 替换原条目，`new` 为 `None` 时仅移除原条目；
+// 返回被移出的条目，调用方应在释放原值的锁之后销毁它们。调用方持有原值的锁，原值已被取出，因而此处不会失败：
+// 转换期间已被其他线程移除或替换的原条目保持不变，这些操作等待原值的锁并随后取得占位值，如同发生在转换之后
+fn replace_converted<T, U>(name: &str, slot: &Arc<RecordData>, new: Option<U>) -> Vec<Record>
+where
+    T: 'static + Send + Sync,
+    U: 'static + Send + Sync,
+{
+    let (type_t, type_u) = (TypeId::of::<T>(), TypeId::of::<U>());
+    check_deadlock!(mut T:name;Lock::TypeKey);
+    check_deadlock!(mut U:name;Lock::TypeKey);
+    let is_current = |type_map: &TypeMap| {
+        type_map
+            .get(name)
+            .is_some_and(|current| Arc::ptr_eq(&current.data, slot))
+    };
+    // 锁已中毒时仍然完成替换，否则已被取出的原值将会丢失
+    fn write<'a>(type_table: &'a TypeTable, name: &str) -> RwLockWriteGuard<'a, TypeMap> {
+        type_table
+            .write_shard(name)
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+    // 注册表的锁已中毒时同样如此
+    fn table(type_id: TypeId) -> Option<Arc<TypeTable>> {
+        _TABLE
+            .get_with(type_id, |table| {
+                Ok::<_, ()>(table.read().unwrap_or_else(PoisonError::into_inner))
+            })
+            .ok()?
+    }
+    let mut removed = Vec::new();
+    let Some(new) = new else {
+        if let Some(table_t) = table(type_t) {
+            let mut map_t = write(&table_t, name);
+            if is_current(&map_t) {
+                removed.extend(map_t.remove(name));
+            }
+        }
+        return removed;
+    };
+    loop {
+        let table_u = match table(type_u) {
+            Some(table_u) => table_u,
+            None => {
+                // `U` 类型对应的表在转换期间已被移除时重新创建
+                check_deadlock!(mut U:name;Lock::Global);
+                let mut map = _TABLE.write().unwrap_or_else(PoisonError::into_inner);
+                Arc::clone(
+                    map.entry(type_u)
+                        .or_insert_with(|| Arc::new(TypeTable::new::<U>())),
+                )
+            }
+        };
+        if type_t == type_u {
+            let mut type_map = write(&table_u, name);
+            if let Some(current) = type_map
+                .get_mut(name)
+                .filter(|current| Arc::ptr_eq(&current.data, slot))
+            {
+                let version = current.version().wrapping_add(1);
+                removed.push(std::mem::replace(
+                    current,
+                    Record::with_version(Box::new(new), version),
+                ));
+            }
+            return removed;
+        }
+        // 原条目所在的表已被整体取出时，原条目不再是当前条目
+        let Some(table_t) = table(type_t) else {
+            let mut map_u = write(&table_u, name);
+            if table_u.is_detached() {
+                continue;
+            }
+            removed.extend(insert_slot(&mut map_u, intern(name), Box::new(new)));
+            return removed;
+        };
+        let (mut map_t, mut map_u) = if type_t < type_u {
+            let map_t = write(&table_t, name);
+            (map_t, write(&table_u, name))
+        } else {
+            let map_u = write(&table_u, name);
+            (write(&table_t, name), map_u)
+        };
+        if table_u.is_detached() {
+            continue;
+        }
+        removed.extend(insert_slot(&mut map_u, intern(name), Box::new(new)));
+        if is_current(&map_t) {
+            removed.extend(map_t.remove(name));
+        }
+        return removed;
+    }
+}
+
+/// 清空整个全局注册表
+///
+/// 返回每个被移除的类型及其对应的值的数量；被移除的值会在释放锁之后被销毁
+///
+/// 如果当前线程正处于 `with` 或 `apply` 闭包中，清空注册表会导致线程死锁，此时不会执行任何操作并返回 `None`
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{clear_all, Registry};
+/// use std::any::TypeId;
+///
+/// Registry::register("a", 1i32).unwrap();
+/// Registry::register("b", 2i32).unwrap();
+/// Registry::register("a", 1.0f64).unwrap();
+///
+/// let mut removed = clear_all().unwrap();
+/// removed.sort_by_key(|(_, count)| *count);
+/// assert_eq!(removed, vec![(TypeId::of::<f64>(), 1), (TypeId::of::<i32>(), 2)]);
+///
+/// assert!(!Registry::<i32>::exists("a"));
+/// assert!(!Registry::<i32>::exists("b"));
+/// assert!(!Registry::<f64>::exists("a"));
+///
+/// Registry::register("a", 3i32).unwrap();
+/// assert_eq!(Registry::<i32>::with("a", |v| *v), Some(3));
+///
+/// Registry::<i32>::with("a", |_| {
+///     assert_eq!(clear_all(), None);
+/// });
+/// ```
+pub fn clear_all() -> Option<Vec<(TypeId, usize)>> {
+    if CONTEXT.with_borrow(|v| !v.is_empty()) {
+        return None;
+    }
+    let table = {
+        let mut map = _TABLE.write().ok()?;
+        std::mem::take(&mut *map)
+    };
+    let ret = table
+        .into_iter()
+        .map(|(type_id, type_map)| {
+            let count = match type_map.into_inner() {
+                Ok(type_map) => type_map.len(),
+                Err(e) => e.into_inner().len(),
+            };
+            (type_id, count)
+        })
+        .collect();
+    Some(ret)
+}
+
+/// `shutdown` 的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// 被清空的类型的数量
+    pub types: usize,
+    /// 执行了移除回调函数的条目数量
+    pub hooks_run: usize,
+    /// 关闭时仍在注册表中的键，按照移除的顺序排列，每项为 (类型名称, 键)
+    pub leaked: Vec<(&'static str, String)>,
+}
+
+/// 清空整个注册表，并按照注册的逆序逐个移除所有条目
+///
+/// 通过 `register_with_drop` 附加的回调函数会在释放注册表的锁之后按照相同的顺序执行；
+/// 关闭时仍在注册表中的键即为未被其所有者主动移除的键，它们被记录在返回结果的 `leaked` 中。
+/// 冻结的类型同样会被清空；锁已中毒的表同样会被清空。再次调用是安全的，此时注册表已为空，返回空的结果
+///
+/// 在调试模式下，如果当前线程正处于 `with` 或 `apply` 等闭包中，则会引发 panic
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{shutdown, Registry};
+/// use std::sync::Mutex;
+///
+/// static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+///
+/// struct Service;
+///
+/// for name in ["db", "cache", "http"] {
+///     Registry::register_with_drop(name, Service, |key, _| {
+///         LOG.lock().unwrap().push(key.to_string());
+///     })
+///     .unwrap();
+/// }
+/// Registry::register("answer", 42).unwrap();
+///
+/// let report = shutdown();
+/// assert_eq!(report.types, 2);
+/// assert_eq!(report.hooks_run, 3);
+/// let leaked: Vec<_> = report.leaked.iter().map(|(_, key)| key.as_str()).collect();
+/// assert_eq!(leaked, vec!["answer", "http", "cache", "db"]);
+/// assert_eq!(*LOG.lock().unwrap(), vec!["http", "cache", "db"]);
+///
+/// assert!(!Registry::<Service>::exists("db"));
+/// assert_eq!(shutdown(), Default::default());
+/// ```
+pub fn shutdown() -> ShutdownReport {
+    check_deadlock!(mut *);
+    let table = {
+        let mut map = _TABLE.write().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *map)
+    };
+    let mut report = ShutdownReport {
+        types: table.len(),
+        ..Default::default()
+    };
+    let mut slots: Vec<_> = table
+        .into_values()
+        .flat_map(|type_table| {
+            let type_name = type_table.type_name;
+            let type_map = type_table.into_inner().unwrap_or_else(|e| e.into_inner());
+            type_map
+                .into_iter()
+                .map(move |(name, slot)| (type_name, name, slot))
+        })
+        .collect();
+    slots.sort_unstable_by_key(|(_, _, slot)| std::cmp::Reverse(slot.registered));
+    for (type_name, name, slot) in slots {
+        if slot.has_on_remove() {
+            report.hooks_run += 1;
+        }
+        drop(slot);
+        report.leaked.push((type_name, name.to_string()));
     }
+    report
 }
 
 /// Make a identifier string with the given path
@@ -447,3 +7901,61 @@ macro_rules! id {
         constcat::concat!($root, concat!($('.', stringify!($x)),+))
     }
 }
+
+/// Define typed key constants with the given paths
+///
+/// Each entry `VIS NAME: Type = path;` expands to `VIS const NAME: Key<Type> = Key::new(id!(path));`,
+/// and the path may start with `@ROOT` like `id!`.
+///
+/// ```rust
+/// use gom::{id, keys, Key};
+///
+/// const APP: &str = id!(app);
+///
+/// keys! {
+///     pub APP_TITLE: String = app.title;
+///     /// Number of frames rendered so far
+///     pub(crate) FRAME_COUNT: u64 = @APP.frame.count;
+///     SCALE: f32 = @APP.scale;
+/// }
+///
+/// assert_eq!(APP_TITLE.name(), ".app.title");
+/// assert_eq!(FRAME_COUNT.name(), ".app.frame.count");
+///
+/// APP_TITLE.register(String::from("demo")).unwrap();
+/// FRAME_COUNT.register(0).unwrap();
+/// FRAME_COUNT.apply(|v| *v += 1);
+/// assert_eq!(FRAME_COUNT.with(|v| *v), Some(1));
+///
+/// let _: Key<f32> = SCALE;
+/// ```
+///
+/// Every entry must have a type annotation:
+///
+/// ```rust,compile_fail
+/// use gom::keys;
+///
+/// keys! {
+///     pub APP_TITLE = app.title;
+/// }
+/// ```
+#[macro_export]
+macro_rules! keys {
+    () => {};
+    ($(#[$meta:meta])* $vis:vis $name:ident : $type:ty = @ $root:ident . $($x:ident).+ ; $($rest:tt)*) => {
+        $(#[$meta])*
+        $vis const $name: $crate::Key<$type> = $crate::Key::new($crate::id!(@ $root . $($x).+));
+        $crate::keys! { $($rest)* }
+    };
+    ($(#[$meta:meta])* $vis:vis $name:ident : $type:ty = $($x:ident).+ ; $($rest:tt)*) => {
+        $(#[$meta])*
+        $vis const $name: $crate::Key<$type> = $crate::Key::new($crate::id!($($x).+));
+        $crate::keys! { $($rest)* }
+    };
+    ($(#[$meta:meta])* $vis:vis $name:ident = $($rest:tt)*) => {
+        compile_error!(concat!("missing type annotation for key `", stringify!($name), "`"));
+    };
+    ($($rest:tt)*) => {
+        compile_error!("expected entries of the form `NAME: Type = path.to.key;`");
+    };
+}
@@ -0,0 +1,133 @@
+//! 按类型统计的锁竞争指标，仅在启用 `metrics` 特性时编译
+//!
+//! 每次获取值自身的锁（`with`、`apply` 等函数）时，先尝试立即获取锁，锁被占用时再阻塞等待，
+//! 并记录获取锁的总次数、需要阻塞等待的次数以及累计的等待时间
+//!
+//! # 示例
+//!
+//! ```rust
+//! use gom::{metrics, Registry};
+//!
+//! struct Counter(u64);
+//!
+//! Registry::register("metrics.counter", Counter(0)).unwrap();
+//! Registry::<Counter>::apply("metrics.counter", |c| c.0 += 1);
+//! Registry::<Counter>::with("metrics.counter", |c| c.0);
+//!
+//! let stats = metrics::snapshot()
+//!     .into_iter()
+//!     .find(|m| m.type_name == std::any::type_name::<Counter>())
+//!     .unwrap();
+//! assert_eq!(stats.acquisitions, 2);
+//! assert_eq!(stats.blocked, 0);
+//! ```
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, LockResult, OnceLock, TryLockError, TryLockResult,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::sync::{lazy_static, RwLock};
+
+/// 某一类型的锁竞争指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeMetrics {
+    /// 值的类型
+    pub type_id: TypeId,
+    /// 值的类型名
+    pub type_name: &'static str,
+    /// 获取该类型的值的锁的总次数
+    pub acquisitions: u64,
+    /// 其中锁被占用而需要阻塞等待的次数
+    pub blocked: u64,
+    /// 阻塞等待的累计时间
+    pub blocked_time: Duration,
+}
+
+// 某一类型的计数器，由该类型的所有条目共享
+#[derive(Default)]
+pub(crate) struct Counters {
+    type_name: OnceLock<&'static str>,
+    acquisitions: AtomicU64,
+    blocked: AtomicU64,
+    blocked_nanos: AtomicU64,
+}
+
+impl Counters {
+    // 先尝试立即获取锁，锁被占用时再阻塞等待并记录等待时间
+    pub(crate) fn acquire<G>(
+        &self,
+        try_lock: impl FnOnce() -> TryLockResult<G>,
+        lock: impl FnOnce() -> LockResult<G>,
+    ) -> LockResult<G> {
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        match try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(TryLockError::Poisoned(e)) => Err(e),
+            Err(TryLockError::WouldBlock) => {
+                let start = Instant::now();
+                let guard = lock();
+                let nanos = start.elapsed().as_nanos().try_into().unwrap_or(u64::MAX);
+                self.blocked.fetch_add(1, Ordering::Relaxed);
+                self.blocked_nanos.fetch_add(nanos, Ordering::Relaxed);
+                guard
+            }
+        }
+    }
+
+    fn reset(&self) {
+        self.acquisitions.store(0, Ordering::Relaxed);
+        self.blocked.store(0, Ordering::Relaxed);
+        self.blocked_nanos.store(0, Ordering::Relaxed);
+    }
+}
+
+lazy_static! {
+    static ref _COUNTERS: RwLock<HashMap<TypeId, Arc<Counters>>> = RwLock::default();
+}
+
+// 获取指定类型的计数器，不存在时创建
+pub(crate) fn counters(type_id: TypeId) -> Arc<Counters> {
+    if let Some(counters) = _COUNTERS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&type_id)
+    {
+        return Arc::clone(counters);
+    }
+    let mut map = _COUNTERS.write().unwrap_or_else(|e| e.into_inner());
+    Arc::clone(map.entry(type_id).or_default())
+}
+
+// 记录指定类型的类型名，在创建该类型对应的表时调用
+pub(crate) fn name(type_id: TypeId, type_name: &'static str) {
+    let _ = counters(type_id).type_name.set(type_name);
+}
+
+/// 获取所有已注册过值的类型的锁竞争指标
+///
+/// 各计数器分别读取，因而并发访问期间得到的各项指标之间可能略有出入
+pub fn snapshot() -> Vec<TypeMetrics> {
+    let map = _COUNTERS.read().unwrap_or_else(|e| e.into_inner());
+    map.iter()
+        .map(|(type_id, counters)| TypeMetrics {
+            type_id: *type_id,
+            type_name: counters.type_name.get().copied().unwrap_or("<unknown>"),
+            acquisitions: counters.acquisitions.load(Ordering::Relaxed),
+            blocked: counters.blocked.load(Ordering::Relaxed),
+            blocked_time: Duration::from_nanos(counters.blocked_nanos.load(Ordering::Relaxed)),
+        })
+        .collect()
+}
+
+/// 将所有类型的锁竞争指标清零
+pub fn reset() {
+    let map = _COUNTERS.read().unwrap_or_else(|e| e.into_inner());
+    for counters in map.values() {
+        counters.reset();
+    }
+}
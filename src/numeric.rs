@@ -0,0 +1,45 @@
+mod private {
+    pub trait Sealed {}
+}
+
+/// 支持 `Registry::fetch_add` 等数值操作的类型
+///
+/// 该 trait 已为所有标准整数与浮点数类型实现，且无法在本 crate 之外实现；整数的加减法在溢出时回绕
+pub trait Numeric: private::Sealed + Copy + PartialOrd {
+    #[doc(hidden)]
+    fn add(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn sub(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_numeric {
+    (int: $($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+            impl Numeric for $t {
+                fn add(self, rhs: Self) -> Self {
+                    self.wrapping_add(rhs)
+                }
+                fn sub(self, rhs: Self) -> Self {
+                    self.wrapping_sub(rhs)
+                }
+            }
+        )*
+    };
+    (float: $($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+            impl Numeric for $t {
+                fn add(self, rhs: Self) -> Self {
+                    self + rhs
+                }
+                fn sub(self, rhs: Self) -> Self {
+                    self - rhs
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric!(int: i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_numeric!(float: f32, f64);
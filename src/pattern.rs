@@ -0,0 +1,60 @@
+use crate::PatternError;
+
+// 模式中的一个段
+enum Segment<'a> {
+    // 匹配内容相同的一个段
+    Literal(&'a str),
+    // `*`，匹配任意一个段
+    One,
+    // `**`，匹配任意数量的段
+    Any,
+}
+
+// 以 `.` 分隔的键匹配模式
+pub(crate) struct Pattern<'a> {
+    segments: Vec<Segment<'a>>,
+}
+
+impl<'a> Pattern<'a> {
+    pub(crate) fn parse(pattern: &'a str) -> Result<Self, PatternError> {
+        if pattern.is_empty() {
+            return Err(PatternError::Empty);
+        }
+        let segments = pattern
+            .split('.')
+            .map(|segment| match segment {
+                "*" => Ok(Segment::One),
+                "**" => Ok(Segment::Any),
+                _ if segment.contains('*') => {
+                    Err(PatternError::InvalidSegment(String::from(segment)))
+                }
+                _ => Ok(Segment::Literal(segment)),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { segments })
+    }
+
+    pub(crate) fn matches(&self, name: &str) -> bool {
+        let parts: Vec<_> = name.split('.').collect();
+        Self::match_segments(&self.segments, &parts)
+    }
+
+    fn match_segments(segments: &[Segment], parts: &[&str]) -> bool {
+        match segments.split_first() {
+            None => parts.is_empty(),
+            Some((Segment::Any, rest)) => {
+                (0..=parts.len()).any(|i| Self::match_segments(rest, &parts[i..]))
+            }
+            Some((segment, rest)) => match parts.split_first() {
+                None => false,
+                Some((part, parts)) => {
+                    let matched = match segment {
+                        Segment::Literal(literal) => literal == part,
+                        _ => true,
+                    };
+                    matched && Self::match_segments(rest, parts)
+                }
+            },
+        }
+    }
+}
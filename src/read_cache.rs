@@ -0,0 +1,67 @@
+// 线程局部的值缓存，由 `Registry::cached_get` 使用
+//
+// 每个线程按类型与键缓存值的副本、所属的条目以及复制时条目的版本号；条目的版本号未改变时直接返回缓存的副本，
+// 而修改、替换、移除或重命名条目都会递增其版本号，使各线程的缓存在下一次访问时失效
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    sync::{atomic::Ordering, Arc},
+};
+
+use crate::{sync::thread_local, RecordData};
+
+struct Cached<T> {
+    data: Arc<RecordData>,
+    version: u64,
+    value: T,
+}
+
+// 某一类型下各键缓存的 `Cached<T>`
+type Entries = HashMap<Arc<str>, Box<dyn Any>>;
+
+thread_local! {
+    static CACHE: RefCell<HashMap<TypeId, Entries>> = RefCell::new(HashMap::new());
+}
+
+// 缓存的副本仍然有效时返回其副本；仅读取一次条目的版本号
+pub(crate) fn lookup<T: 'static + Clone>(name: &str) -> Option<T> {
+    CACHE.with(|cache| {
+        // 值的 `clone` 中再次调用 `cached_get` 时缓存仍可读取
+        let cache = cache.try_borrow().ok()?;
+        let cached = cache
+            .get(&TypeId::of::<T>())?
+            .get(name)?
+            .downcast_ref::<Cached<T>>()?;
+        (cached.data.version.load(Ordering::Relaxed) == cached.version)
+            .then(|| cached.value.clone())
+    })
+}
+
+// 缓存在版本号为 `version` 时复制的副本；版本号须在复制之前读取，从而复制期间的修改会使该副本在下一次访问时失效
+pub(crate) fn store<T: 'static>(name: &str, data: Arc<RecordData>, version: u64, value: T) {
+    CACHE.with(|cache| {
+        if let Ok(mut cache) = cache.try_borrow_mut() {
+            let cached = Cached {
+                data,
+                version,
+                value,
+            };
+            cache
+                .entry(TypeId::of::<T>())
+                .or_default()
+                .insert(crate::intern(name), Box::new(cached));
+        }
+    });
+}
+
+// 键已不存在时移除其缓存，不再持有已被移除的条目
+pub(crate) fn evict<T: 'static>(name: &str) {
+    CACHE.with(|cache| {
+        if let Ok(mut cache) = cache.try_borrow_mut() {
+            if let Some(entries) = cache.get_mut(&TypeId::of::<T>()) {
+                entries.remove(name);
+            }
+        }
+    });
+}
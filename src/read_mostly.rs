@@ -0,0 +1,109 @@
+use std::{
+    any::Any,
+    sync::{Arc, TryLockError},
+    time::Instant,
+};
+
+use arc_swap::{ArcSwap, Guard};
+
+use crate::{
+    sync::{spin_until, Mutex, MutexGuard},
+    Value,
+};
+
+// 以 `Registry::register_read_mostly` 注册的值；读取时仅加载当前值的 `Arc` 而不获取任何锁，修改时构造新值并整体替换
+pub(crate) struct ReadMostly<T> {
+    current: ArcSwap<T>,
+    // 串行化修改，从而“复制、修改、替换”不会丢失其他线程的修改
+    writer: Mutex<()>,
+    // 注册时保存的 `T::clone`，因而修改时不要求调用方的 `T` 实现 `Clone`
+    clone: fn(&T) -> T,
+}
+
+impl<T: 'static + Send + Sync> ReadMostly<T> {
+    pub(crate) fn new(value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            current: ArcSwap::from_pointee(value),
+            writer: Mutex::new(()),
+            clone: T::clone,
+        }
+    }
+
+    pub(crate) fn load(&self) -> Guard<Arc<T>> {
+        self.current.load()
+    }
+
+    // 获取修改的互斥锁；持有期间依次调用 `cloned` 与 `store` 即可完成一次不会丢失修改的“复制、修改、替换”
+    pub(crate) fn lock(&self) -> MutexGuard<'_, ()> {
+        self.writer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // 与 `lock` 相同，但互斥锁被占用时立即返回 `None`
+    pub(crate) fn try_lock(&self) -> Option<MutexGuard<'_, ()>> {
+        match self.writer.try_lock() {
+            Ok(writer) => Some(writer),
+            Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    // 与 `try_lock` 相同，但互斥锁被占用时等待至截止时间
+    pub(crate) fn lock_until(&self, deadline: Instant) -> Option<MutexGuard<'_, ()>> {
+        match spin_until(deadline, || self.writer.try_lock()) {
+            Ok(writer) => Some(writer),
+            Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    // 当前值的副本
+    pub(crate) fn cloned(&self) -> T {
+        (self.clone)(&self.current.load())
+    }
+
+    pub(crate) fn store(&self, value: T) {
+        self.current.store(Arc::new(value));
+    }
+
+    // 替换为新值并取回旧值；旧值仍被读取方共享时返回其副本
+    pub(crate) fn swap(&self, value: T) -> T {
+        let old = self.current.swap(Arc::new(value));
+        Arc::try_unwrap(old).unwrap_or_else(|old| (self.clone)(&old))
+    }
+}
+
+// 擦除类型后的 `ReadMostly`，供不知道值类型的条目操作使用
+pub(crate) trait Published: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    // 当前值的副本
+    fn snapshot(&self) -> Value;
+
+    // 以类型擦除的方式只读访问当前值
+    fn visit(&self, func: &mut dyn FnMut(&dyn Any));
+
+    // 取回当前值的所有权；值仍被读取方共享时返回其副本
+    fn into_value(self: Box<Self>) -> Value;
+}
+
+impl<T: 'static + Send + Sync> Published for ReadMostly<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn snapshot(&self) -> Value {
+        Box::new(self.cloned())
+    }
+
+    fn visit(&self, func: &mut dyn FnMut(&dyn Any)) {
+        func(&**self.current.load());
+    }
+
+    fn into_value(self: Box<Self>) -> Value {
+        let current = self.current.into_inner();
+        Box::new(Arc::try_unwrap(current).unwrap_or_else(|current| (self.clone)(&current)))
+    }
+}
@@ -0,0 +1,114 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use crate::{Record, TypeMap, TypeRegistry};
+
+// 每个类型对应的表被划分为的分片数量
+pub(crate) const SHARDS: usize = 16;
+
+// 指定键所在的分片
+pub(crate) fn shard_index(hasher: &RandomState, name: &str) -> usize {
+    hasher.hash_one(name) as usize % SHARDS
+}
+
+// `TypeRegistry` 使用的保留键不属于任何常规的键，因而不出现在枚举结果中，也不受批量操作的影响
+fn is_listed(name: &str) -> bool {
+    name != TypeRegistry::KEY
+}
+
+// 同时持有某一类型对应的表的全部分片的锁，按分片顺序获取；提供与单个表相同的查询与修改函数，
+// 其中枚举与批量修改的函数会跳过保留键
+pub(crate) struct Shards<'a, G> {
+    guards: Vec<G>,
+    hasher: &'a RandomState,
+}
+
+impl<'a, G: Deref<Target = TypeMap>> Shards<'a, G> {
+    pub(crate) fn new(guards: Vec<G>, hasher: &'a RandomState) -> Self {
+        Self { guards, hasher }
+    }
+
+    fn shard(&self, name: &str) -> &TypeMap {
+        &self.guards[shard_index(self.hasher, name)]
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&Record> {
+        self.shard(name).get(name)
+    }
+
+    pub(crate) fn contains_key(&self, name: &str) -> bool {
+        self.shard(name).contains_key(name)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.guards
+            .iter()
+            .map(|shard| shard.len() - usize::from(shard.contains_key(TypeRegistry::KEY)))
+            .sum()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.guards.iter().map(|shard| shard.capacity()).sum()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Arc<str>, &Record)> {
+        self.guards
+            .iter()
+            .flat_map(|shard| shard.iter())
+            .filter(|(name, _)| is_listed(name))
+    }
+
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &Arc<str>> {
+        self.guards
+            .iter()
+            .flat_map(|shard| shard.keys())
+            .filter(|name| is_listed(name))
+    }
+
+    pub(crate) fn values(&self) -> impl Iterator<Item = &Record> {
+        self.iter().map(|(_, record)| record)
+    }
+}
+
+impl<G: DerefMut<Target = TypeMap>> Shards<'_, G> {
+    // 指定键所在的分片
+    pub(crate) fn shard_mut(&mut self, name: &str) -> &mut TypeMap {
+        &mut self.guards[shard_index(self.hasher, name)]
+    }
+
+    pub(crate) fn insert(&mut self, name: Arc<str>, record: Record) -> Option<Record> {
+        self.shard_mut(&name).insert(name, record)
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> Option<Record> {
+        self.shard_mut(name).remove(name)
+    }
+
+    // 取出全部分片中除保留键以外的条目
+    pub(crate) fn take(&mut self) -> TypeMap {
+        let mut map = TypeMap::with_capacity(self.len());
+        for shard in &mut self.guards {
+            let reserved = shard.remove_entry(TypeRegistry::KEY);
+            map.extend(shard.drain());
+            shard.extend(reserved);
+        }
+        map
+    }
+
+    // 为每个分片预留平均分配到其中的容量
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        for shard in &mut self.guards {
+            shard.reserve(additional.div_ceil(SHARDS));
+        }
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        for shard in &mut self.guards {
+            shard.shrink_to_fit();
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::{fmt, hash::Hash, marker::PhantomData};
+
+/// 注册表中某个条目的编号，由 `Registry::slot_of` 提供
+///
+/// 编号由下标与条目的注册序号组成，通过 `Registry::with_slot` 与 `Registry::apply_slot` 访问时直接以下标定位条目，
+/// 并以注册序号判断该条目是否仍然存在；条目被移除或被替换后编号失效
+pub struct Slot<T> {
+    index: usize,
+    generation: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Slot<T> {
+    pub(crate) fn new(index: usize, generation: u64) -> Self {
+        Self {
+            index,
+            generation,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+impl<T> Clone for Slot<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Slot<T> {}
+
+impl<T> PartialEq for Slot<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.index, self.generation) == (other.index, other.generation)
+    }
+}
+
+impl<T> Eq for Slot<T> {}
+
+impl<T> Hash for Slot<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (self.index, self.generation).hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Slot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Slot")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
@@ -0,0 +1,167 @@
+// 慢速访问的回调函数
+//
+// 未设置回调函数时，每次访问仅需读取一次阈值；设置后，`with` 与 `apply` 记录等待值自身的锁的时间与闭包函数的执行时间，
+// 并在释放该值的锁之后调用回调函数，因而回调函数中可以再次访问注册表
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::sync::thread_local;
+
+/// 慢速访问的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SlowAccessKind {
+    /// 等待值的读锁
+    ReadWait,
+    /// 等待值的写锁
+    WriteWait,
+    /// 执行 `with` 的闭包函数
+    With,
+    /// 执行 `apply` 的闭包函数
+    Apply,
+}
+
+/// 传递给慢速访问回调函数的信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlowAccessInfo<'a> {
+    /// 超过阈值的操作
+    pub kind: SlowAccessKind,
+    /// 被访问的键
+    pub key: &'a str,
+    /// 被访问的值的类型名
+    pub type_name: &'static str,
+    /// 该操作所用的时间
+    pub elapsed: Duration,
+}
+
+type Hook = fn(&SlowAccessInfo);
+
+// 以纳秒计的阈值，`u64::MAX` 表示未设置回调函数
+static THRESHOLD: AtomicU64 = AtomicU64::new(u64::MAX);
+// 回调函数的设置不属于注册表的加锁协议，因而始终使用标准库的读写锁
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+thread_local! {
+    // 当前线程是否正在执行回调函数；回调函数中的访问不再触发回调函数
+    static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 设置慢速访问的回调函数
+///
+/// `with`、`apply` 及基于它们的函数等待值的锁或执行闭包函数的时间超过 `threshold` 时，调用 `hook`；
+/// 回调函数在释放该值的锁之后调用，因而可以在其中记录日志或再次访问注册表，回调函数中的访问不会再次触发回调函数。
+/// 再次调用时替换先前的设置
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{Registry, SlowAccessInfo, SlowAccessKind};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::time::Duration;
+///
+/// static SLOW: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn log(info: &SlowAccessInfo) {
+///     if info.key == "slow.config" && info.kind == SlowAccessKind::Apply {
+///         // 回调函数执行时，该值的锁已被释放
+///         assert_eq!(Registry::<u32>::get(info.key), Some(1));
+///         SLOW.fetch_add(1, Ordering::SeqCst);
+///     }
+/// }
+///
+/// Registry::register("slow.config", 0u32).unwrap();
+/// gom::set_slow_access_hook(Duration::from_millis(20), log);
+/// Registry::<u32>::apply("slow.config", |v| {
+///     std::thread::sleep(Duration::from_millis(50));
+///     *v += 1;
+/// });
+/// Registry::<u32>::apply("slow.config", |_| {});
+/// gom::clear_slow_access_hook();
+/// assert_eq!(SLOW.load(Ordering::SeqCst), 1);
+/// ```
+pub fn set_slow_access_hook(threshold: Duration, hook: fn(&SlowAccessInfo)) {
+    let mut slot = HOOK.write().unwrap_or_else(|e| e.into_inner());
+    *slot = Some(hook);
+    let nanos = threshold.as_nanos().try_into().unwrap_or(u64::MAX - 1);
+    THRESHOLD.store(nanos, Ordering::Release);
+}
+
+/// 移除慢速访问的回调函数
+pub fn clear_slow_access_hook() {
+    let mut slot = HOOK.write().unwrap_or_else(|e| e.into_inner());
+    THRESHOLD.store(u64::MAX, Ordering::Release);
+    *slot = None;
+}
+
+// 记录一次访问中等待锁与执行闭包函数的时间；未设置回调函数时不读取时间
+pub(crate) struct Stopwatch {
+    threshold: Duration,
+    start: Option<Instant>,
+    wait: Duration,
+}
+
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        let nanos = THRESHOLD.load(Ordering::Acquire);
+        let enabled = nanos != u64::MAX && !IN_HOOK.with(Cell::get);
+        Self {
+            threshold: Duration::from_nanos(nanos),
+            start: enabled.then(Instant::now),
+            wait: Duration::ZERO,
+        }
+    }
+
+    // 已获取锁，此后的时间计入闭包函数的执行时间
+    pub(crate) fn locked(&mut self) {
+        if let Some(start) = &mut self.start {
+            let now = Instant::now();
+            self.wait = now - *start;
+            *start = now;
+        }
+    }
+
+    // 闭包函数已返回且锁已释放，对超过阈值的操作调用回调函数
+    pub(crate) fn finish(self, write: bool, key: &str, type_name: &'static str) {
+        let Some(start) = self.start else {
+            return;
+        };
+        let (wait, run) = if write {
+            (SlowAccessKind::WriteWait, SlowAccessKind::Apply)
+        } else {
+            (SlowAccessKind::ReadWait, SlowAccessKind::With)
+        };
+        for (kind, elapsed) in [(wait, self.wait), (run, start.elapsed())] {
+            if elapsed > self.threshold {
+                report(&SlowAccessInfo {
+                    kind,
+                    key,
+                    type_name,
+                    elapsed,
+                });
+            }
+        }
+    }
+}
+
+#[cold]
+fn report(info: &SlowAccessInfo) {
+    // 复制回调函数后立即释放锁，回调函数中可以重新设置回调函数
+    let Some(hook) = *HOOK.read().unwrap_or_else(|e| e.into_inner()) else {
+        return;
+    };
+    // 回调函数发生 panic 时同样需要恢复标记
+    struct Reset;
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            IN_HOOK.with(|x| x.set(false));
+        }
+    }
+    IN_HOOK.with(|x| x.set(true));
+    let _reset = Reset;
+    hook(info);
+}
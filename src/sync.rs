@@ -0,0 +1,242 @@
+// 注册表内部使用的同步原语
+//
+// 默认使用标准库的 `RwLock`；启用 `parking_lot` 特性时改用 `parking_lot::RwLock`，并包装为与标准库相同的接口，
+// 其获取锁的函数总是返回 `Ok`，因而调用方处理锁中毒的分支不会被执行
+//
+// 以 `--cfg loom` 编译时，读写锁、互斥锁、条件变量、全局变量与线程局部变量均改用 `loom` 提供的模拟实现，
+// 从而以 `loom` 检查注册表的加锁协议；此时忽略 `parking_lot` 特性。`loom` 的读写锁同样被包装为与标准库相同的接口，其锁不会中毒
+use std::{
+    sync::{TryLockError, TryLockResult},
+    time::Instant,
+};
+
+#[cfg(any(loom, feature = "parking_lot"))]
+use std::sync::LockResult;
+
+#[cfg(not(any(loom, feature = "parking_lot")))]
+pub(crate) use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(all(feature = "parking_lot", not(loom)))]
+pub(crate) use parking_lot::{RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(loom)]
+pub(crate) use loom::sync::{RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(not(loom))]
+pub(crate) use std::sync::{Condvar, Mutex};
+
+#[cfg(loom)]
+pub(crate) use loom::sync::{Condvar, Mutex};
+
+// 仅被 `arc-swap` 与 `deadlock-detection` 特性使用
+#[cfg(all(not(loom), any(feature = "arc-swap", feature = "deadlock-detection")))]
+pub(crate) use std::sync::MutexGuard;
+
+#[cfg(all(loom, any(feature = "arc-swap", feature = "deadlock-detection")))]
+pub(crate) use loom::sync::MutexGuard;
+
+#[cfg(not(loom))]
+pub(crate) use lazy_static::lazy_static;
+#[cfg(not(loom))]
+pub(crate) use std::{thread::yield_now, thread_local};
+
+#[cfg(loom)]
+pub(crate) use loom::{lazy_static, thread::yield_now};
+
+// `loom::thread_local!` 不接受 `const { ... }` 形式的初始值，去掉 `const` 后转发给它
+#[cfg(loom)]
+macro_rules! loom_thread_local {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = const { $init:expr }; $($rest:tt)*) => {
+        loom::thread_local!($(#[$attr])* $vis static $name: $t = $init;);
+        $crate::sync::thread_local!($($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr; $($rest:tt)*) => {
+        loom::thread_local!($(#[$attr])* $vis static $name: $t = $init;);
+        $crate::sync::thread_local!($($rest)*);
+    };
+}
+
+#[cfg(loom)]
+pub(crate) use loom_thread_local as thread_local;
+
+#[cfg(all(feature = "parking_lot", not(loom)))]
+#[derive(Default)]
+pub(crate) struct RwLock<T: ?Sized>(parking_lot::RwLock<T>);
+
+#[cfg(all(feature = "parking_lot", not(loom)))]
+impl<T> RwLock<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self(parking_lot::RwLock::new(value))
+    }
+
+    pub(crate) fn into_inner(self) -> LockResult<T> {
+        Ok(self.0.into_inner())
+    }
+}
+
+#[cfg(all(feature = "parking_lot", not(loom)))]
+impl<T: ?Sized> RwLock<T> {
+    pub(crate) fn read(&self) -> LockResult<RwLockReadGuard<'_, T>> {
+        Ok(self.0.read())
+    }
+
+    pub(crate) fn write(&self) -> LockResult<RwLockWriteGuard<'_, T>> {
+        Ok(self.0.write())
+    }
+
+    pub(crate) fn try_read(&self) -> TryLockResult<RwLockReadGuard<'_, T>> {
+        self.0.try_read().ok_or(TryLockError::WouldBlock)
+    }
+
+    pub(crate) fn try_write(&self) -> TryLockResult<RwLockWriteGuard<'_, T>> {
+        self.0.try_write().ok_or(TryLockError::WouldBlock)
+    }
+
+    pub(crate) fn is_poisoned(&self) -> bool {
+        false
+    }
+
+    pub(crate) fn clear_poison(&self) {}
+}
+
+#[cfg(loom)]
+#[derive(Default)]
+pub(crate) struct RwLock<T>(loom::sync::RwLock<T>);
+
+#[cfg(loom)]
+impl<T> RwLock<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self(loom::sync::RwLock::new(value))
+    }
+
+    pub(crate) fn into_inner(self) -> LockResult<T> {
+        self.0.into_inner()
+    }
+
+    pub(crate) fn read(&self) -> LockResult<RwLockReadGuard<'_, T>> {
+        self.0.read()
+    }
+
+    pub(crate) fn write(&self) -> LockResult<RwLockWriteGuard<'_, T>> {
+        self.0.write()
+    }
+
+    pub(crate) fn try_read(&self) -> TryLockResult<RwLockReadGuard<'_, T>> {
+        self.0.try_read()
+    }
+
+    pub(crate) fn try_write(&self) -> TryLockResult<RwLockWriteGuard<'_, T>> {
+        self.0.try_write()
+    }
+
+    pub(crate) fn is_poisoned(&self) -> bool {
+        false
+    }
+
+    pub(crate) fn clear_poison(&self) {}
+}
+
+// `loom` 的线程局部变量仅提供 `with`，为其补充标准库中 `Cell` 与 `RefCell` 类型的线程局部变量的便捷函数
+#[cfg(loom)]
+pub(crate) trait LocalCell<T> {
+    fn get(&'static self) -> T;
+
+    fn set(&'static self, value: T);
+}
+
+#[cfg(loom)]
+impl<T: Copy + 'static> LocalCell<T> for loom::thread::LocalKey<std::cell::Cell<T>> {
+    fn get(&'static self) -> T {
+        self.with(std::cell::Cell::get)
+    }
+
+    fn set(&'static self, value: T) {
+        self.with(|cell| cell.set(value));
+    }
+}
+
+#[cfg(loom)]
+pub(crate) trait LocalRefCell<T> {
+    fn with_borrow<R>(&'static self, f: impl FnOnce(&T) -> R) -> R;
+
+    fn with_borrow_mut<R>(&'static self, f: impl FnOnce(&mut T) -> R) -> R;
+}
+
+#[cfg(loom)]
+impl<T: 'static> LocalRefCell<T> for loom::thread::LocalKey<std::cell::RefCell<T>> {
+    fn with_borrow<R>(&'static self, f: impl FnOnce(&T) -> R) -> R {
+        self.with(|cell| f(&cell.borrow()))
+    }
+
+    fn with_borrow_mut<R>(&'static self, f: impl FnOnce(&mut T) -> R) -> R {
+        self.with(|cell| f(&mut cell.borrow_mut()))
+    }
+}
+
+// 在截止时间之前获取锁；超时返回 `TryLockError::WouldBlock`
+pub(crate) trait TimedLock<T> {
+    fn try_read_until(&self, deadline: Instant) -> TryLockResult<RwLockReadGuard<'_, T>>;
+
+    fn try_write_until(&self, deadline: Instant) -> TryLockResult<RwLockWriteGuard<'_, T>>;
+}
+
+// 标准库的 `RwLock` 与 `Mutex` 不支持限时获取，因而反复尝试获取锁；两次尝试之间的休眠从数微秒开始逐次加倍，最长为 1 毫秒
+#[cfg(any(loom, not(feature = "parking_lot"), feature = "arc-swap"))]
+pub(crate) fn spin_until<G>(
+    deadline: Instant,
+    mut attempt: impl FnMut() -> TryLockResult<G>,
+) -> TryLockResult<G> {
+    use std::{thread, time::Duration};
+
+    let mut backoff = Duration::from_micros(4);
+    loop {
+        match attempt() {
+            Err(TryLockError::WouldBlock) => {}
+            ret => return ret,
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(TryLockError::WouldBlock);
+        }
+        thread::sleep(backoff.min(deadline - now));
+        backoff = (backoff * 2).min(Duration::from_millis(1));
+    }
+}
+
+#[cfg(not(any(loom, feature = "parking_lot")))]
+impl<T> TimedLock<T> for RwLock<T> {
+    fn try_read_until(&self, deadline: Instant) -> TryLockResult<RwLockReadGuard<'_, T>> {
+        spin_until(deadline, || self.try_read())
+    }
+
+    fn try_write_until(&self, deadline: Instant) -> TryLockResult<RwLockWriteGuard<'_, T>> {
+        spin_until(deadline, || self.try_write())
+    }
+}
+
+#[cfg(loom)]
+impl<T> TimedLock<T> for RwLock<T> {
+    fn try_read_until(&self, deadline: Instant) -> TryLockResult<RwLockReadGuard<'_, T>> {
+        spin_until(deadline, || self.try_read())
+    }
+
+    fn try_write_until(&self, deadline: Instant) -> TryLockResult<RwLockWriteGuard<'_, T>> {
+        spin_until(deadline, || self.try_write())
+    }
+}
+
+#[cfg(all(feature = "parking_lot", not(loom)))]
+impl<T> TimedLock<T> for RwLock<T> {
+    fn try_read_until(&self, deadline: Instant) -> TryLockResult<RwLockReadGuard<'_, T>> {
+        self.0
+            .try_read_until(deadline)
+            .ok_or(TryLockError::WouldBlock)
+    }
+
+    fn try_write_until(&self, deadline: Instant) -> TryLockResult<RwLockWriteGuard<'_, T>> {
+        self.0
+            .try_write_until(deadline)
+            .ok_or(TryLockError::WouldBlock)
+    }
+}
@@ -0,0 +1,274 @@
+use std::{
+    any::{Any, TypeId},
+    collections::{hash_map::Entry, HashMap},
+    sync::Arc,
+};
+
+use crate::{intern, Context, ContextOperator, ReadAccess, RecordData, Wait, WriteAccess, _TABLE};
+
+// 事务中对某个键的访问请求
+struct Request {
+    type_id: TypeId,
+    type_name: &'static str,
+    name: Arc<str>,
+    write: bool,
+    check: fn(&str, bool),
+    lock: for<'a> fn(&'a RecordData, bool) -> Option<Box<dyn Guard + 'a>>,
+}
+
+// 擦除类型后的 `ReadAccess` 与 `WriteAccess`
+trait Guard {
+    fn get(&self) -> &dyn Any;
+
+    // 以只读方式访问时返回 `None`
+    fn get_mut(&mut self) -> Option<&mut dyn Any>;
+
+    // 发布修改并记录一次修改，以只读方式访问时不执行任何操作
+    fn commit(self: Box<Self>);
+}
+
+impl<T: 'static + Send + Sync> Guard for ReadAccess<'_, T> {
+    fn get(&self) -> &dyn Any {
+        &**self
+    }
+
+    fn get_mut(&mut self) -> Option<&mut dyn Any> {
+        None
+    }
+
+    fn commit(self: Box<Self>) {}
+}
+
+impl<T: 'static + Send + Sync> Guard for WriteAccess<'_, T> {
+    fn get(&self) -> &dyn Any {
+        &**self
+    }
+
+    fn get_mut(&mut self) -> Option<&mut dyn Any> {
+        Some(&mut **self)
+    }
+
+    fn commit(self: Box<Self>) {
+        WriteAccess::commit(*self);
+    }
+}
+
+fn lock<T: 'static + Send + Sync>(data: &RecordData, write: bool) -> Option<Box<dyn Guard + '_>> {
+    if write {
+        Some(Box::new(data.write_as::<T>(Wait::Block).ok()?))
+    } else {
+        Some(Box::new(data.read_as::<T>(Wait::Block).ok()?))
+    }
+}
+
+/// 同时访问多个键的事务
+///
+/// 所有被请求的锁按照 `TypeId` 与键的顺序获取，因而并发执行的事务之间不会死锁
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{Registry, Transaction};
+///
+/// struct Account(i64);
+/// struct Rate(i64);
+///
+/// Registry::register("alice", Account(100)).unwrap();
+/// Registry::register("bob", Account(0)).unwrap();
+/// Registry::register("fee", Rate(1)).unwrap();
+///
+/// let ret = Transaction::new()
+///     .write::<Account>("alice")
+///     .write::<Account>("bob")
+///     .read::<Rate>("fee")
+///     .run(|txn| {
+///         let fee = txn.get::<Rate>("fee").unwrap().0;
+///         txn.get_mut::<Account>("alice").unwrap().0 -= 50 + fee;
+///         txn.get_mut::<Account>("bob").unwrap().0 += 50;
+///     });
+/// assert_eq!(ret, Some(()));
+/// assert_eq!(Registry::<Account>::with("alice", |a| a.0), Some(49));
+/// assert_eq!(Registry::<Account>::with("bob", |a| a.0), Some(50));
+///
+/// // 任一键不存在时，整个事务都不会执行
+/// let ret = Transaction::new()
+///     .write::<Account>("alice")
+///     .write::<Account>("carol")
+///     .run(|txn| txn.get_mut::<Account>("alice").unwrap().0 = 0);
+/// assert_eq!(ret, None);
+/// assert_eq!(Registry::<Account>::with("alice", |a| a.0), Some(49));
+/// ```
+///
+/// 三个线程同时执行相互重叠的事务：
+///
+/// ```rust
+/// use gom::{Registry, Transaction};
+/// use std::thread;
+///
+/// for key in ["a", "b", "c"] {
+///     Registry::register(key, 0i64).unwrap();
+/// }
+///
+/// let workers: Vec<_> = [("a", "b"), ("b", "c"), ("c", "a")]
+///     .into_iter()
+///     .map(|(from, to)| {
+///         thread::spawn(move || {
+///             for _ in 0..1000 {
+///                 Transaction::new()
+///                     .write::<i64>(to)
+///                     .write::<i64>(from)
+///                     .run(|txn| {
+///                         *txn.get_mut::<i64>(from).unwrap() -= 1;
+///                         *txn.get_mut::<i64>(to).unwrap() += 1;
+///                     })
+///                     .unwrap();
+///             }
+///         })
+///     })
+///     .collect();
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+/// for key in ["a", "b", "c"] {
+///     assert_eq!(Registry::<i64>::with(key, |v| *v), Some(0));
+/// }
+/// ```
+#[derive(Default)]
+pub struct Transaction {
+    requests: Vec<Request>,
+}
+
+impl Transaction {
+    /// 创建一个空的事务
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn request<T: 'static + Send + Sync>(mut self, name: &str, write: bool) -> Self {
+        let type_id = TypeId::of::<T>();
+        let check: fn(&str, bool) = |name, write| {
+            if write {
+                check_deadlock!(mut T:name;crate::Lock::Key);
+            } else {
+                check_deadlock!(ref T:name);
+            }
+        };
+        match self
+            .requests
+            .iter_mut()
+            .find(|r| r.type_id == type_id && &*r.name == name)
+        {
+            Some(request) => request.write |= write,
+            None => self.requests.push(Request {
+                type_id,
+                type_name: std::any::type_name::<T>(),
+                name: intern(name),
+                write,
+                check,
+                lock: lock::<T>,
+            }),
+        }
+        self
+    }
+
+    /// 请求以只读方式访问指定键对应的值
+    pub fn read<T: 'static + Send + Sync>(self, name: &str) -> Self {
+        self.request::<T>(name, false)
+    }
+
+    /// 请求以可写方式访问指定键对应的值
+    pub fn write<T: 'static + Send + Sync>(self, name: &str) -> Self {
+        self.request::<T>(name, true)
+    }
+
+    /// 获取所有被请求的锁并执行闭包函数
+    ///
+    /// 如果任一键不存在、以可写方式访问的类型已被冻结或锁已中毒，则返回 `None` 并且不会执行闭包函数；否则，返回闭包函数的返回值
+    pub fn run<R, F: FnOnce(&mut TransactionContext) -> R>(mut self, func: F) -> Option<R> {
+        self.requests
+            .sort_by(|a, b| (a.type_id, &a.name).cmp(&(b.type_id, &b.name)));
+        for request in &self.requests {
+            (request.check)(&request.name, request.write);
+        }
+        // 先收集所有被请求的条目，释放注册表及各类型对应的表的锁之后再获取各个值自身的锁
+        let locks = {
+            let mut type_tables = HashMap::new();
+            for request in &self.requests {
+                if let Entry::Vacant(entry) = type_tables.entry(request.type_id) {
+                    entry.insert(_TABLE.get(request.type_id).ok()??);
+                }
+                if request.write && type_tables[&request.type_id].is_frozen() {
+                    return None;
+                }
+            }
+            let mut type_maps = HashMap::new();
+            for request in &self.requests {
+                if let Entry::Vacant(entry) = type_maps.entry(request.type_id) {
+                    entry.insert(type_tables[&request.type_id].read().ok()?);
+                }
+            }
+            self.requests
+                .iter()
+                .map(|request| {
+                    let slot = type_maps[&request.type_id].get(&request.name)?;
+                    Some(Arc::clone(&slot.data))
+                })
+                .collect::<Option<Vec<_>>>()?
+        };
+        let mut guards = Vec::with_capacity(locks.len());
+        for (request, lock) in self.requests.iter().zip(&locks) {
+            let guard = (request.lock)(lock, request.write)?;
+            guards.push((request.type_id, &*request.name, guard));
+        }
+        let frames: Vec<_> = self
+            .requests
+            .iter()
+            .map(|request| {
+                let name = request.name.clone();
+                ContextOperator::enter(if request.write {
+                    Context::Apply(name, request.type_id, request.type_name)
+                } else {
+                    Context::With(name, request.type_id, request.type_name)
+                })
+            })
+            .collect();
+        let mut context = TransactionContext { guards };
+        let ret = func(&mut context);
+        drop(frames);
+        for (_, _, guard) in context.guards {
+            guard.commit();
+        }
+        Some(ret)
+    }
+}
+
+/// 事务执行期间用于访问被请求的值的对象
+pub struct TransactionContext<'a> {
+    guards: Vec<(TypeId, &'a str, Box<dyn Guard + 'a>)>,
+}
+
+impl TransactionContext<'_> {
+    /// 读取事务中被请求的值
+    ///
+    /// 如果该键未被请求，则返回 `None`
+    pub fn get<T: 'static>(&self, name: &str) -> Option<&T> {
+        let type_id = TypeId::of::<T>();
+        let (_, _, guard) = self
+            .guards
+            .iter()
+            .find(|(id, key, _)| *id == type_id && *key == name)?;
+        guard.get().downcast_ref::<T>()
+    }
+
+    /// 修改事务中以可写方式请求的值
+    ///
+    /// 如果该键未被请求或仅以只读方式请求，则返回 `None`
+    pub fn get_mut<T: 'static>(&mut self, name: &str) -> Option<&mut T> {
+        let type_id = TypeId::of::<T>();
+        let (_, _, guard) = self
+            .guards
+            .iter_mut()
+            .find(|(id, key, _)| *id == type_id && *key == name)?;
+        guard.get_mut()?.downcast_mut::<T>()
+    }
+}
@@ -0,0 +1,100 @@
+use std::any::Any;
+
+use crate::Registry;
+
+/// 以类型为索引的注册表，每个类型至多对应一个值
+///
+/// 值保存在 `Registry::<T>` 的保留键 `TypeRegistry::KEY` 下，因而两者共享同一份存储与死锁检查；
+/// 该保留键以 NUL 字符开头，不会与常规的键冲突。`Registry::<T>` 的 `keys`、`len`、`snapshot` 等枚举函数
+/// 不会列出该保留键，`clear`、`drain`、`retain` 等批量操作也不会移除保存在其下的值
+///
+/// # 示例
+///
+/// ```rust
+/// use gom::{Registry, TypeRegistry};
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Config {
+///     verbose: bool,
+/// }
+///
+/// assert_eq!(TypeRegistry::set(Config { verbose: false }), None);
+/// TypeRegistry::apply(|c: &mut Config| c.verbose = true);
+/// assert_eq!(TypeRegistry::get::<Config>(), Some(Config { verbose: true }));
+/// assert_eq!(TypeRegistry::with(|c: &Config| c.verbose), Some(true));
+///
+/// // 与 `Registry` 中的常规键互不影响
+/// Registry::register("config", Config { verbose: false }).unwrap();
+/// assert_eq!(Registry::<Config>::with("config", |c| c.verbose), Some(false));
+/// assert_eq!(TypeRegistry::with(|c: &Config| c.verbose), Some(true));
+/// assert_eq!(Registry::<Config>::remove("config"), Some(Config { verbose: false }));
+/// assert_eq!(TypeRegistry::remove::<Config>(), Some(Config { verbose: true }));
+/// assert_eq!(TypeRegistry::get::<Config>(), None);
+/// ```
+///
+/// 枚举与批量操作仅涉及常规的键：
+///
+/// ```rust
+/// use gom::{Registry, TypeRegistry};
+///
+/// TypeRegistry::set(1u64);
+/// Registry::register("a", 2u64).unwrap();
+/// Registry::register("b", 3u64).unwrap();
+///
+/// let mut keys = Registry::<u64>::keys();
+/// keys.sort();
+/// assert_eq!(keys, vec!["a", "b"]);
+/// assert_eq!(Registry::<u64>::len(), 2);
+/// assert_eq!(Registry::<u64>::retain(|_, v| *v != 2), 1);
+/// assert_eq!(Registry::<u64>::snapshot(), vec![("b".to_string(), 3)]);
+///
+/// assert_eq!(Registry::<u64>::clear(), 1);
+/// assert!(Registry::<u64>::is_empty());
+/// assert!(Registry::<u64>::take_map().is_empty());
+/// assert_eq!(TypeRegistry::get::<u64>(), Some(1));
+/// ```
+///
+/// 两种方式访问同一个值时同样会进行死锁检查：
+///
+/// ```rust,should_panic
+/// use gom::{Registry, TypeRegistry};
+///
+/// TypeRegistry::set(42i32);
+/// # if !cfg!(any(debug_assertions, feature = "deadlock-detection")) { panic!() }
+/// TypeRegistry::with(|_: &i32| {
+///     Registry::<i32>::apply(TypeRegistry::KEY, |v| *v += 1);
+/// });
+/// ```
+pub struct TypeRegistry;
+
+impl TypeRegistry {
+    /// 保存值时使用的保留键
+    pub const KEY: &'static str = "\0gom::TypeRegistry";
+
+    /// 设置该类型对应的值
+    ///
+    /// 如果该类型已有对应的值，则返回旧值，否则返回 `None`
+    pub fn set<T: 'static + Send + Sync + Any>(value: T) -> Option<T> {
+        Registry::<T>::set(Self::KEY, value)
+    }
+
+    /// 获取该类型对应的值的副本
+    pub fn get<T: 'static + Send + Sync + Any + Clone>() -> Option<T> {
+        Registry::<T>::get(Self::KEY)
+    }
+
+    /// 向该类型对应的值应用一个函数，该函数仅能读取该值
+    pub fn with<T: 'static + Send + Sync + Any, R>(func: impl FnOnce(&T) -> R) -> Option<R> {
+        Registry::<T>::with(Self::KEY, func)
+    }
+
+    /// 向该类型对应的值应用一个函数，该函数可以修改该值
+    pub fn apply<T: 'static + Send + Sync + Any, R>(func: impl FnOnce(&mut T) -> R) -> Option<R> {
+        Registry::<T>::apply(Self::KEY, func)
+    }
+
+    /// 移除该类型对应的值
+    pub fn remove<T: 'static + Send + Sync + Any>() -> Option<T> {
+        Registry::<T>::remove(Self::KEY)
+    }
+}
@@ -0,0 +1,187 @@
+// 跨线程的等待图，仅在启用 `deadlock-detection` 特性时编译
+//
+// 每个线程的上下文访问栈被同步到全局的等待图中，作为该线程持有的值的锁；线程在获取某个值的锁之前记录其将要等待的键，
+// 并沿 "等待的键 → 持有该键的线程 → 该线程等待的键" 查找，回到当前线程时即形成了环，继续等待将导致死锁
+use std::{
+    any::TypeId,
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+    thread::{self, ThreadId},
+};
+
+use crate::{
+    intern,
+    sync::{lazy_static, Mutex, MutexGuard},
+    Context,
+};
+
+// 线程将要等待的键
+struct Wanted {
+    key: Arc<str>,
+    type_id: TypeId,
+    type_name: &'static str,
+    write: bool,
+}
+
+impl Wanted {
+    // 持有该上下文对应的锁的线程是否会阻塞等待该键的线程
+    fn blocked_by(&self, ctx: &Context) -> bool {
+        match ctx {
+            Context::Apply(key, type_id, _) => *type_id == self.type_id && *key == self.key,
+            Context::With(key, type_id, _) => {
+                self.write && *type_id == self.type_id && *key == self.key
+            }
+            Context::Type(..) => false,
+        }
+    }
+}
+
+impl fmt::Display for Wanted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = if self.write { "write" } else { "read" };
+        write!(f, "{op} key `{}` of type `{}`", self.key, self.type_name)
+    }
+}
+
+#[derive(Default)]
+struct Graph {
+    held: HashMap<ThreadId, Vec<Context>>,
+    waiting: HashMap<ThreadId, Wanted>,
+}
+
+impl Graph {
+    // 查找从 `start` 出发并回到 `start` 的环，返回环上依次经过的线程
+    fn cycle(&self, start: ThreadId) -> Option<Vec<ThreadId>> {
+        let mut path = vec![start];
+        let mut visited = HashSet::new();
+        self.walk(start, start, &mut path, &mut visited)
+            .then_some(path)
+    }
+
+    fn walk(
+        &self,
+        start: ThreadId,
+        current: ThreadId,
+        path: &mut Vec<ThreadId>,
+        visited: &mut HashSet<ThreadId>,
+    ) -> bool {
+        let Some(wanted) = self.waiting.get(&current) else {
+            return false;
+        };
+        for (holder, held) in &self.held {
+            // 线程自身的嵌套访问由上下文访问栈检查
+            if *holder == current || !held.iter().any(|ctx| wanted.blocked_by(ctx)) {
+                continue;
+            }
+            if *holder == start {
+                return true;
+            }
+            if visited.insert(*holder) {
+                path.push(*holder);
+                if self.walk(start, *holder, path, visited) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
+        false
+    }
+
+    fn describe(&self, cycle: &[ThreadId]) -> String {
+        let threads: Vec<_> = cycle
+            .iter()
+            .map(|id| {
+                let held: Vec<_> = self
+                    .held
+                    .get(id)
+                    .into_iter()
+                    .flatten()
+                    .map(Context::to_string)
+                    .collect();
+                match self.waiting.get(id) {
+                    Some(wanted) => {
+                        format!("{id:?} holds [{}] and wants to {wanted}", held.join(", "))
+                    }
+                    None => format!("{id:?} holds [{}]", held.join(", ")),
+                }
+            })
+            .collect();
+        threads.join("; ")
+    }
+}
+
+lazy_static! {
+    static ref _GRAPH: Mutex<Graph> = Mutex::new(Graph::default());
+}
+
+fn graph() -> MutexGuard<'static, Graph> {
+    _GRAPH.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// 同步 `ContextOperator::push`；推入上下文时当前线程已获取对应的锁，因而不再等待任何键
+pub(crate) fn push(ctx: &Context) {
+    let id = thread::current().id();
+    let mut graph = graph();
+    graph.waiting.remove(&id);
+    graph.held.entry(id).or_default().push(ctx.clone());
+}
+
+// 同步 `ContextOperator::pop`
+pub(crate) fn pop() {
+    let id = thread::current().id();
+    let mut graph = graph();
+    if let Some(held) = graph.held.get_mut(&id) {
+        held.pop();
+        if held.is_empty() {
+            graph.held.remove(&id);
+        }
+    }
+}
+
+// 同步 `ContextOperator::remove`
+pub(crate) fn remove(ctx: &Context) {
+    let id = thread::current().id();
+    let mut graph = graph();
+    if let Some(held) = graph.held.get_mut(&id) {
+        if let Some(index) = held.iter().rposition(|x| x == ctx) {
+            held.remove(index);
+        }
+        if held.is_empty() {
+            graph.held.remove(&id);
+        }
+    }
+}
+
+// 记录当前线程将要等待的键，直至返回的守卫被销毁；如果等待该键会与其他线程形成环，则引发 panic
+pub(crate) fn wait(type_id: TypeId, type_name: &'static str, name: &str, write: bool) -> Waiting {
+    let id = thread::current().id();
+    let mut graph = graph();
+    graph.waiting.insert(
+        id,
+        Wanted {
+            key: intern(name),
+            type_id,
+            type_name,
+            write,
+        },
+    );
+    if let Some(cycle) = graph.cycle(id) {
+        let message = graph.describe(&cycle);
+        graph.waiting.remove(&id);
+        // 引发 panic 之前释放等待图的锁，其他线程仍可继续使用等待图
+        drop(graph);
+        thread_deadlock!("cycle between threads ({message})");
+    }
+    Waiting
+}
+
+// 当前线程正在等待的键的记录，被销毁时移除；守卫被销毁时当前线程必然不再等待该键
+#[must_use]
+pub(crate) struct Waiting;
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        graph().waiting.remove(&thread::current().id());
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use gom::*;
+
+// 统计当前线程的分配次数，从而不受并行执行的其他测试影响
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<R>(func: impl FnOnce() -> R) -> (usize, R) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let ret = func();
+    (ALLOCATIONS.with(Cell::get) - before, ret)
+}
+
+// 启用 `deadlock-detection` 特性时，每次访问都会在全局的等待图中记录上下文
+#[test]
+#[cfg(not(feature = "deadlock-detection"))]
+fn access_does_not_allocate() {
+    const KEY: &str = "alloc.counter";
+    const ROUNDS: usize = 1000;
+
+    Registry::register(KEY, 0u64).unwrap();
+    let handle = Registry::<u64>::handle(KEY).unwrap();
+    // 预热上下文访问栈等只在首次访问时分配的内存
+    Registry::<u64>::with(KEY, |v| *v);
+    Registry::<u64>::apply(KEY, |v| *v += 1);
+    handle.with(|v| *v);
+
+    let (with, sum) = allocations(|| {
+        (0..ROUNDS)
+            .map(|_| Registry::<u64>::with(KEY, |v| *v).unwrap())
+            .sum::<u64>()
+    });
+    assert_eq!(sum, ROUNDS as u64);
+    let (apply, _) = allocations(|| {
+        for _ in 0..ROUNDS {
+            Registry::<u64>::apply(KEY, |v| *v += 1).unwrap();
+        }
+    });
+    let (handle_with, _) = allocations(|| {
+        for _ in 0..ROUNDS {
+            handle.with(|v| *v).unwrap();
+        }
+    });
+    assert_eq!((with, apply, handle_with), (0, 0, 0));
+    assert_eq!(Registry::<u64>::get(KEY), Some(ROUNDS as u64 + 1));
+}
+
+#[test]
+fn keys_are_shared_across_types() {
+    const KEY: &str = "alloc.shared";
+
+    Registry::register(KEY, 1u8).unwrap();
+    // 其他类型使用相同的键时不再为键分配内存；预先注册其他键并预留容量，从而排除创建该类型对应的表以及扩容的分配
+    Registry::register("alloc.shared.other", 0u16).unwrap();
+    Registry::<u16>::reserve(16);
+    let (shared, _) = allocations(|| Registry::register(KEY, 2u16).unwrap());
+    // 仅为装箱的值与条目的共享数据分配内存
+    assert_eq!(shared, 2);
+}
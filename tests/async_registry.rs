@@ -0,0 +1,241 @@
+#![cfg(feature = "async")]
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use gom::*;
+
+const HOLD: Duration = Duration::from_millis(200);
+
+// 在另一个线程中持有指定键的写锁 `HOLD` 时间后将值加一，返回该线程的句柄；返回时写锁已被获取
+fn hold_write_lock(name: &'static str) -> thread::JoinHandle<()> {
+    let (locked, wait_locked) = mpsc::channel();
+    let holder = thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(AsyncRegistry::<u64>::apply(name, |v| {
+            locked.send(()).unwrap();
+            thread::sleep(HOLD);
+            *v += 1;
+        }));
+    });
+    wait_locked.recv().unwrap();
+    holder
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn waiting_does_not_block_the_executor() {
+    AsyncRegistry::register("async.busy", 0u64).await;
+    let holder = hold_write_lock("async.busy");
+
+    let done = Arc::new(AtomicBool::new(false));
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let ticker = tokio::spawn({
+        let done = Arc::clone(&done);
+        let ticks = Arc::clone(&ticks);
+        async move {
+            while !done.load(Ordering::SeqCst) {
+                ticks.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }
+    });
+
+    // 唯一的工作线程在等待读锁期间仍在执行其他任务
+    let value = AsyncRegistry::<u64>::with("async.busy", |v| *v).await;
+    done.store(true, Ordering::SeqCst);
+    ticker.await.unwrap();
+    holder.join().unwrap();
+
+    assert_eq!(value, Some(1));
+    assert!(ticks.load(Ordering::SeqCst) >= 5, "ticks: {ticks:?}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn contended_apply_loses_no_updates() {
+    const TASKS: u64 = 16;
+    const ROUNDS: u64 = 200;
+
+    AsyncRegistry::register("async.counter", 0u64).await;
+    let tasks: Vec<_> = (0..TASKS)
+        .map(|_| {
+            tokio::spawn(async {
+                for _ in 0..ROUNDS {
+                    AsyncRegistry::<u64>::apply("async.counter", |v| *v += 1)
+                        .await
+                        .unwrap();
+                    tokio::task::yield_now().await;
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(
+        AsyncRegistry::<u64>::with("async.counter", |v| *v).await,
+        Some(TASKS * ROUNDS)
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn remove_while_waiting() {
+    AsyncRegistry::register("async.removed", 0u64).await;
+    let holder = hold_write_lock("async.removed");
+
+    // 值的锁按先后顺序授予：先于 `remove` 排队的读取看到修改后的值，在此之后排队的读取看到值已被移除
+    let early = tokio::spawn(AsyncRegistry::<u64>::with("async.removed", |v| *v));
+    tokio::task::yield_now().await;
+    let remover = tokio::spawn(AsyncRegistry::<u64>::remove("async.removed"));
+    tokio::task::yield_now().await;
+    let late = tokio::spawn(AsyncRegistry::<u64>::with("async.removed", |v| *v));
+    tokio::task::yield_now().await;
+    holder.join().unwrap();
+
+    assert_eq!(early.await.unwrap(), Some(1));
+    assert_eq!(remover.await.unwrap(), Some(1));
+    assert_eq!(late.await.unwrap(), None);
+    assert!(!AsyncRegistry::<u64>::exists("async.removed").await);
+}
+
+#[tokio::test]
+async fn storage_is_separate_from_registry() {
+    Registry::register("async.shared", 1u8).unwrap();
+    AsyncRegistry::register("async.shared", 2u8).await;
+
+    assert_eq!(Registry::<u8>::get("async.shared"), Some(1));
+    assert_eq!(
+        AsyncRegistry::<u8>::with("async.shared", |v| *v).await,
+        Some(2)
+    );
+    assert_eq!(AsyncRegistry::<u8>::remove("async.shared").await, Some(2));
+    assert!(Registry::<u8>::exists("async.shared"));
+}
+
+#[tokio::test]
+async fn wait_for_resolves_after_registration() {
+    let loader = tokio::spawn(async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        AsyncRegistry::register("async.late", 7u32).await;
+    });
+    assert_eq!(AsyncRegistry::<u32>::waiters("async.late"), 0);
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        AsyncRegistry::<u32>::wait_for("async.late"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        AsyncRegistry::<u32>::with("async.late", |v| *v).await,
+        Some(7)
+    );
+    assert_eq!(AsyncRegistry::<u32>::waiters("async.late"), 0);
+    loader.await.unwrap();
+}
+
+#[tokio::test]
+async fn dropped_waiter_is_unregistered() {
+    let waiter = tokio::spawn(AsyncRegistry::<u32>::wait_for("async.never"));
+    let other = tokio::spawn(AsyncRegistry::<u32>::wait_for("async.never"));
+    while AsyncRegistry::<u32>::waiters("async.never") < 2 {
+        tokio::task::yield_now().await;
+    }
+    waiter.abort();
+    assert!(waiter.await.unwrap_err().is_cancelled());
+    assert_eq!(AsyncRegistry::<u32>::waiters("async.never"), 1);
+    other.abort();
+    assert!(other.await.unwrap_err().is_cancelled());
+    assert_eq!(AsyncRegistry::<u32>::waiters("async.never"), 0);
+
+    assert!(
+        !AsyncRegistry::<u32>::wait_for_timeout("async.never", Duration::from_millis(10)).await
+    );
+    assert_eq!(AsyncRegistry::<u32>::waiters("async.never"), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn wait_for_does_not_miss_racing_registration() {
+    for i in 0..200u32 {
+        let name = format!("async.race.{i}");
+        let waiter = tokio::spawn({
+            let name = name.clone();
+            async move { AsyncRegistry::<u32>::wait_for(&name).await }
+        });
+        AsyncRegistry::register(&name, i).await;
+        tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .unwrap_or_else(|_| panic!("waiter for `{name}` missed the registration"))
+            .unwrap();
+        assert_eq!(AsyncRegistry::<u32>::waiters(&name), 0);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn changed_since_does_not_miss_racing_mutation() {
+    AsyncRegistry::register("async.watched", 0u64).await;
+    for _ in 0..200 {
+        let seen = AsyncRegistry::<u64>::version("async.watched")
+            .await
+            .unwrap();
+        let writer = tokio::spawn(AsyncRegistry::<u64>::apply("async.watched", |v| *v += 1));
+        let event = tokio::time::timeout(
+            Duration::from_secs(5),
+            AsyncRegistry::<u64>::changed_since("async.watched", seen),
+        )
+        .await
+        .expect("the mutation was missed");
+        assert_eq!(event, ChangeEvent::Modified);
+        writer.await.unwrap().unwrap();
+    }
+    assert_eq!(
+        AsyncRegistry::<u64>::version("async.watched").await,
+        Some(200)
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn removal_resolves_pending_changed() {
+    AsyncRegistry::register("async.doomed", 0u64).await;
+    let watchers: Vec<_> = (0..3)
+        .map(|_| tokio::spawn(AsyncRegistry::<u64>::changed("async.doomed")))
+        .collect();
+    tokio::task::yield_now().await;
+    // 只读访问不算作修改
+    AsyncRegistry::<u64>::with("async.doomed", |v| *v).await;
+    assert_eq!(AsyncRegistry::<u64>::remove("async.doomed").await, Some(0));
+    for watcher in watchers {
+        assert_eq!(watcher.await.unwrap(), ChangeEvent::Removed);
+    }
+    assert_eq!(
+        AsyncRegistry::<u64>::changed("async.doomed").await,
+        ChangeEvent::Removed
+    );
+    assert_eq!(
+        AsyncRegistry::<u64>::changed_since("async.doomed", 0).await,
+        ChangeEvent::Removed
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn reregistration_counts_as_modification() {
+    AsyncRegistry::register("async.replaced", 0u64).await;
+    let watcher = tokio::spawn(AsyncRegistry::<u64>::changed("async.replaced"));
+    tokio::task::yield_now().await;
+    AsyncRegistry::register("async.replaced", 1u64).await;
+    assert_eq!(watcher.await.unwrap(), ChangeEvent::Modified);
+    assert_eq!(
+        AsyncRegistry::<u64>::version("async.replaced").await,
+        Some(1)
+    );
+    assert_eq!(
+        AsyncRegistry::<u64>::changed_since("async.replaced", 0).await,
+        ChangeEvent::Modified
+    );
+}
@@ -0,0 +1,86 @@
+use std::{
+    sync::{mpsc, Arc, Barrier},
+    thread,
+};
+
+use gom::*;
+
+#[test]
+fn apply_is_visible_to_other_threads() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Flag(u32);
+
+    Registry::register("cache.flag", Flag(0)).unwrap();
+    let (updated, seen) = (mpsc::channel(), mpsc::channel());
+    let reader = thread::spawn(move || {
+        assert_eq!(Registry::<Flag>::cached_get("cache.flag"), Some(Flag(0)));
+        seen.0.send(()).unwrap();
+        for expected in 1..=3 {
+            updated.1.recv().unwrap();
+            assert_eq!(
+                Registry::<Flag>::cached_get("cache.flag"),
+                Some(Flag(expected))
+            );
+            seen.0.send(()).unwrap();
+        }
+    });
+    seen.1.recv().unwrap();
+    // 原位修改、替换与重新注册都使其他线程的缓存失效
+    Registry::<Flag>::apply("cache.flag", |f| f.0 = 1).unwrap();
+    updated.0.send(()).unwrap();
+    seen.1.recv().unwrap();
+    Registry::<Flag>::replace("cache.flag", Flag(2)).unwrap();
+    updated.0.send(()).unwrap();
+    seen.1.recv().unwrap();
+    Registry::<Flag>::remove("cache.flag").unwrap();
+    Registry::register("cache.flag", Flag(3)).unwrap();
+    updated.0.send(()).unwrap();
+    reader.join().unwrap();
+}
+
+#[test]
+fn removal_and_rename_invalidate() {
+    Registry::register("cache.old", String::from("value")).unwrap();
+    assert_eq!(
+        Registry::<String>::cached_get("cache.old").as_deref(),
+        Some("value")
+    );
+    Registry::<String>::rename("cache.old", "cache.new").unwrap();
+    assert_eq!(Registry::<String>::cached_get("cache.old"), None);
+    assert_eq!(
+        Registry::<String>::cached_get("cache.new").as_deref(),
+        Some("value")
+    );
+    Registry::<String>::remove("cache.new").unwrap();
+    assert_eq!(Registry::<String>::cached_get("cache.new"), None);
+}
+
+#[test]
+fn concurrent_readers_observe_every_write() {
+    const READERS: usize = 4;
+    Registry::register("cache.counter", 0u64).unwrap();
+    let barrier = Arc::new(Barrier::new(READERS + 1));
+    let readers: Vec<_> = (0..READERS)
+        .map(|_| {
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                let mut last = 0;
+                barrier.wait();
+                while last < 100 {
+                    let value = Registry::<u64>::cached_get("cache.counter").unwrap();
+                    // 各线程观察到的值单调递增
+                    assert!(value >= last);
+                    last = value;
+                    thread::yield_now();
+                }
+            })
+        })
+        .collect();
+    barrier.wait();
+    for _ in 0..100 {
+        Registry::<u64>::apply("cache.counter", |v| *v += 1).unwrap();
+    }
+    for reader in readers {
+        reader.join().unwrap();
+    }
+}
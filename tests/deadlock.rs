@@ -0,0 +1,213 @@
+use gom::*;
+
+const ID1: &str = "id1";
+const ID2: &str = "id2";
+
+#[test]
+fn register_inside_with() {
+    Registry::register(ID1, 1).unwrap();
+    Registry::register(ID2, 2.0).unwrap();
+
+    let ret = Registry::<i32>::with(ID1, |v| {
+        assert_eq!(*v, 1);
+        Registry::register("id3", 2.9).unwrap();
+    });
+    assert_eq!(ret, Some(()));
+
+    assert_eq!(Registry::<f64>::with("id3", |f| *f), Some(2.9));
+}
+
+#[test]
+fn register_new_type_inside_with() {
+    struct Fresh(u8);
+    struct Other(&'static str);
+
+    Registry::register("outer", 1u16).unwrap();
+
+    // 两种类型在闭包执行前都还没有对应的表
+    let ret = Registry::<u16>::with("outer", |v| {
+        Registry::register("fresh", Fresh(*v as u8 + 2)).unwrap();
+        Registry::<Fresh>::with("fresh", |f| f.0)
+    });
+    assert_eq!(ret, Some(Some(3)));
+
+    let ret = Registry::<u16>::apply("outer", |v| {
+        *v += 1;
+        Registry::register("other", Other("new")).unwrap();
+    });
+    assert_eq!(ret, Some(()));
+    assert_eq!(Registry::<Other>::with("other", |o| o.0), Some("new"));
+    assert_eq!(Registry::<u16>::get("outer"), Some(2));
+}
+
+// 以 `cargo test --release --features deadlock-detection` 运行时确认发布模式下同样会检查死锁
+#[test]
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+#[should_panic(expected = "Thread deadlock!")]
+fn nested_apply_panics() {
+    struct Nested(u8);
+
+    Registry::register("nested", Nested(0)).unwrap();
+    Registry::<Nested>::with("nested", |_| {
+        Registry::<Nested>::apply("nested", |n| n.0 += 1);
+    });
+}
+
+#[test]
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+fn nested_apply_checked_would_deadlock() {
+    struct Checked(u8);
+
+    Registry::register("checked", Checked(0)).unwrap();
+    let ret = Registry::<Checked>::with("checked", |_| {
+        Registry::<Checked>::apply_checked("checked", |c| c.0 += 1)
+    });
+    assert!(matches!(
+        ret,
+        Some(Err(RegistryError::WouldDeadlock { .. }))
+    ));
+}
+
+// 探测函数在任何构建模式下都有效，回调可以据此拒绝会导致死锁的操作而不是引发 panic
+#[test]
+fn probe_before_nested_apply() {
+    struct Plugin(u32);
+
+    Registry::register("plugin", Plugin(0)).unwrap();
+    let callback = || {
+        if Registry::<Plugin>::would_deadlock_write("plugin") {
+            return Err("refused");
+        }
+        Registry::<Plugin>::apply("plugin", |p| p.0 += 1).ok_or("missing")
+    };
+    assert_eq!(
+        Registry::<Plugin>::with("plugin", |_| callback()),
+        Some(Err("refused"))
+    );
+    assert_eq!(callback(), Ok(()));
+    assert_eq!(Registry::<Plugin>::with("plugin", |p| p.0), Some(1));
+}
+
+#[test]
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+fn panic_message_names_keys_and_types() {
+    struct Outer(u8);
+    struct Inner;
+
+    Registry::register("outer", Outer(0)).unwrap();
+    Registry::register("inner", Inner).unwrap();
+    let payload = std::thread::spawn(|| {
+        Registry::<Outer>::apply("outer", |_| {
+            Registry::<Inner>::with("inner", |_| {
+                Registry::<Outer>::apply("outer", |o| o.0 += 1);
+            });
+        });
+    })
+    .join()
+    .unwrap_err();
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert!(message.starts_with("Thread deadlock!"));
+    assert!(message.contains(&format!(
+        "cannot write key `outer` of type `{}`",
+        std::any::type_name::<Outer>()
+    )));
+    assert!(message.contains(&format!(
+        "apply `outer` of type `{}` -> with `inner` of type `{}`",
+        std::any::type_name::<Outer>(),
+        std::any::type_name::<Inner>()
+    )));
+}
+
+// `init` 在键所在分片的写锁下执行；其中写入同一类型的其他键可能需要同一分片的锁，因而在调试模式下引发 panic，而不是视键的分布而挂起
+#[test]
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+#[should_panic(expected = "Thread deadlock!")]
+fn write_inside_get_or_register_init_panics() {
+    struct Lazy;
+
+    Registry::<Lazy>::get_or_register_with(
+        "lazy",
+        || {
+            Registry::<Lazy>::set("lazy.other", Lazy);
+            Lazy
+        },
+        |_| (),
+    );
+}
+
+#[test]
+#[cfg(any(debug_assertions, feature = "deadlock-detection"))]
+#[should_panic(expected = "Thread deadlock!")]
+fn write_inside_acquire_init_panics() {
+    struct Pooled;
+
+    Registry::<Pooled>::acquire("pooled", || {
+        Registry::<Pooled>::set("pooled.other", Pooled);
+        Pooled
+    });
+}
+
+// 闭包函数发生 panic 时上下文同样会被弹出，捕获该 panic 之后在同一线程中继续访问不会被误判为死锁
+#[test]
+fn access_after_caught_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    struct Caught(u32);
+
+    Registry::register("caught", Caught(0)).unwrap();
+    let ret = catch_unwind(AssertUnwindSafe(|| {
+        Registry::<Caught>::with("caught", |_| panic!("inside with"))
+    }));
+    assert!(ret.is_err());
+    assert_eq!(Registry::<Caught>::apply("caught", |c| c.0 += 1), Some(()));
+
+    let ret = catch_unwind(AssertUnwindSafe(|| {
+        Registry::<Caught>::apply("caught", |_| panic!("inside apply"))
+    }));
+    assert!(ret.is_err());
+    // 写锁在 panic 时中毒；清除中毒状态之后可以继续修改该值
+    Registry::<Caught>::clear_poison("caught");
+    assert_eq!(Registry::<Caught>::apply("caught", |c| c.0 += 1), Some(()));
+    assert_eq!(Registry::<Caught>::with("caught", |c| c.0), Some(2));
+}
+
+// 两个线程以相反的顺序修改两个键；后一个开始等待的线程检查出等待图中的环并引发 panic，而不是永远等待
+#[test]
+#[cfg(feature = "deadlock-detection")]
+fn cross_thread_cycle_panics() {
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    struct Account(u32);
+
+    Registry::register("account.a", Account(0)).unwrap();
+    Registry::register("account.b", Account(0)).unwrap();
+    let barrier = Arc::new(Barrier::new(2));
+    let spawn = |first: &'static str, second: &'static str| {
+        let barrier = Arc::clone(&barrier);
+        thread::spawn(move || {
+            Registry::<Account>::apply(first, |_| {
+                barrier.wait();
+                Registry::<Account>::apply(second, |account| account.0 += 1);
+            })
+        })
+    };
+    let (a, b) = (
+        spawn("account.a", "account.b"),
+        spawn("account.b", "account.a"),
+    );
+    let results = [a.join(), b.join()];
+
+    let messages: Vec<_> = results
+        .iter()
+        .filter_map(|r| r.as_ref().err())
+        .map(|payload| payload.downcast_ref::<String>().unwrap().clone())
+        .collect();
+    assert_eq!(messages.len(), 1);
+    let message = &messages[0];
+    assert!(message.starts_with("Thread deadlock! cycle between threads"));
+    assert!(message.contains("holds [apply `account.a`"));
+    assert!(message.contains("holds [apply `account.b`"));
+    assert!(message.contains("wants to write key `account.a`"));
+    assert!(message.contains("wants to write key `account.b`"));
+}
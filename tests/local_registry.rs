@@ -0,0 +1,64 @@
+use gom::*;
+
+// 以下各测试所用的类型在当前线程中从未被注册过
+
+#[test]
+fn exists_on_unused_type() {
+    struct Unused;
+    assert!(!LocalRegistry::<Unused>::exists("never"));
+}
+
+#[test]
+fn remove_on_unused_type() {
+    struct Unused;
+    assert!(LocalRegistry::<Unused>::remove("never").is_none());
+}
+
+#[test]
+fn with_on_unused_type() {
+    struct Unused(u8);
+    assert_eq!(LocalRegistry::<Unused>::with("never", |v| v.0), None);
+}
+
+#[test]
+fn apply_on_unused_type() {
+    struct Unused(u8);
+    assert_eq!(LocalRegistry::<Unused>::apply("never", |v| v.0 += 1), None);
+}
+
+#[test]
+fn replace_on_unused_type() {
+    struct Unused;
+    assert!(LocalRegistry::<Unused>::replace("never", Unused).is_none());
+    assert!(!LocalRegistry::<Unused>::exists("never"));
+}
+
+#[test]
+fn replace_missing_key_on_used_type() {
+    struct Used(u8);
+    LocalRegistry::register("local.used", Used(1));
+    assert!(LocalRegistry::<Used>::replace("local.missing", Used(2)).is_none());
+    assert!(!LocalRegistry::<Used>::exists("local.missing"));
+    assert_eq!(
+        LocalRegistry::<Used>::replace("local.used", Used(3)).map(|v| v.0),
+        Some(1)
+    );
+    assert_eq!(LocalRegistry::<Used>::with("local.used", |v| v.0), Some(3));
+}
+
+#[test]
+fn tables_are_per_thread() {
+    struct Value(u8);
+    LocalRegistry::register("local.value", Value(1));
+    assert!(LocalRegistry::<Value>::exists("local.value"));
+    std::thread::spawn(|| {
+        assert!(!LocalRegistry::<Value>::exists("local.value"));
+        assert_eq!(LocalRegistry::<Value>::with("local.value", |v| v.0), None);
+    })
+    .join()
+    .unwrap();
+    assert_eq!(
+        LocalRegistry::<Value>::with("local.value", |v| v.0),
+        Some(1)
+    );
+}
@@ -0,0 +1,104 @@
+use std::{sync::mpsc, thread, time::Duration};
+
+// 在 `apply` 闭包中注册的同类型的键的数量；键按哈希值分布在 16 个分片中，其中几乎必然有与被访问的键位于同一分片的键
+const KEYS: usize = 256;
+
+use gom::*;
+
+// 一个线程在 `apply` 闭包中注册同类型的其他键（持有值的锁，再获取表的锁），另一个线程此时访问同一个键；
+// 后者在获取值的锁之前需要释放表的锁，否则两者相互等待
+fn assert_no_deadlock<T: 'static + Send + Sync + Default>(name: &'static str, access: fn(&str)) {
+    let (started, wait_started) = mpsc::channel();
+    let (done, finished) = mpsc::channel();
+    let applier = {
+        let done = done.clone();
+        thread::spawn(move || {
+            Registry::<T>::apply(name, |_| {
+                started.send(()).unwrap();
+                // 等待另一个线程开始等待该值的锁
+                thread::sleep(Duration::from_millis(50));
+                for i in 0..KEYS {
+                    Registry::<T>::set(&format!("{name}.{i}"), T::default());
+                }
+            })
+            .unwrap();
+            done.send(()).unwrap();
+        })
+    };
+    wait_started.recv().unwrap();
+    let accessor = thread::spawn(move || {
+        access(name);
+        done.send(()).unwrap();
+    });
+    for _ in 0..2 {
+        finished
+            .recv_timeout(Duration::from_secs(10))
+            .expect("deadlocked");
+    }
+    applier.join().unwrap();
+    accessor.join().unwrap();
+}
+
+#[test]
+fn entry_waits_for_value_without_holding_table_lock() {
+    Registry::register("entry", 0u64).unwrap();
+    assert_no_deadlock::<u64>("entry", |name| {
+        Registry::<u64>::entry(name, |e| *e.or_insert(0) += 1).unwrap();
+    });
+    assert_eq!(Registry::<u64>::get("entry"), Some(1));
+}
+
+#[test]
+fn transform_waits_for_value_without_holding_table_lock() {
+    Registry::register("transform", 0u32).unwrap();
+    assert_no_deadlock::<u32>("transform", |name| {
+        transform::<u32, u32, _>(name, |v| v + 1).unwrap();
+    });
+    assert_eq!(Registry::<u32>::get("transform"), Some(1));
+}
+
+#[test]
+fn vacant_entry_does_not_hold_registry_lock() {
+    struct Vacant(u32);
+    struct Created(u32);
+    struct Other;
+
+    let (started, wait_started) = mpsc::channel();
+    let (done, finished) = mpsc::channel();
+    let entry = {
+        let done = done.clone();
+        thread::spawn(move || {
+            // 键不存在时闭包持有该键所在分片的写锁，但不应持有注册表的锁
+            Registry::<Vacant>::entry("lock_order.vacant", |e| {
+                started.send(()).unwrap();
+                // 等待另一个线程开始获取注册表的写锁
+                thread::sleep(Duration::from_millis(50));
+                assert!(!Registry::<Other>::exists("lock_order.other"));
+                e.or_insert(Vacant(1));
+            })
+            .unwrap();
+            done.send(()).unwrap();
+        })
+    };
+    wait_started.recv().unwrap();
+    // 注册新类型的值需要获取注册表的写锁以创建该类型对应的表
+    let register = thread::spawn(move || {
+        Registry::register("lock_order.created", Created(2)).unwrap();
+        done.send(()).unwrap();
+    });
+    for _ in 0..2 {
+        finished
+            .recv_timeout(Duration::from_secs(10))
+            .expect("deadlocked");
+    }
+    entry.join().unwrap();
+    register.join().unwrap();
+    assert_eq!(
+        Registry::<Vacant>::with("lock_order.vacant", |v| v.0),
+        Some(1)
+    );
+    assert_eq!(
+        Registry::<Created>::with("lock_order.created", |v| v.0),
+        Some(2)
+    );
+}
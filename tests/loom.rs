@@ -0,0 +1,61 @@
+//! 以 `loom` 检查注册表的加锁协议
+//!
+//! 运行：`RUSTFLAGS="--cfg loom" cargo test --release --test loom`
+#![cfg(loom)]
+
+use gom::Registry;
+use loom::thread;
+
+// 限制抢占次数，否则注册表内部的锁使状态空间过大而无法在合理时间内穷举
+fn model(f: impl Fn() + Sync + Send + 'static) {
+    let mut builder = loom::model::Builder::new();
+    builder.preemption_bound = Some(2);
+    builder.check(f);
+}
+
+// 同一个键被并发地替换与移除时，每个值要么仍在注册表中，要么被 `remove` 取回，不会丢失
+#[test]
+fn register_remove_on_one_key() {
+    model(|| {
+        Registry::register("loom.key", 1u32).unwrap();
+        let remover = thread::spawn(|| Registry::<u32>::remove("loom.key"));
+        Registry::register("loom.key", 2u32).unwrap();
+
+        let removed = remover.join().unwrap();
+        let remaining = Registry::<u32>::get("loom.key");
+        assert!(
+            matches!((removed, remaining), (Some(1), Some(2)) | (Some(2), None)),
+            "removed {removed:?}, remaining {remaining:?}"
+        );
+    });
+}
+
+// 读取与移除并发时，读取到的要么是旧值，要么是不存在
+#[test]
+fn with_remove_on_one_key() {
+    model(|| {
+        Registry::register("loom.key", 1u32).unwrap();
+        let reader = thread::spawn(|| Registry::<u32>::with("loom.key", |v| *v));
+        assert_eq!(Registry::<u32>::remove("loom.key"), Some(1));
+        let read = reader.join().unwrap();
+        assert!(matches!(read, None | Some(1)), "read {read:?}");
+    });
+}
+
+// 两个线程以相反的顺序依次修改两个键时，两次修改都不会丢失
+#[test]
+fn concurrent_apply_on_two_keys() {
+    model(|| {
+        Registry::register("loom.a", 0u32).unwrap();
+        Registry::register("loom.b", 0u32).unwrap();
+        let other = thread::spawn(|| {
+            Registry::<u32>::apply("loom.b", |b| *b += 1).unwrap();
+            Registry::<u32>::apply("loom.a", |a| *a += 1).unwrap();
+        });
+        Registry::<u32>::apply("loom.a", |a| *a += 1).unwrap();
+        Registry::<u32>::apply("loom.b", |b| *b += 1).unwrap();
+        other.join().unwrap();
+        assert_eq!(Registry::<u32>::get("loom.a"), Some(2));
+        assert_eq!(Registry::<u32>::get("loom.b"), Some(2));
+    });
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "metrics")]
+
+use std::{
+    sync::{Arc, Barrier},
+    thread,
+    time::Duration,
+};
+
+use gom::*;
+
+const HOLD: Duration = Duration::from_millis(100);
+
+#[test]
+fn slow_apply_counts_as_contention() {
+    struct Hotspot(u64);
+    struct Quiet(u64);
+
+    Registry::register("metrics.hotspot", Hotspot(0)).unwrap();
+    Registry::register("metrics.quiet", Quiet(0)).unwrap();
+    let barrier = Arc::new(Barrier::new(2));
+    let holder = {
+        let barrier = Arc::clone(&barrier);
+        thread::spawn(move || {
+            Registry::<Hotspot>::apply("metrics.hotspot", |v| {
+                barrier.wait();
+                thread::sleep(HOLD);
+                v.0 += 1;
+            })
+            .unwrap();
+        })
+    };
+    // 持有者已获取写锁之后再访问，因而必然需要阻塞等待
+    barrier.wait();
+    Registry::<Hotspot>::apply("metrics.hotspot", |v| v.0 += 1).unwrap();
+    Registry::<Quiet>::apply("metrics.quiet", |v| v.0 += 1).unwrap();
+    holder.join().unwrap();
+
+    let find = |type_name: &str| {
+        metrics::snapshot()
+            .into_iter()
+            .find(|m| m.type_name == type_name)
+            .unwrap()
+    };
+    let hotspot = find(std::any::type_name::<Hotspot>());
+    assert_eq!(hotspot.acquisitions, 2);
+    assert_eq!(hotspot.blocked, 1);
+    assert!(hotspot.blocked_time >= HOLD / 2, "{hotspot:?}");
+    let quiet = find(std::any::type_name::<Quiet>());
+    assert_eq!((quiet.acquisitions, quiet.blocked), (1, 0));
+}
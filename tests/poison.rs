@@ -0,0 +1,29 @@
+// `parking_lot` 的锁不会中毒
+#![cfg(not(feature = "parking_lot"))]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use gom::*;
+
+// 键不存在时，`entry` 的闭包函数执行期间持有该键所在分片的写锁，在其中发生 panic 会使该分片的锁中毒
+fn poison_shard<T: 'static + Send + Sync>(name: &str) {
+    let ret = catch_unwind(AssertUnwindSafe(|| {
+        Registry::<T>::entry(name, |_| panic!("inside entry"))
+    }));
+    assert!(ret.is_err());
+}
+
+#[test]
+fn set_on_poisoned_shard_returns_value() {
+    #[derive(Debug)]
+    struct Poisoned(u32);
+
+    poison_shard::<Poisoned>("poisoned.key");
+
+    let err = Registry::try_set("poisoned.key", Poisoned(2)).unwrap_err();
+    assert_eq!(err.kind(), RegisterErrorKind::Poisoned);
+    assert_eq!(err.into_value().0, 2);
+    // 失败的 `set` 不会被误报为新建了该键
+    assert!(Registry::<Poisoned>::set("poisoned.key", Poisoned(3)).is_none());
+    assert!(Registry::<Poisoned>::replace("poisoned.key", Poisoned(4)).is_none());
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "arc-swap")]
+
+use std::{any::TypeId, sync::mpsc, thread, time::Duration};
+
+use gom::*;
+
+// 在另一个线程中以 `apply` 持有指定键的修改互斥锁，直至 `release` 被发送或被销毁；返回时互斥锁已被获取
+fn hold_writer(name: &'static str) -> (mpsc::Sender<()>, thread::JoinHandle<()>) {
+    let (locked, wait_locked) = mpsc::channel();
+    let (release, wait_release) = mpsc::channel::<()>();
+    let holder = thread::spawn(move || {
+        Registry::<u32>::apply(name, |v| {
+            locked.send(()).unwrap();
+            let _ = wait_release.recv_timeout(Duration::from_secs(10));
+            *v += 1;
+        })
+        .unwrap();
+    });
+    wait_locked.recv().unwrap();
+    (release, holder)
+}
+
+#[test]
+fn direct_accessors_see_published_value() {
+    Registry::register_read_mostly("rm.direct", 1u32).unwrap();
+
+    let handle = Registry::<u32>::handle("rm.direct").unwrap();
+    assert_eq!(
+        handle.apply(|v| {
+            *v += 1;
+            *v
+        }),
+        Some(2)
+    );
+    assert_eq!(handle.with(|v| *v), Some(2));
+
+    let slot = Registry::<u32>::slot_of("rm.direct").unwrap();
+    assert_eq!(
+        Registry::apply_slot(slot, |v: &mut u32| {
+            *v += 1;
+            *v
+        }),
+        Some(3)
+    );
+    assert_eq!(Registry::with_slot(slot, |v: &u32| *v), Some(3));
+
+    let hot = Registry::<u32>::hot_key("rm.direct");
+    assert_eq!(
+        hot.apply(|v| {
+            *v += 1;
+            *v
+        }),
+        Some(4)
+    );
+    assert_eq!(hot.with(|v| *v), Some(4));
+
+    assert_eq!(
+        Registry::<u32>::try_apply("rm.direct", |v| {
+            *v += 1;
+            *v
+        }),
+        Ok(5)
+    );
+    assert_eq!(Registry::<u32>::try_with("rm.direct", |v| *v), Ok(5));
+    let timeout = Duration::from_millis(100);
+    assert_eq!(
+        Registry::<u32>::apply_timeout("rm.direct", timeout, |v| {
+            *v += 1;
+            *v
+        }),
+        Ok(6)
+    );
+    assert_eq!(
+        Registry::<u32>::with_timeout("rm.direct", timeout, |v| *v),
+        Ok(6)
+    );
+
+    let version = Registry::<u32>::with_versioned("rm.direct", |_, version| version).unwrap();
+    Registry::<u32>::apply("rm.direct", |v| *v += 1);
+    assert_eq!(
+        Registry::<u32>::with_versioned("rm.direct", |v, current| (*v, current > version)),
+        Some((7, true))
+    );
+    assert_eq!(Registry::<u32>::with_or("rm.direct", &0, |v| *v), 7);
+
+    {
+        let mut guard = Registry::<u32>::write_guard("rm.direct").unwrap();
+        *guard += 1;
+        // 修改在守卫被销毁时才发布
+        assert_eq!(Registry::<u32>::get("rm.direct"), Some(7));
+    }
+    assert_eq!(*Registry::<u32>::read_guard("rm.direct").unwrap(), 8);
+
+    assert_eq!(
+        Registry::<u32>::compare_and_swap("rm.direct", &8, 9),
+        Ok(())
+    );
+    assert_eq!(
+        Registry::<u32>::compare_and_swap("rm.direct", &8, 10),
+        Err(CasError::Mismatch {
+            current: 9,
+            new: 10
+        })
+    );
+    assert_eq!(
+        Registry::<u32>::fetch_update("rm.direct", |v| Some(v + 1)),
+        Some(Ok(9))
+    );
+    assert_eq!(Registry::<u32>::get("rm.direct"), Some(10));
+
+    Registry::<u32>::copy("rm.direct", "rm.direct.copy", false).unwrap();
+    assert_eq!(Registry::<u32>::get("rm.direct.copy"), Some(10));
+    assert!(Registry::<u32>::snapshot().contains(&("rm.direct".to_string(), 10)));
+
+    let mut visited = Vec::new();
+    visit_any("rm.direct", |type_id, _, value| {
+        visited.push((type_id, value.downcast_ref::<u32>().copied()));
+    });
+    assert_eq!(visited, vec![(TypeId::of::<u32>(), Some(10))]);
+
+    assert_eq!(
+        Transaction::new()
+            .write::<u32>("rm.direct")
+            .run(|txn| *txn.get_mut::<u32>("rm.direct").unwrap() += 1),
+        Some(())
+    );
+    assert_eq!(Registry::<u32>::get("rm.direct"), Some(11));
+}
+
+#[test]
+fn entry_publishes_only_when_modified() {
+    Registry::register_read_mostly("rm.entry", 1u64).unwrap();
+    let version = Registry::<u64>::version("rm.entry");
+    assert_eq!(
+        Registry::<u64>::entry("rm.entry", |e| *e.or_insert(0)),
+        Some(1)
+    );
+    assert_eq!(Registry::<u64>::version("rm.entry"), version);
+
+    Registry::<u64>::entry("rm.entry", |e| *e.and_modify(|v| *v += 1).or_insert(0) += 1);
+    assert_eq!(Registry::<u64>::get("rm.entry"), Some(3));
+    assert_ne!(Registry::<u64>::version("rm.entry"), version);
+}
+
+#[test]
+fn try_replace_reports_contention_with_writer() {
+    Registry::register_read_mostly("rm.try_replace", 0u32).unwrap();
+    let (release, holder) = hold_writer("rm.try_replace");
+    let err = Registry::<u32>::try_replace("rm.try_replace", 5).unwrap_err();
+    assert_eq!(err.kind(), TryAccessError::WouldBlock);
+    assert_eq!(err.into_value(), 5);
+    assert_eq!(
+        Registry::<u32>::try_apply("rm.try_replace", |v| *v),
+        Err(TryAccessError::WouldBlock)
+    );
+    release.send(()).unwrap();
+    holder.join().unwrap();
+    assert_eq!(Registry::<u32>::try_replace("rm.try_replace", 5), Ok(1));
+}
+
+#[test]
+fn override_waits_for_writer() {
+    Registry::register_read_mostly("rm.override", 0u32).unwrap();
+    let (release, holder) = hold_writer("rm.override");
+    let overrider = thread::spawn(|| {
+        Registry::with_override("rm.override", 10u32, || Registry::<u32>::get("rm.override"))
+            .unwrap()
+    });
+    thread::sleep(Duration::from_millis(50));
+    release.send(()).unwrap();
+    holder.join().unwrap();
+    // 覆盖在修改完成之后进行，因而修改不会被覆盖与恢复丢弃
+    assert_eq!(overrider.join().unwrap(), Some(10));
+    assert_eq!(Registry::<u32>::get("rm.override"), Some(1));
+}
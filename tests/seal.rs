@@ -0,0 +1,116 @@
+use gom::*;
+
+// 各测试在同一进程中并发执行，因而分别封存不同的前缀
+
+#[test]
+fn register_many_rejects_sealed_keys() {
+    seal_prefix(".many");
+    let report = Registry::register_many(
+        vec![
+            ("many.free".to_string(), 1u32),
+            (".many.y".to_string(), 2u32),
+        ],
+        ConflictPolicy::Overwrite,
+    );
+    assert!(report.inserted.is_empty());
+    assert_eq!(report.skipped, vec![".many.y"]);
+    assert_eq!(report.remainder.len(), 2);
+    assert!(!Registry::<u32>::exists(".many.y"));
+    assert!(!Registry::<u32>::exists("many.free"));
+}
+
+#[test]
+fn extend_skips_sealed_keys() {
+    seal_prefix(".ext");
+    let report =
+        Registry::<u32>::extend([(".ext.z".to_string(), 1u32), (".extra.z".to_string(), 2u32)]);
+    assert_eq!(
+        report,
+        ExtendReport {
+            inserted: 1,
+            replaced: 0,
+            sealed: 1
+        }
+    );
+    assert!(!Registry::<u32>::exists(".ext.z"));
+    // 只匹配完整的段
+    assert!(Registry::<u32>::exists(".extra.z"));
+}
+
+#[test]
+fn rename_into_sealed_prefix() {
+    Registry::register("ren.src", 1u32).unwrap();
+    seal_prefix(".ren");
+    assert_eq!(
+        Registry::<u32>::rename("ren.src", ".ren.dst"),
+        Err(RenameError::SealedNamespace)
+    );
+    assert!(Registry::<u32>::exists("ren.src"));
+    assert!(!Registry::<u32>::exists(".ren.dst"));
+}
+
+#[test]
+fn get_or_register_with_does_not_create_sealed_key() {
+    Registry::register(".gor.a", 1u32).unwrap();
+    seal_prefix(".gor");
+    let created = Registry::<u32>::get_or_register_with(
+        ".gor.b",
+        || panic!("init must not run for a sealed key"),
+        |v| *v,
+    );
+    assert_eq!(created, None);
+    assert!(!Registry::<u32>::exists(".gor.b"));
+    // 已存在的键仍可访问
+    assert_eq!(
+        Registry::<u32>::get_or_register_with(".gor.a", || 0, |v| *v),
+        Some(1)
+    );
+}
+
+#[test]
+fn entry_does_not_create_sealed_key() {
+    Registry::register(".ent.a", 1u32).unwrap();
+    seal_prefix(".ent");
+    assert_eq!(Registry::<u32>::entry(".ent.b", |e| *e.or_insert(2)), None);
+    assert!(!Registry::<u32>::exists(".ent.b"));
+    assert_eq!(
+        Registry::<u32>::entry(".ent.a", |e| *e.and_modify(|v| *v += 1).or_insert(0)),
+        Some(2)
+    );
+}
+
+#[test]
+fn rename_prefix_into_sealed_prefix() {
+    Registry::register(".rp.src.a", 1u32).unwrap();
+    seal_prefix(".rp.dst");
+    assert_eq!(
+        Registry::<u32>::rename_prefix(".rp.src", ".rp.dst"),
+        Err(RenamePrefixError::SealedNamespace(".rp.dst.a".to_string()))
+    );
+    assert!(Registry::<u32>::exists(".rp.src.a"));
+    assert!(!Registry::<u32>::exists(".rp.dst.a"));
+}
+
+#[test]
+fn rename_prefix_all_into_sealed_prefix() {
+    Registry::register(".rpa.src.a", 1u32).unwrap();
+    Registry::register(".rpa.src.a", String::from("a")).unwrap();
+    seal_prefix(".rpa.dst");
+    assert_eq!(
+        rename_prefix_all(".rpa.src", ".rpa.dst"),
+        Err(RenamePrefixError::SealedNamespace(".rpa.dst.a".to_string()))
+    );
+    assert!(Registry::<u32>::exists(".rpa.src.a"));
+    assert!(Registry::<String>::exists(".rpa.src.a"));
+    assert!(!Registry::<String>::exists(".rpa.dst.a"));
+}
+
+#[test]
+fn set_reports_sealed_namespace() {
+    seal_prefix(".set");
+    assert_eq!(Registry::<u32>::set(".set.a", 1), None);
+    let err = Registry::<u32>::try_set(".set.a", 2).unwrap_err();
+    assert_eq!(err.kind(), RegisterErrorKind::SealedNamespace);
+    assert_eq!(err.into_value(), 2);
+    assert!(!Registry::<u32>::exists(".set.a"));
+}
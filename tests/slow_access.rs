@@ -0,0 +1,80 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Barrier, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use gom::*;
+
+const THRESHOLD: Duration = Duration::from_millis(30);
+const SLOW: Duration = Duration::from_millis(80);
+
+static FIRED: AtomicUsize = AtomicUsize::new(0);
+static SEEN: Mutex<Vec<(SlowAccessKind, String, &'static str)>> = Mutex::new(Vec::new());
+
+fn record(info: &SlowAccessInfo) {
+    assert!(info.elapsed > THRESHOLD);
+    // 回调函数在锁释放之后调用，再次访问同一个键不会死锁
+    assert!(Registry::<u32>::with(info.key, |_| ()).is_some());
+    FIRED.fetch_add(1, Ordering::SeqCst);
+    SEEN.lock()
+        .unwrap()
+        .push((info.kind, info.key.to_string(), info.type_name));
+}
+
+#[test]
+fn hook_reports_slow_closures_and_waits() {
+    Registry::register("slow.closure", 0u32).unwrap();
+    Registry::register("slow.contended", 0u32).unwrap();
+    set_slow_access_hook(THRESHOLD, record);
+
+    Registry::<u32>::apply("slow.closure", |v| {
+        thread::sleep(SLOW);
+        *v += 1;
+    })
+    .unwrap();
+    Registry::<u32>::apply("slow.closure", |v| *v += 1).unwrap();
+    assert_eq!(FIRED.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        SEEN.lock().unwrap()[..],
+        [(
+            SlowAccessKind::Apply,
+            String::from("slow.closure"),
+            std::any::type_name::<u32>()
+        )]
+    );
+
+    // 另一个线程的慢速闭包同时使当前线程等待读锁
+    let barrier = Arc::new(Barrier::new(2));
+    let holder = {
+        let barrier = Arc::clone(&barrier);
+        thread::spawn(move || {
+            Registry::<u32>::apply("slow.contended", |_| {
+                barrier.wait();
+                thread::sleep(SLOW);
+            })
+        })
+    };
+    barrier.wait();
+    Registry::<u32>::with("slow.contended", |_| ()).unwrap();
+    holder.join().unwrap().unwrap();
+    clear_slow_access_hook();
+    Registry::<u32>::apply("slow.closure", |_| thread::sleep(SLOW)).unwrap();
+
+    let mut seen: Vec<_> = SEEN.lock().unwrap()[1..]
+        .iter()
+        .map(|(kind, key, _)| (*kind, key.clone()))
+        .collect();
+    seen.sort_by_key(|(kind, _)| *kind as u8);
+    assert_eq!(
+        seen,
+        [
+            (SlowAccessKind::ReadWait, String::from("slow.contended")),
+            (SlowAccessKind::Apply, String::from("slow.contended")),
+        ]
+    );
+    assert_eq!(FIRED.load(Ordering::SeqCst), 3);
+}
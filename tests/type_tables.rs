@@ -0,0 +1,99 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use gom::*;
+
+#[test]
+fn cached_table_follows_removal() {
+    struct Config(u32);
+
+    Registry::register("tables.config", Config(1)).unwrap();
+    let (step, done) = (mpsc::channel::<()>(), mpsc::channel());
+    let reader = thread::spawn(move || {
+        // 首次访问后当前线程缓存了该类型对应的表
+        done.0
+            .send(Registry::<Config>::with("tables.config", |c| c.0))
+            .unwrap();
+        for _ in 0..2 {
+            step.1.recv().unwrap();
+            done.0
+                .send(Registry::<Config>::with("tables.config", |c| c.0))
+                .unwrap();
+        }
+    });
+    assert_eq!(done.1.recv().unwrap(), Some(1));
+    // 整个表被取出时，其他线程仍缓存着该表
+    assert_eq!(Registry::<Config>::take_map().len(), 1);
+    step.0.send(()).unwrap();
+    assert_eq!(done.1.recv().unwrap(), None);
+    Registry::register("tables.config", Config(2)).unwrap();
+    step.0.send(()).unwrap();
+    assert_eq!(done.1.recv().unwrap(), Some(2));
+    reader.join().unwrap();
+}
+
+#[test]
+fn bootstrap_is_atomic_for_readers() {
+    const KEYS: u32 = 20;
+    Registry::register("tables.boot.ready", 0u32).unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let reader = {
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            let mut seen_first = false;
+            while !stop.load(Ordering::Relaxed) {
+                let first = Registry::<u32>::with("tables.boot.0", |v| *v);
+                let last = Registry::<u32>::with(&format!("tables.boot.{}", KEYS - 1), |v| *v);
+                // 观察到 `bootstrap` 注册的第一个键后，其注册的其他键必然都已可见
+                if first.is_some() {
+                    assert_eq!(last, Some(KEYS - 1));
+                    seen_first = true;
+                }
+                thread::yield_now();
+            }
+            seen_first
+        })
+    };
+    thread::sleep(Duration::from_millis(20));
+    bootstrap(|b| {
+        for i in 0..KEYS {
+            b.register(&format!("tables.boot.{}", i), i).unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+    })
+    .unwrap();
+    thread::sleep(Duration::from_millis(20));
+    stop.store(true, Ordering::Relaxed);
+    assert!(reader.join().unwrap());
+}
+
+#[test]
+fn register_survives_concurrent_table_removal() {
+    struct Item(u32);
+
+    let stop = Arc::new(AtomicBool::new(false));
+    // 反复移除该类型的最后一个键，从而其对应的表被反复移除与重新创建
+    let churn = {
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                Registry::register("tables.churn", Item(0)).unwrap();
+                Registry::<Item>::remove("tables.churn");
+            }
+        })
+    };
+    for i in 0..2000 {
+        let name = format!("tables.item.{}", i);
+        Registry::register(&name, Item(i)).unwrap();
+        assert_eq!(Registry::<Item>::with(&name, |item| item.0), Some(i));
+        Registry::<Item>::remove(&name);
+    }
+    stop.store(true, Ordering::Relaxed);
+    churn.join().unwrap();
+}
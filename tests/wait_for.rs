@@ -0,0 +1,74 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use gom::*;
+
+const DELAY: Duration = Duration::from_millis(50);
+
+#[test]
+fn wakes_when_registered_by_another_thread() {
+    struct Config(&'static str);
+
+    let start = Instant::now();
+    let loader = thread::spawn(|| {
+        thread::sleep(DELAY);
+        // 其他类型与其他键的注册不会使等待者提前返回
+        Registry::register(".app.config", 0u8).unwrap();
+        Registry::register(".app.other", Config("other")).unwrap();
+        thread::sleep(DELAY);
+        Registry::register(".app.config", Config("loaded")).unwrap();
+    });
+    assert!(Registry::<Config>::wait_for(".app.config", None));
+    let waited = start.elapsed();
+    assert!(waited >= DELAY * 2, "{waited:?}");
+    assert!(
+        waited < DELAY * 2 + Duration::from_millis(500),
+        "{waited:?}"
+    );
+    assert_eq!(
+        Registry::<Config>::with(".app.config", |c| c.0),
+        Some("loaded")
+    );
+    loader.join().unwrap();
+}
+
+#[test]
+fn wakes_when_set_or_renamed() {
+    let setter = thread::spawn(|| {
+        thread::sleep(DELAY);
+        Registry::<u32>::set("wait.set", 1);
+        thread::sleep(DELAY);
+        Registry::register("wait.staging", 2u32).unwrap();
+        Registry::<u32>::rename("wait.staging", "wait.renamed").unwrap();
+    });
+    let timeout = Some(Duration::from_secs(5));
+    assert_eq!(
+        Registry::<u32>::wait_for_with("wait.set", timeout, |v| *v),
+        Some(1)
+    );
+    assert_eq!(
+        Registry::<u32>::wait_for_with("wait.renamed", timeout, |v| *v),
+        Some(2)
+    );
+    setter.join().unwrap();
+}
+
+#[test]
+fn times_out() {
+    let start = Instant::now();
+    assert!(!Registry::<u64>::wait_for("wait.never", Some(DELAY)));
+    assert!(start.elapsed() >= DELAY);
+    assert_eq!(
+        Registry::<u64>::wait_for_with("wait.never", Some(DELAY), |v| *v),
+        None
+    );
+
+    // 已存在的键立即返回，即使等待时间为零
+    Registry::register("wait.present", 7u64).unwrap();
+    assert!(Registry::<u64>::wait_for(
+        "wait.present",
+        Some(Duration::ZERO)
+    ));
+}
@@ -0,0 +1,59 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Barrier,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use gom::*;
+
+const READERS: usize = 8;
+const WRITES: usize = 50;
+
+// 8 个线程持续读取同一个启用了优先写入的键，同时另一个线程反复修改该键，返回单次写入的最长等待时间
+fn max_write_latency(key: &'static str) -> Duration {
+    Registry::register(key, 0u64).unwrap();
+    Registry::<u64>::set_write_priority(key, true).unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let barrier = Arc::new(Barrier::new(READERS + 1));
+    let readers: Vec<_> = (0..READERS)
+        .map(|_| {
+            let (stop, barrier) = (Arc::clone(&stop), Arc::clone(&barrier));
+            thread::spawn(move || {
+                barrier.wait();
+                while !stop.load(Ordering::Relaxed) {
+                    // 每次读取都持有读锁一小段时间，使各读取者的读锁相互重叠
+                    Registry::<u64>::with(key, |_| {
+                        let start = Instant::now();
+                        while start.elapsed() < Duration::from_micros(50) {
+                            std::hint::spin_loop();
+                        }
+                    });
+                }
+            })
+        })
+        .collect();
+    barrier.wait();
+    let mut max = Duration::ZERO;
+    for _ in 0..WRITES {
+        let start = Instant::now();
+        Registry::<u64>::apply(key, |v| *v += 1).unwrap();
+        max = max.max(start.elapsed());
+        thread::sleep(Duration::from_millis(1));
+    }
+    stop.store(true, Ordering::Relaxed);
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    assert_eq!(Registry::<u64>::get(key), Some(WRITES as u64));
+    max
+}
+
+#[test]
+fn writer_latency_is_bounded() {
+    let latency = max_write_latency("priority.on");
+    // 读取者至多让出 10 毫秒，写入者只需等待已持有读锁的读取者释放
+    assert!(latency < Duration::from_millis(250), "{latency:?}");
+}